@@ -0,0 +1,40 @@
+-- Resultados mensuales de backtest.
+-- Una fila por configuración de estrategia, mercado y `timerange`.
+-- freqdash agrupa estas filas por configuración (ver `StrategyKey` en
+-- src/backtest/logic.rs) para calcular el resumen de cada una.
+CREATE TABLE IF NOT EXISTS backtest (
+    id                              BIGSERIAL PRIMARY KEY,
+
+    -- Mercado
+    exchange                        TEXT NOT NULL,      -- en mayúsculas: BINANCE
+    stake_currency                  TEXT NOT NULL,      -- en mayúsculas: USDT
+    pairlist                        TEXT NOT NULL,      -- bases separadas por comas: BTC,ETH
+    timerange                       TEXT NOT NULL,      -- YYYYMMDD-YYYYMMDD
+
+    -- Configuración de la estrategia
+    strategy                        TEXT NOT NULL,
+    timeframe                       TEXT NOT NULL,      -- 5m, 1h...
+    minimal_roi                     TEXT NOT NULL,      -- JSON {"minutos": ratio}
+    stoploss                        TEXT NOT NULL,      -- ratio negativo: -0.1
+    max_open_trades                 INTEGER NOT NULL,   -- -1 = sin límite
+    trailing_stop                   BOOLEAN NOT NULL,
+    trailing_stop_positive          DOUBLE PRECISION NOT NULL DEFAULT 0,
+    trailing_stop_positive_offset   DOUBLE PRECISION NOT NULL DEFAULT 0,
+    trailing_only_offset_is_reached BOOLEAN NOT NULL,
+    entry_pricing                   TEXT NOT NULL,      -- price_side de entry_pricing
+    exit_pricing                    TEXT NOT NULL,      -- price_side de exit_pricing
+    check_depth_of_market_enable    BOOLEAN NOT NULL,
+
+    -- Resultados del periodo
+    profit_total                    DOUBLE PRECISION NOT NULL,  -- ratio: 0.05 = 5 %
+    total_trades                    INTEGER NOT NULL,
+    wins                            INTEGER NOT NULL,
+    winner_holding_avg_s            DOUBLE PRECISION,           -- segundos
+    max_drawdown                    DOUBLE PRECISION,           -- ratio de la cuenta
+    rejected_signals                INTEGER,
+
+    updated_at                      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS backtest_market_idx
+    ON backtest (exchange, stake_currency, pairlist, timerange);
@@ -0,0 +1,63 @@
+-- Detalle por par y por trade de cada fila de `backtest`, escrito por
+-- `cli import`. Se borra junto con la fila a la que pertenece.
+
+-- Una tabla `backtest` creada fuera de freqdash no pasa por el CREATE TABLE
+-- de V1: se le añade aquí el `id` (numerando las filas existentes) y, si no
+-- es único, su clave primaria o, si ya tiene otra, una restricción UNIQUE.
+ALTER TABLE backtest ADD COLUMN IF NOT EXISTS id BIGSERIAL;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1
+        FROM pg_index i
+        JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = i.indkey[0]
+        WHERE i.indrelid = 'backtest'::regclass
+          AND i.indisunique AND i.indnatts = 1 AND i.indpred IS NULL
+          AND a.attname = 'id'
+    ) THEN
+        IF EXISTS (
+            SELECT 1 FROM pg_index
+            WHERE indrelid = 'backtest'::regclass AND indisprimary
+        ) THEN
+            ALTER TABLE backtest ADD CONSTRAINT backtest_id_key UNIQUE (id);
+        ELSE
+            ALTER TABLE backtest ADD PRIMARY KEY (id);
+        END IF;
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS backtest_pair (
+    backtest_id       BIGINT NOT NULL REFERENCES backtest (id) ON DELETE CASCADE,
+    pair              TEXT NOT NULL,               -- BTC/USDT
+    trades            INTEGER NOT NULL,
+    profit_mean       DOUBLE PRECISION NOT NULL,   -- ratio medio por trade
+    profit_total      DOUBLE PRECISION NOT NULL,   -- ratio sobre el capital inicial
+    profit_total_abs  DOUBLE PRECISION NOT NULL,   -- en stake_currency
+    wins              INTEGER NOT NULL,
+    draws             INTEGER NOT NULL,
+    losses            INTEGER NOT NULL,
+    duration_avg      TEXT NOT NULL                -- H:MM:SS
+);
+
+CREATE INDEX IF NOT EXISTS backtest_pair_backtest_idx ON backtest_pair (backtest_id);
+
+CREATE TABLE IF NOT EXISTS backtest_trade (
+    backtest_id     BIGINT NOT NULL REFERENCES backtest (id) ON DELETE CASCADE,
+    pair            TEXT NOT NULL,
+    is_short        BOOLEAN NOT NULL,
+    open_date       TIMESTAMPTZ,
+    close_date      TIMESTAMPTZ,
+    open_rate       DOUBLE PRECISION NOT NULL,
+    close_rate      DOUBLE PRECISION,
+    amount          DOUBLE PRECISION NOT NULL,
+    stake_amount    DOUBLE PRECISION NOT NULL,
+    profit_ratio    DOUBLE PRECISION NOT NULL,
+    profit_abs      DOUBLE PRECISION NOT NULL,
+    trade_duration  INTEGER,                       -- minutos
+    exit_reason     TEXT,
+    enter_tag       TEXT
+);
+
+CREATE INDEX IF NOT EXISTS backtest_trade_backtest_idx ON backtest_trade (backtest_id);
@@ -1,3 +1,18 @@
 pub mod model;
+pub mod hyperparams;
 pub mod sql;
+pub mod aggregate;
+pub mod planner;
 pub mod logic;
+pub mod cache;
+pub mod drilldown;
+pub mod metrics;
+pub mod timerange;
+pub mod walk_forward;
+pub mod markets;
+pub mod charts;
+pub mod sensitivity;
+pub mod fields;
+pub mod lexer;
+pub mod filter;
+pub mod scoring;
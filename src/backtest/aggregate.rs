@@ -0,0 +1,818 @@
+// src/backtest/aggregate.rs
+//
+// Motor de agrupación: convierte las filas mensuales (`BacktestRecord`) o los
+// agregados que calcula la fuente (`GroupedRecord`) en una `StrategyGridRow`
+// por configuración y mercado. No depende de ningún backend; las fuentes solo
+// entregan registros tipados.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::backtest::hyperparams::parse_stoploss;
+use crate::backtest::metrics::{ordered_profits, risk_metrics};
+use crate::backtest::model::{
+    BacktestRecord, Coverage, CoveragePolicy, GridQuery, Sensitivity, StrategyGridRow,
+};
+use crate::backtest::planner::FetchReport;
+use crate::backtest::timerange::periods_per_year;
+use crate::source::RecordSink;
+
+/// Columnas que identifican una configuración de estrategia, con los
+/// trailing como texto para poder compararlos.
+pub type StrategyKey = (
+    String,
+    String,
+    String,
+    String,
+    i32,
+    bool,
+    String,
+    String,
+    bool,
+    String,
+    String,
+    bool,
+);
+
+/// Exchange, moneda y pairlist de una fila.
+pub type MarketKey = (String, String, String);
+
+/// Agrupa en Rust las filas mensuales por mercado y `StrategyKey`. `timeranges`
+/// son los periodos consultados, con los que se mide la cobertura.
+pub fn summarize_records(
+    records: Vec<BacktestRecord>,
+    params: &GridQuery,
+    timeranges: &[String],
+) -> Vec<StrategyGridRow> {
+    let mut grouper = RecordGrouper::new(params, timeranges, &no_progress);
+    for record in records {
+        grouper.push(record);
+    }
+    grouper.finish()
+}
+
+/// Filas informadas entre dos avisos de progreso.
+const PROGRESS_STEP: usize = 1000;
+
+/// Avance de la lectura y agrupación de filas mensuales.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Progress {
+    /// Filas leídas hasta ahora.
+    pub read: usize,
+    /// Filas que se van a leer, si la fuente lo sabe de antemano.
+    pub total: Option<usize>,
+    /// Configuraciones (por mercado) formadas hasta ahora.
+    pub groups: usize,
+    pub done: bool,
+    /// Tiempos de la lectura por tramos; solo en el último aviso, y solo si
+    /// se leyó así.
+    pub fetch: Option<FetchReport>,
+}
+
+impl Progress {
+    /// Fracción leída entre 0 y 1, si se conoce el total.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.read as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.total {
+            Some(total) => write!(f, "{}/{} filas", self.read, total)?,
+            None => write!(f, "{} filas", self.read)?,
+        }
+        write!(f, " · {} configuraciones", self.groups)
+    }
+}
+
+/// Receptor de progreso que no hace nada.
+pub fn no_progress(_: Progress) {}
+
+/// Agrupa las filas mensuales a medida que llegan de la fuente, sin
+/// esperar a tenerlas todas, y avisa del avance cada `PROGRESS_STEP` filas.
+pub struct RecordGrouper<'a> {
+    params: &'a GridQuery,
+    timeranges: &'a [String],
+    // Una misma configuración en otro mercado es otra fila; ordenadas para
+    // que los empates salgan siempre igual
+    groups: BTreeMap<(MarketKey, StrategyKey), GroupAccumulator>,
+    progress: Progress,
+    on_progress: &'a (dyn Fn(Progress) + Sync),
+}
+
+/// Sumas parciales de una configuración.
+#[derive(Default)]
+struct GroupAccumulator {
+    trailing_stop_positive: f64,
+    trailing_stop_positive_offset: f64,
+    monthly: HashMap<String, f64>,
+    profits: Vec<f64>,
+    total_trades: i32,
+    wins: i32,
+    winner_holding_avg_s: f64,
+    max_drawdown: f64,
+    rejected_signals: i32,
+}
+
+impl<'a> RecordGrouper<'a> {
+    pub fn new(
+        params: &'a GridQuery,
+        timeranges: &'a [String],
+        on_progress: &'a (dyn Fn(Progress) + Sync),
+    ) -> Self {
+        Self {
+            params,
+            timeranges,
+            groups: BTreeMap::new(),
+            progress: Progress::default(),
+            on_progress,
+        }
+    }
+
+    pub fn push(&mut self, record: BacktestRecord) {
+        let group = self
+            .groups
+            .entry((market_key(&record), strategy_key(&record)))
+            .or_insert_with(|| GroupAccumulator {
+                trailing_stop_positive: record.trailing_stop_positive,
+                trailing_stop_positive_offset: record.trailing_stop_positive_offset,
+                ..Default::default()
+            });
+
+        let month = &record.timerange[0..8];
+        group.monthly.insert(month.to_string(), record.profit_total);
+        group.profits.push(record.profit_total);
+        group.total_trades += record.total_trades;
+        group.wins += record.wins;
+        group.winner_holding_avg_s += record.winner_holding_avg_s.unwrap_or(0.0);
+        group.max_drawdown += record.max_drawdown.unwrap_or(0.0);
+        group.rejected_signals += record.rejected_signals.unwrap_or(0);
+
+        self.progress.read += 1;
+        if self.progress.read % PROGRESS_STEP == 0 {
+            self.report();
+        }
+    }
+
+    /// Tiempos de la lectura por tramos, para el aviso final de `finish`.
+    pub fn set_fetch_report(&mut self, report: FetchReport) {
+        self.progress.fetch = Some(report);
+    }
+
+    /// Cierra los grupos y calcula las métricas de cada configuración.
+    pub fn finish(mut self) -> Vec<StrategyGridRow> {
+        self.progress.done = true;
+        self.report();
+
+        let mut result = Vec::with_capacity(self.groups.len());
+        for ((market, key), group) in self.groups {
+            let count = group.profits.len() as f64;
+            let profits = group.profits;
+            let avg_monthly_profit = mean(&profits);
+            let totals = GroupTotals {
+                total_trades: group.total_trades,
+                wins: group.wins,
+                win_time: group.winner_holding_avg_s / count,
+                drawdown_perc: group.max_drawdown / count,
+                rejected_signals: group.rejected_signals as f64 / count,
+                profit_total: profits.iter().sum(),
+                avg_monthly_profit,
+                std_monthly_profit: stddev(&profits, avg_monthly_profit),
+                max_profit_month: profits.iter().cloned().fold(f64::MIN, f64::max),
+                min_profit_month: profits.iter().cloned().fold(f64::MAX, f64::min),
+                neg_months: profits.iter().filter(|&&p| p < 0.0).count(),
+                total_gain: profits.iter().filter(|&&p| p > 0.0).sum(),
+                total_loss: profits.iter().filter(|&&p| p < 0.0).map(|p| p.abs()).sum(),
+                monthly: group.monthly,
+            };
+
+            result.push(build_row(
+                &market,
+                &key,
+                group.trailing_stop_positive,
+                group.trailing_stop_positive_offset,
+                totals,
+                self.params,
+                self.timeranges,
+            ));
+        }
+
+        result
+    }
+
+    fn report(&mut self) {
+        self.progress.groups = self.groups.len();
+        (self.on_progress)(self.progress.clone());
+    }
+}
+
+impl RecordSink for RecordGrouper<'_> {
+    fn expect(&mut self, rows: usize) {
+        self.progress.total = Some(rows);
+        self.report();
+    }
+
+    fn push(&mut self, record: BacktestRecord) {
+        RecordGrouper::push(self, record);
+    }
+}
+
+/// Una configuración y mercado que la fuente ya agregó
+/// (`AggregationMode::Server`, ver `sql::build_grouped_sql`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupedRecord {
+    pub exchange: String,
+    pub stake_currency: String,
+    pub pairlist: String,
+    pub strategy: String,
+    pub timeframe: String,
+    pub minimal_roi: String,
+    pub stoploss: String,
+    pub max_open_trades: i32,
+    pub trailing_stop: bool,
+    pub trailing_stop_positive: f64,
+    pub trailing_stop_positive_offset: f64,
+    pub trailing_only_offset_is_reached: bool,
+    pub entry_pricing: String,
+    pub exit_pricing: String,
+    pub check_depth_of_market_enable: bool,
+    pub total_trades: i32,
+    pub wins: i32,
+    pub win_time: f64,
+    pub drawdown_perc: f64,
+    pub rejected_signals: f64,
+    pub profit_total: f64,
+    pub avg_monthly_profit: f64,
+    pub std_monthly_profit: f64,
+    pub max_profit_month: f64,
+    pub min_profit_month: f64,
+    pub neg_months: usize,
+    pub total_gain: f64,
+    pub total_loss: f64,
+    /// Periodos con fila y su profit, en el mismo orden.
+    pub timeranges: Vec<String>,
+    pub profits: Vec<f64>,
+}
+
+/// Completa las configuraciones que la fuente ya agregó.
+pub fn summarize_grouped(
+    records: Vec<GroupedRecord>,
+    params: &GridQuery,
+    timeranges: &[String],
+) -> Vec<StrategyGridRow> {
+    let mut result = Vec::with_capacity(records.len());
+
+    for record in records {
+        let market: MarketKey = (record.exchange, record.stake_currency, record.pairlist);
+        let key: StrategyKey = (
+            record.strategy,
+            record.timeframe,
+            record.minimal_roi,
+            record.stoploss,
+            record.max_open_trades,
+            record.trailing_stop,
+            record.trailing_stop_positive.to_string(),
+            record.trailing_stop_positive_offset.to_string(),
+            record.trailing_only_offset_is_reached,
+            record.entry_pricing,
+            record.exit_pricing,
+            record.check_depth_of_market_enable,
+        );
+
+        let monthly = record
+            .timeranges
+            .iter()
+            .map(|timerange| timerange[0..8].to_string())
+            .zip(record.profits)
+            .collect();
+
+        let totals = GroupTotals {
+            total_trades: record.total_trades,
+            wins: record.wins,
+            win_time: record.win_time,
+            drawdown_perc: record.drawdown_perc,
+            rejected_signals: record.rejected_signals,
+            profit_total: record.profit_total,
+            avg_monthly_profit: record.avg_monthly_profit,
+            std_monthly_profit: record.std_monthly_profit,
+            max_profit_month: record.max_profit_month,
+            min_profit_month: record.min_profit_month,
+            neg_months: record.neg_months,
+            total_gain: record.total_gain,
+            total_loss: record.total_loss,
+            monthly,
+        };
+
+        result.push(build_row(
+            &market,
+            &key,
+            record.trailing_stop_positive,
+            record.trailing_stop_positive_offset,
+            totals,
+            params,
+            timeranges,
+        ));
+    }
+
+    result
+}
+
+/// Valores agregados de una configuración, vengan de Rust o de la fuente.
+struct GroupTotals {
+    total_trades: i32,
+    wins: i32,
+    win_time: f64,
+    drawdown_perc: f64,
+    rejected_signals: f64,
+    profit_total: f64,
+    avg_monthly_profit: f64,
+    std_monthly_profit: f64,
+    max_profit_month: f64,
+    min_profit_month: f64,
+    neg_months: usize,
+    total_gain: f64,
+    total_loss: f64,
+    monthly: HashMap<String, f64>,
+}
+
+fn market_key(record: &BacktestRecord) -> MarketKey {
+    (
+        record.exchange.clone(),
+        record.stake_currency.clone(),
+        record.pairlist.clone(),
+    )
+}
+
+pub(crate) fn strategy_key(record: &BacktestRecord) -> StrategyKey {
+    (
+        record.strategy.clone(),
+        record.timeframe.clone(),
+        record.minimal_roi.clone(),
+        record.stoploss.clone(),
+        record.max_open_trades,
+        record.trailing_stop,
+        record.trailing_stop_positive.to_string(),
+        record.trailing_stop_positive_offset.to_string(),
+        record.trailing_only_offset_is_reached,
+        record.entry_pricing.clone(),
+        record.exit_pricing.clone(),
+        record.check_depth_of_market_enable,
+    )
+}
+
+/// Clave de la configuración de una fila del resumen; la misma que
+/// `strategy_key` da a sus filas mensuales.
+pub fn row_key(row: &StrategyGridRow) -> StrategyKey {
+    (
+        row.strategy.clone(),
+        row.timeframe.clone(),
+        row.minimal_roi.clone(),
+        row.stoploss.clone(),
+        row.max_open_trades,
+        row.trailing_stop,
+        row.trailing_stop_positive.unwrap_or_default().to_string(),
+        row.trailing_stop_positive_offset.unwrap_or_default().to_string(),
+        row.trailing_only_offset_is_reached,
+        row.entry_price.clone(),
+        row.exit_price.clone(),
+        row.check_depth_of_market_enable,
+    )
+}
+
+/// Completa las métricas derivadas comunes a ambos modos de agregación.
+fn build_row(
+    market: &MarketKey,
+    key: &StrategyKey,
+    trailing_stop_positive: f64,
+    trailing_stop_positive_offset: f64,
+    mut totals: GroupTotals,
+    params: &GridQuery,
+    timeranges: &[String],
+) -> StrategyGridRow {
+    let (
+        strategy,
+        timeframe,
+        minimal_roi,
+        stoploss,
+        max_open_trades,
+        trailing_stop,
+        _,
+        _, // tsp_str y tspo_str
+        trailing_only_offset_is_reached,
+        entry_price,
+        exit_price,
+        check_depth_of_market_enable,
+    ) = key;
+    let (exchange, currency, pairlist) = market;
+
+    let coverage = coverage(&totals.monthly, timeranges);
+    // `monthly` conserva solo los periodos con resultado; los huecos van en
+    // `coverage.gaps` y, con `FillZero`, cuentan como 0 en esta serie
+    let filled = if params.coverage_policy == CoveragePolicy::FillZero && !coverage.is_complete() {
+        Some(fill_gaps(&mut totals, &coverage.gaps))
+    } else {
+        None
+    };
+    let series = filled.as_ref().unwrap_or(&totals.monthly);
+
+    let total_trades = totals.total_trades;
+    let wins = totals.wins;
+    let losses = total_trades - wins;
+    let win_rate = if total_trades > 0 {
+        wins as f64 / total_trades as f64
+    } else {
+        0.0
+    };
+    let loss_rate = if total_trades > 0 {
+        losses as f64 / total_trades as f64
+    } else {
+        0.0
+    };
+    let avg_trade_profit = if total_trades > 0 {
+        totals.profit_total / total_trades as f64
+    } else {
+        0.0
+    };
+    let expectancy = (win_rate * avg_trade_profit) - (loss_rate * avg_trade_profit.abs()); // Corregido
+
+    let profit_factor = if totals.total_loss > 0.0 {
+        totals.total_gain / totals.total_loss
+    } else {
+        999.0
+    }; // Valor alto para ganancias sin pérdidas
+
+    let risk = risk_metrics(
+        &ordered_profits(series),
+        params.accumulation,
+        periods_per_year(params),
+    );
+
+    let weight = match params.coverage_policy {
+        CoveragePolicy::Penalize => coverage.ratio(),
+        CoveragePolicy::Exclude | CoveragePolicy::FillZero => 1.0,
+    };
+
+    StrategyGridRow {
+        exchange: exchange.clone(),
+        currency: currency.clone(),
+        pairlist: pairlist.clone(),
+        strategy: strategy.clone(),
+        timeframe: timeframe.clone(),
+        minimal_roi: minimal_roi.clone(),
+        stoploss: stoploss.clone(),
+        roi_table: minimal_roi.parse().ok(),
+        stoploss_value: parse_stoploss(stoploss).ok(),
+        max_open_trades: *max_open_trades,
+        trailing_stop: *trailing_stop,
+        trailing_stop_positive: Some(trailing_stop_positive),
+        trailing_stop_positive_offset: Some(trailing_stop_positive_offset),
+        trailing_only_offset_is_reached: *trailing_only_offset_is_reached,
+        entry_price: entry_price.clone(),
+        exit_price: exit_price.clone(),
+        check_depth_of_market_enable: *check_depth_of_market_enable,
+        total_profit: totals.profit_total * weight,
+        total_trades,
+        wins,
+        win_rate,
+        win_time: totals.win_time,
+        drawdown_perc: totals.drawdown_perc,
+        rejected_signals: totals.rejected_signals,
+        neg_months: totals.neg_months,
+        avg_monthly_profit: totals.avg_monthly_profit * weight,
+        std_monthly_profit: totals.std_monthly_profit,
+        max_profit_month: totals.max_profit_month,
+        min_profit_month: totals.min_profit_month,
+        avg_trade_profit,
+        losses,
+        loss_rate,
+        expectancy,
+        profit_factor,
+        sharpe: risk.sharpe,
+        sortino: risk.sortino,
+        calmar: risk.calmar,
+        ulcer_index: risk.ulcer_index,
+        recovery_factor: risk.recovery_factor,
+        max_losing_streak: risk.max_losing_streak,
+        equity_return: risk.equity_return * weight,
+        max_drawdown: risk.max_drawdown,
+        max_drawdown_months: risk.max_drawdown_months,
+        coverage,
+        // Se calcula después, con todas las filas (`apply_sensitivity`)
+        sensitivity: Sensitivity::default(),
+        score: None,
+        monthly: totals.monthly,
+    }
+}
+
+/// Compara los periodos de `monthly` con los consultados.
+fn coverage(monthly: &HashMap<String, f64>, timeranges: &[String]) -> Coverage {
+    let gaps: Vec<String> = timeranges
+        .iter()
+        .map(|timerange| timerange[0..8].to_string())
+        .filter(|month| !monthly.contains_key(month))
+        .collect();
+    Coverage {
+        expected: timeranges.len(),
+        present: timeranges.len() - gaps.len(),
+        gaps,
+    }
+}
+
+/// Serie de `totals.monthly` con los huecos como profit 0, con la que se
+/// recalculan las estadísticas por periodo. Totales, ganancias, pérdidas y
+/// meses negativos no cambian, y `monthly` sigue sin los huecos.
+fn fill_gaps(totals: &mut GroupTotals, gaps: &[String]) -> HashMap<String, f64> {
+    let mut filled = totals.monthly.clone();
+    for month in gaps {
+        filled.insert(month.clone(), 0.0);
+    }
+    let profits: Vec<f64> = filled.values().copied().collect();
+    totals.avg_monthly_profit = mean(&profits);
+    totals.std_monthly_profit = stddev(&profits, totals.avg_monthly_profit);
+    totals.max_profit_month = profits.iter().cloned().fold(f64::MIN, f64::max);
+    totals.min_profit_month = profits.iter().cloned().fold(f64::MAX, f64::min);
+    filled
+}
+
+fn mean(data: &[f64]) -> f64 {
+    if data.is_empty() {
+        0.0
+    } else {
+        data.iter().sum::<f64>() / data.len() as f64
+    }
+}
+
+fn stddev(data: &[f64], mean: f64) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let variance = data
+        .iter()
+        .map(|value| {
+            let diff = mean - *value;
+            diff * diff
+        })
+        .sum::<f64>()
+        / data.len() as f64;
+    variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::model::AggregationMode;
+
+    fn assert_close(a: f64, b: f64, field: &str) {
+        assert!((a - b).abs() < 1e-9, "{}: {} != {}", field, a, b);
+    }
+
+    fn record(timerange: &str, profit_total: f64) -> BacktestRecord {
+        BacktestRecord {
+            strategy: "A".to_string(),
+            timeframe: "5m".to_string(),
+            minimal_roi: "{}".to_string(),
+            stoploss: "-0.1".to_string(),
+            max_open_trades: 3,
+            trailing_stop: false,
+            trailing_stop_positive: 0.0,
+            trailing_stop_positive_offset: 0.0,
+            trailing_only_offset_is_reached: false,
+            entry_pricing: "same".to_string(),
+            exit_pricing: "same".to_string(),
+            check_depth_of_market_enable: false,
+            profit_total,
+            total_trades: 10,
+            wins: 5,
+            winner_holding_avg_s: None,
+            max_drawdown: None,
+            rejected_signals: None,
+            timerange: timerange.to_string(),
+            exchange: "BINANCE".to_string(),
+            stake_currency: "USDT".to_string(),
+            pairlist: "BTC".to_string(),
+        }
+    }
+
+    /// Lo que devuelve `sql::build_grouped_sql` para las filas de una sola
+    /// configuración y mercado.
+    fn grouped_like_postgres(records: &[BacktestRecord]) -> GroupedRecord {
+        let first = &records[0];
+        let count = records.len() as f64;
+        let mut sorted = records.to_vec();
+        sorted.sort_by(|a, b| a.timerange.cmp(&b.timerange));
+        let profits: Vec<f64> = sorted.iter().map(|r| r.profit_total).collect();
+        let avg = profits.iter().sum::<f64>() / count;
+        let variance = profits.iter().map(|p| (p - avg).powi(2)).sum::<f64>() / count;
+
+        GroupedRecord {
+            exchange: first.exchange.clone(),
+            stake_currency: first.stake_currency.clone(),
+            pairlist: first.pairlist.clone(),
+            strategy: first.strategy.clone(),
+            timeframe: first.timeframe.clone(),
+            minimal_roi: first.minimal_roi.clone(),
+            stoploss: first.stoploss.clone(),
+            max_open_trades: first.max_open_trades,
+            trailing_stop: first.trailing_stop,
+            trailing_stop_positive: first.trailing_stop_positive,
+            trailing_stop_positive_offset: first.trailing_stop_positive_offset,
+            trailing_only_offset_is_reached: first.trailing_only_offset_is_reached,
+            entry_pricing: first.entry_pricing.clone(),
+            exit_pricing: first.exit_pricing.clone(),
+            check_depth_of_market_enable: first.check_depth_of_market_enable,
+            total_trades: records.iter().map(|r| r.total_trades).sum(),
+            wins: records.iter().map(|r| r.wins).sum(),
+            win_time: records.iter().map(|r| r.winner_holding_avg_s.unwrap_or(0.0)).sum::<f64>()
+                / count,
+            drawdown_perc: records.iter().map(|r| r.max_drawdown.unwrap_or(0.0)).sum::<f64>()
+                / count,
+            rejected_signals: records
+                .iter()
+                .map(|r| r.rejected_signals.unwrap_or(0) as f64)
+                .sum::<f64>()
+                / count,
+            profit_total: profits.iter().sum(),
+            avg_monthly_profit: avg,
+            std_monthly_profit: variance.sqrt(),
+            max_profit_month: profits.iter().cloned().fold(f64::MIN, f64::max),
+            min_profit_month: profits.iter().cloned().fold(f64::MAX, f64::min),
+            neg_months: profits.iter().filter(|&&p| p < 0.0).count(),
+            total_gain: profits.iter().filter(|&&p| p > 0.0).sum(),
+            total_loss: -profits.iter().filter(|&&p| p < 0.0).sum::<f64>(),
+            timeranges: sorted.iter().map(|r| r.timerange.clone()).collect(),
+            profits,
+        }
+    }
+
+    #[test]
+    fn client_and_server_aggregation_match_offline() {
+        let timeranges = vec![
+            "20240101-20240201".to_string(),
+            "20240201-20240301".to_string(),
+            "20240301-20240401".to_string(),
+        ];
+        let month = |timerange: &str, profit: f64, trades: i32, wins: i32| {
+            let mut record = record(timerange, profit);
+            record.total_trades = trades;
+            record.wins = wins;
+            record.winner_holding_avg_s = Some(trades as f64 * 60.0);
+            record.max_drawdown = Some(profit.abs() / 2.0);
+            record.rejected_signals = Some(wins);
+            record
+        };
+        let a = vec![
+            month("20240301-20240401", 0.02, 8, 5),
+            month("20240101-20240201", 0.10, 12, 9),
+            month("20240201-20240301", -0.05, 10, 3),
+        ];
+        // Sin datos opcionales y con un hueco en marzo
+        let mut b = vec![
+            record("20240101-20240201", -0.03),
+            record("20240201-20240301", -0.01),
+        ];
+        for record in &mut b {
+            record.strategy = "B".to_string();
+        }
+        let query = GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 3,
+            aggregation: AggregationMode::Client,
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        };
+
+        let records = a.iter().chain(&b).cloned().collect();
+        let mut client = summarize_records(records, &query, &timeranges);
+        let grouped = vec![grouped_like_postgres(&b), grouped_like_postgres(&a)];
+        let mut server = summarize_grouped(grouped, &query, &timeranges);
+        client.sort_by(|x, y| x.strategy.cmp(&y.strategy));
+        server.sort_by(|x, y| x.strategy.cmp(&y.strategy));
+
+        assert_eq!(client.len(), 2);
+        assert_eq!(server.len(), 2);
+        for (c, s) in client.iter().zip(&server) {
+            assert_eq!(row_key(c), row_key(s));
+            assert_eq!(c.total_trades, s.total_trades);
+            assert_eq!(c.wins, s.wins);
+            assert_eq!(c.losses, s.losses);
+            assert_eq!(c.neg_months, s.neg_months);
+            assert_eq!(c.monthly, s.monthly);
+            assert_eq!(c.coverage, s.coverage);
+            assert_eq!(c.max_losing_streak, s.max_losing_streak);
+            assert_close(c.total_profit, s.total_profit, "total_profit");
+            assert_close(c.win_rate, s.win_rate, "win_rate");
+            assert_close(c.loss_rate, s.loss_rate, "loss_rate");
+            assert_close(c.win_time, s.win_time, "win_time");
+            assert_close(c.drawdown_perc, s.drawdown_perc, "drawdown_perc");
+            assert_close(c.rejected_signals, s.rejected_signals, "rejected_signals");
+            assert_close(c.avg_monthly_profit, s.avg_monthly_profit, "avg_monthly_profit");
+            assert_close(c.std_monthly_profit, s.std_monthly_profit, "std_monthly_profit");
+            assert_close(c.max_profit_month, s.max_profit_month, "max_profit_month");
+            assert_close(c.min_profit_month, s.min_profit_month, "min_profit_month");
+            assert_close(c.avg_trade_profit, s.avg_trade_profit, "avg_trade_profit");
+            assert_close(c.expectancy, s.expectancy, "expectancy");
+            assert_close(c.profit_factor, s.profit_factor, "profit_factor");
+            assert_close(c.sharpe, s.sharpe, "sharpe");
+            assert_close(c.equity_return, s.equity_return, "equity_return");
+            assert_close(c.max_drawdown, s.max_drawdown, "max_drawdown");
+        }
+        assert_eq!(client[0].neg_months, 1);
+        assert_eq!(client[1].neg_months, 2);
+        assert_eq!(client[1].coverage.gaps, vec!["20240301"]);
+        assert_close(client[0].profit_factor, 0.12 / 0.05, "profit_factor");
+    }
+
+    #[test]
+    fn coverage_policy_handles_gaps() {
+        let timeranges = vec![
+            "20240101-20240201".to_string(),
+            "20240201-20240301".to_string(),
+            "20240301-20240401".to_string(),
+            "20240401-20240501".to_string(),
+        ];
+        let records = || {
+            vec![
+                record("20240101-20240201", 0.10),
+                record("20240301-20240401", 0.10),
+            ]
+        };
+        let mut query = GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 4,
+            aggregation: AggregationMode::Client,
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: CoveragePolicy::FillZero,
+        };
+
+        let filled = &summarize_records(records(), &query, &timeranges)[0];
+        assert_eq!(filled.coverage.expected, 4);
+        assert_eq!(filled.coverage.present, 2);
+        assert_eq!(filled.coverage.gaps, vec!["20240201", "20240401"]);
+        // Los huecos no se escriben como un 0 % real en `monthly`
+        assert_eq!(filled.monthly.len(), filled.coverage.present);
+        assert!(!filled.monthly.contains_key("20240201"));
+        assert_close(filled.avg_monthly_profit, 0.05, "avg_monthly_profit");
+        assert_close(filled.min_profit_month, 0.0, "min_profit_month");
+        assert_close(filled.total_profit, 0.20, "total_profit");
+
+        query.coverage_policy = CoveragePolicy::Penalize;
+        let penalized = &summarize_records(records(), &query, &timeranges)[0];
+        assert_eq!(penalized.monthly.len(), 2);
+        assert_close(penalized.avg_monthly_profit, 0.05, "avg_monthly_profit");
+        assert_close(penalized.total_profit, 0.10, "total_profit");
+    }
+
+    #[test]
+    fn grouper_reports_progress_while_grouping() {
+        let timeranges = vec!["20240101-20240201".to_string()];
+        let query = GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 1,
+            aggregation: AggregationMode::Client,
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        };
+        let events = std::sync::Mutex::new(Vec::new());
+        let on_progress = |progress: Progress| events.lock().unwrap().push(progress);
+
+        let mut grouper = RecordGrouper::new(&query, &timeranges, &on_progress);
+        RecordSink::expect(&mut grouper, 2 * PROGRESS_STEP);
+        for i in 0..2 * PROGRESS_STEP {
+            let mut record = record("20240101-20240201", 0.01);
+            record.strategy = format!("S{}", i % 3);
+            grouper.push(record);
+        }
+        let rows = grouper.finish();
+
+        let events = events.into_inner().unwrap();
+        let read: Vec<usize> = events.iter().map(|p| p.read).collect();
+        assert_eq!(read, vec![0, PROGRESS_STEP, 2 * PROGRESS_STEP, 2 * PROGRESS_STEP]);
+        assert_eq!(events[1].fraction(), Some(0.5));
+        assert_eq!(events[1].groups, 3);
+        assert!(events.last().unwrap().done);
+        assert_eq!(rows.len(), 3);
+    }
+}
@@ -0,0 +1,333 @@
+// src/backtest/cache.rs
+//
+// Caché de `get_grid_summary`. La clave es la URL de la fuente (resumida en
+// un hash) y la consulta normalizada; cada entrada guarda la marca de agua de
+// la fuente (`BacktestSource::watermark`) con la que se calculó y deja de
+// valer en cuanto la tabla cambia. Las entradas viven en memoria y, con
+// `[cache] disk = true`, también en JSON en el directorio de caché.
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::backtest::aggregate::Progress;
+use crate::backtest::logic::get_grid_summary_with_progress;
+use crate::backtest::model::{GridQuery, StrategyGridRow};
+use crate::backtest::timerange::generate_timeranges;
+use crate::config;
+use crate::db;
+use crate::error::{BoxError, Error, Result};
+use crate::source::BacktestSource;
+
+/// Consultas que se conservan en memoria; se descarta la usada hace más tiempo.
+const MAX_ENTRIES: usize = 16;
+
+static CACHE: OnceCell<GridCache> = OnceCell::new();
+
+/// De dónde salió un resumen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    Memory,
+    Disk,
+    /// Se calculó y quedó guardado.
+    Miss,
+    /// Se calculó sin caché: está desactivada o la fuente no tiene marca de agua.
+    Bypassed,
+}
+
+impl CacheStatus {
+    pub fn is_hit(self) -> bool {
+        matches!(self, CacheStatus::Memory | CacheStatus::Disk)
+    }
+}
+
+impl fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheStatus::Memory => write!(f, "caché en memoria"),
+            CacheStatus::Disk => write!(f, "caché en disco"),
+            CacheStatus::Miss => write!(f, "calculado"),
+            CacheStatus::Bypassed => write!(f, "sin caché"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// Se comprueba al leer del disco: el nombre del fichero es solo un hash.
+    key: String,
+    watermark: String,
+    rows: Vec<StrategyGridRow>,
+}
+
+pub struct GridCache {
+    entries: Mutex<HashMap<String, (Instant, Entry)>>,
+    dir: Option<PathBuf>,
+}
+
+impl GridCache {
+    /// Caché en memoria y, si se indica `dir`, también en disco.
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            dir,
+        }
+    }
+
+    /// `get_grid_summary_with_progress` reutilizando el resultado si la
+    /// consulta ya se hizo con los mismos datos. `source_id` distingue las
+    /// fuentes (su URL).
+    pub async fn grid_summary<S: BacktestSource>(
+        &self,
+        source: &S,
+        source_id: &str,
+        params: &GridQuery,
+        on_progress: &(dyn Fn(Progress) + Sync),
+    ) -> Result<(Vec<StrategyGridRow>, CacheStatus)> {
+        let watermark = match source.watermark().await {
+            Ok(Some(watermark)) => watermark,
+            Ok(None) => {
+                let rows = get_grid_summary_with_progress(source, params, on_progress).await?;
+                return Ok((rows, CacheStatus::Bypassed));
+            }
+            Err(e) => {
+                println!("⚠️ Caché no disponible: {}", e);
+                let rows = get_grid_summary_with_progress(source, params, on_progress).await?;
+                return Ok((rows, CacheStatus::Bypassed));
+            }
+        };
+        let key = cache_key(source_id, params)?;
+
+        if let Some(rows) = self.read_memory(&key, &watermark) {
+            return Ok((rows, CacheStatus::Memory));
+        }
+        if let Some(entry) = self.read_disk(&key, &watermark) {
+            let rows = entry.rows.clone();
+            self.remember(entry);
+            return Ok((rows, CacheStatus::Disk));
+        }
+
+        let rows = get_grid_summary_with_progress(source, params, on_progress).await?;
+        let entry = Entry {
+            key,
+            watermark,
+            rows: rows.clone(),
+        };
+        if let Some(path) = self.path(&entry.key) {
+            if let Err(e) = write_entry(&path, &entry) {
+                println!("⚠️ No se pudo guardar la caché: {}", e);
+            }
+        }
+        self.remember(entry);
+        Ok((rows, CacheStatus::Miss))
+    }
+
+    fn read_memory(&self, key: &str, watermark: &str) -> Option<Vec<StrategyGridRow>> {
+        let mut entries = self.entries.lock().ok()?;
+        let (used, entry) = entries.get_mut(key)?;
+        if entry.watermark != watermark {
+            return None;
+        }
+        *used = Instant::now();
+        Some(entry.rows.clone())
+    }
+
+    fn read_disk(&self, key: &str, watermark: &str) -> Option<Entry> {
+        let text = fs::read_to_string(self.path(key)?).ok()?;
+        let entry: Entry = serde_json::from_str(&text).ok()?;
+        (entry.key == key && entry.watermark == watermark).then_some(entry)
+    }
+
+    fn remember(&self, entry: Entry) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if !entries.contains_key(&entry.key) && entries.len() >= MAX_ENTRIES {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(entry.key.clone(), (Instant::now(), entry));
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:016x}.json", fnv1a(key))))
+    }
+}
+
+/// Caché compartida por la CLI y la GUI, con el directorio de `[cache]`.
+pub fn global() -> &'static GridCache {
+    CACHE.get_or_init(|| {
+        GridCache::new(config::settings().ok().and_then(|s| s.cache.disk_dir()))
+    })
+}
+
+/// Resumen de `params` en la fuente activa, a través de la caché global
+/// salvo que `[cache] enabled = false`.
+pub async fn cached_grid_summary(
+    params: &GridQuery,
+    on_progress: &(dyn Fn(Progress) + Sync),
+) -> Result<(Vec<StrategyGridRow>, CacheStatus)> {
+    let source = db::get_source()?;
+    if !config::settings()?.cache.enabled {
+        let rows = get_grid_summary_with_progress(source.as_ref(), params, on_progress).await?;
+        return Ok((rows, CacheStatus::Bypassed));
+    }
+    global()
+        .grid_summary(source.as_ref(), config::get_database_url()?, params, on_progress)
+        .await
+}
+
+/// Consulta con los mercados en mayúsculas, ordenados y sin repetir, y el
+/// intervalo sustituido por los periodos que genera: dos consultas que piden
+/// los mismos periodos comparten entrada.
+pub fn normalize(params: &GridQuery) -> Result<GridQuery> {
+    let markets = |values: &[String]| {
+        let mut values: Vec<String> = values.iter().map(|v| v.trim().to_uppercase()).collect();
+        values.sort();
+        values.dedup();
+        values
+    };
+    Ok(GridQuery {
+        exchanges: markets(&params.exchanges),
+        currencies: markets(&params.currencies),
+        pairlists: markets(&params.pairlists),
+        start_date: String::new(),
+        months: 0,
+        end_date: None,
+        custom_ranges: generate_timeranges(params)?,
+        ..params.clone()
+    })
+}
+
+/// La URL entra solo como hash para no escribir contraseñas en disco.
+fn cache_key(source_id: &str, params: &GridQuery) -> Result<String> {
+    let query = serde_json::to_string(&normalize(params)?)
+        .map_err(|e| Error::Config(e.to_string()))?;
+    Ok(format!("{:016x}:{}", fnv1a(source_id), query))
+}
+
+fn write_entry(path: &Path, entry: &Entry) -> std::result::Result<(), BoxError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Hash FNV-1a de 64 bits: estable entre ejecuciones, para nombrar ficheros.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::aggregate::no_progress;
+    use crate::backtest::model::{AggregationMode, Bucket};
+    use crate::source::FileSource;
+
+    const HEADER: &str = "strategy,timeframe,minimal_roi,stoploss,max_open_trades,trailing_stop,\
+        trailing_stop_positive,trailing_stop_positive_offset,\
+        trailing_only_offset_is_reached,entry_pricing,exit_pricing,\
+        check_depth_of_market_enable,profit_total,total_trades,wins,\
+        winner_holding_avg_s,max_drawdown,rejected_signals,timerange,\
+        exchange,stake_currency,pairlist\n";
+
+    fn query() -> GridQuery {
+        GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 1,
+            aggregation: AggregationMode::Client,
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        }
+    }
+
+    #[test]
+    fn equivalent_queries_share_a_key() {
+        let by_months = query();
+        let by_end_date = GridQuery {
+            exchanges: vec!["binance".to_string(), "BINANCE".to_string()],
+            months: 6,
+            end_date: Some("2024-01-15".to_string()),
+            ..query()
+        };
+        let custom = GridQuery {
+            bucket: Bucket::Custom,
+            custom_ranges: vec!["20240101-20240201".to_string()],
+            ..query()
+        };
+        let key = cache_key("sqlite://a.db", &by_months).unwrap();
+        assert_eq!(key, cache_key("sqlite://a.db", &by_end_date).unwrap());
+        assert_ne!(key, cache_key("sqlite://b.db", &by_months).unwrap());
+        // El tamaño de periodo anualiza las métricas: no se mezcla
+        assert_ne!(key, cache_key("sqlite://a.db", &custom).unwrap());
+    }
+
+    #[tokio::test]
+    async fn changed_source_invalidates_entries() {
+        let dir = std::env::temp_dir().join(format!("freqdash-cache-{}", std::process::id()));
+        let path = dir.join("backtest.csv");
+        fs::create_dir_all(&dir).unwrap();
+        let row = |profit: f64| {
+            format!(
+                "A,5m,{{}},-0.1,3,false,0.01,0.02,false,same,same,false,{},10,6,,0.02,1,\
+                 20240101-20240201,BINANCE,USDT,BTC\n",
+                profit
+            )
+        };
+        fs::write(&path, format!("{}{}", HEADER, row(0.05))).unwrap();
+        let source = FileSource::new(&path);
+        let id = "file://backtest.csv";
+        let params = query();
+
+        let cache = GridCache::new(Some(dir.join("cache")));
+        let (rows, status) = cache
+            .grid_summary(&source, id, &params, &no_progress)
+            .await
+            .unwrap();
+        assert_eq!((rows.len(), status), (1, CacheStatus::Miss));
+        let (_, status) = cache
+            .grid_summary(&source, id, &params, &no_progress)
+            .await
+            .unwrap();
+        assert_eq!(status, CacheStatus::Memory);
+
+        // Una caché nueva sobre el mismo directorio lee el fichero
+        let reopened = GridCache::new(Some(dir.join("cache")));
+        let (_, status) = reopened
+            .grid_summary(&source, id, &params, &no_progress)
+            .await
+            .unwrap();
+        assert_eq!(status, CacheStatus::Disk);
+
+        fs::write(&path, format!("{}{}", HEADER, row(0.125))).unwrap();
+        let (rows, status) = cache
+            .grid_summary(&source, id, &params, &no_progress)
+            .await
+            .unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(status, CacheStatus::Miss);
+        assert_eq!(rows[0].total_profit, 0.125);
+    }
+}
@@ -0,0 +1,155 @@
+// src/backtest/charts.rs
+//
+// Datos de los gráficos de la GUI, sin dependencias de GTK: el mapa de calor
+// configuración × mes y las curvas de capital de varias filas.
+
+use std::collections::BTreeSet;
+
+use crate::backtest::markets::{market_label, market_labels};
+use crate::backtest::metrics::equity_curve;
+use crate::backtest::model::{AccumulationMode, StrategyGridRow};
+
+/// Profit mensual de cada fila; `None` en los periodos sin datos.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    /// Periodos (`AAAAMMDD`) en orden cronológico.
+    pub months: Vec<String>,
+    pub rows: Vec<HeatmapRow>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapRow {
+    pub label: String,
+    /// Un valor por cada periodo de `Heatmap::months`.
+    pub profits: Vec<Option<f64>>,
+}
+
+impl Heatmap {
+    /// Mapa de `rows` en su orden, con la unión de sus periodos como columnas.
+    pub fn from_rows(rows: &[StrategyGridRow]) -> Self {
+        let months: Vec<String> = rows
+            .iter()
+            .flat_map(|row| row.monthly.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let with_market = market_labels(rows).len() > 1;
+        let rows = rows
+            .iter()
+            .map(|row| HeatmapRow {
+                label: row_label(row, with_market),
+                profits: months.iter().map(|month| row.monthly.get(month).copied()).collect(),
+            })
+            .collect();
+        Heatmap { months, rows }
+    }
+
+    /// Mayor profit mensual en valor absoluto: el extremo de la escala de
+    /// color, simétrica alrededor de 0.
+    pub fn max_abs(&self) -> f64 {
+        self.rows
+            .iter()
+            .flat_map(|row| row.profits.iter().flatten())
+            .fold(0.0, |max: f64, profit| max.max(profit.abs()))
+    }
+}
+
+/// Curva de capital de una fila sobre unos periodos comunes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquitySeries {
+    pub label: String,
+    /// Capital al cierre de cada periodo de `months`, partiendo de 1; los
+    /// periodos sin datos no lo mueven.
+    pub points: Vec<f64>,
+}
+
+/// Curvas de `rows` sobre la unión de sus periodos, acumuladas con `mode`.
+/// Devuelve los periodos (`AAAAMMDD`) y una serie por fila.
+pub fn equity_series(
+    rows: &[StrategyGridRow],
+    mode: AccumulationMode,
+) -> (Vec<String>, Vec<EquitySeries>) {
+    let heatmap = Heatmap::from_rows(rows);
+    let series = heatmap
+        .rows
+        .into_iter()
+        .map(|row| {
+            let profits: Vec<f64> = row.profits.iter().map(|p| p.unwrap_or(0.0)).collect();
+            EquitySeries {
+                label: row.label,
+                // Sin el capital inicial: un punto por periodo
+                points: equity_curve(&profits, mode).split_off(1),
+            }
+        })
+        .collect();
+    (heatmap.months, series)
+}
+
+/// `AAAA-MM` de un periodo `AAAAMMDD`.
+pub fn month_label(month: &str) -> String {
+    match (month.get(..4), month.get(4..6)) {
+        (Some(year), Some(month)) => format!("{}-{}", year, month),
+        _ => month.to_string(),
+    }
+}
+
+/// Etiqueta corta de una configuración para leyendas y ejes.
+fn row_label(row: &StrategyGridRow, with_market: bool) -> String {
+    let mut label = format!(
+        "{} ({}) ROI {} SL {} MOT {}",
+        row.strategy, row.timeframe, row.minimal_roi, row.stoploss, row.max_open_trades
+    );
+    if with_market {
+        label.push_str(&format!(" · {}", market_label(row)));
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(strategy: &str, monthly: &[(&str, f64)]) -> StrategyGridRow {
+        StrategyGridRow {
+            strategy: strategy.to_string(),
+            timeframe: "5m".to_string(),
+            exchange: "BINANCE".to_string(),
+            currency: "USDT".to_string(),
+            pairlist: "BTC".to_string(),
+            monthly: monthly.iter().map(|(m, p)| (m.to_string(), *p)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn heatmap_marks_missing_months() {
+        let rows = vec![
+            row("A", &[("20240201", 0.1), ("20240101", -0.2)]),
+            row("B", &[("20240301", 0.05)]),
+        ];
+        let heatmap = Heatmap::from_rows(&rows);
+
+        assert_eq!(heatmap.months, vec!["20240101", "20240201", "20240301"]);
+        assert_eq!(heatmap.rows[0].profits, vec![Some(-0.2), Some(0.1), None]);
+        assert_eq!(heatmap.rows[1].profits, vec![None, None, Some(0.05)]);
+        assert_eq!(heatmap.max_abs(), 0.2);
+        assert!(heatmap.rows[0].label.starts_with("A (5m)"));
+    }
+
+    #[test]
+    fn equity_keeps_flat_through_gaps() {
+        let rows = vec![
+            row("A", &[("20240101", 0.1), ("20240301", 0.1)]),
+            row("B", &[("20240201", -0.5)]),
+        ];
+        let (months, series) = equity_series(&rows, AccumulationMode::Compounded);
+
+        assert_eq!(months.len(), 3);
+        let a = &series[0].points;
+        assert_eq!(a.len(), 3);
+        assert!((a[0] - 1.1).abs() < 1e-9 && (a[1] - 1.1).abs() < 1e-9);
+        assert!((a[2] - 1.21).abs() < 1e-9);
+        assert_eq!(series[1].points, vec![1.0, 0.5, 0.5]);
+        assert_eq!(month_label("20240301"), "2024-03");
+    }
+}
@@ -0,0 +1,357 @@
+// src/backtest/drilldown.rs
+//
+// Detalle de una configuración del resumen: sus filas mensuales y, si la
+// fuente los guarda (tablas `backtest_pair` y `backtest_trade` que escribe
+// `cli import`), sus resultados por par y sus trades. Lo usan el
+// subcomando `show` de la CLI y la ventana de detalle de la GUI.
+
+use chrono::{DateTime, Utc};
+use csv::Writer;
+use std::collections::BTreeMap;
+use std::fs::File;
+
+use crate::backtest::aggregate::{row_key, StrategyKey};
+use crate::backtest::logic::{optional, resolve_timeranges};
+use crate::backtest::model::{BacktestRecord, GridQuery, StrategyGridRow};
+use crate::backtest::timerange::generate_timeranges;
+use crate::error::{Error, Result};
+use crate::source::BacktestSource;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyDetail {
+    pub key: StrategyKey,
+    /// Periodos pedidos, en orden, tengan o no resultado.
+    pub periods: Vec<String>,
+    /// Filas mensuales de la configuración, por mercado y periodo.
+    pub months: Vec<BacktestRecord>,
+    /// `None` si la fuente no guarda resultados por par.
+    pub pairs: Option<Vec<PairDetail>>,
+    /// `None` si la fuente no guarda los trades.
+    pub trades: Option<Vec<TradeDetail>>,
+}
+
+/// Resultado de un par en un periodo (`backtest_pair`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairDetail {
+    /// `EXCHANGE/MONEDA/PAIRLIST` de la fila mensual.
+    pub market: String,
+    pub timerange: String,
+    pub pair: String,
+    pub trades: i32,
+    pub profit_mean: f64,
+    pub profit_total: f64,
+    pub profit_total_abs: f64,
+    pub wins: i32,
+    pub draws: i32,
+    pub losses: i32,
+    pub duration_avg: String,
+}
+
+/// Un trade de un periodo (`backtest_trade`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeDetail {
+    pub market: String,
+    pub timerange: String,
+    pub pair: String,
+    pub is_short: bool,
+    pub open_date: Option<DateTime<Utc>>,
+    pub close_date: Option<DateTime<Utc>>,
+    pub open_rate: f64,
+    pub close_rate: Option<f64>,
+    pub amount: f64,
+    pub stake_amount: f64,
+    pub profit_ratio: f64,
+    pub profit_abs: f64,
+    /// Minutos.
+    pub trade_duration: Option<i32>,
+    pub exit_reason: Option<String>,
+    pub enter_tag: Option<String>,
+}
+
+/// Resultados de un par sumados en todos los periodos.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PairTotals {
+    pub market: String,
+    pub pair: String,
+    pub periods: usize,
+    pub trades: i32,
+    pub wins: i32,
+    pub draws: i32,
+    pub losses: i32,
+    /// Suma de los ratios de cada periodo.
+    pub profit_total: f64,
+    pub profit_total_abs: f64,
+}
+
+impl StrategyDetail {
+    /// Detalle con solo las filas mensuales.
+    pub fn new(key: StrategyKey, periods: Vec<String>, months: Vec<BacktestRecord>) -> Self {
+        Self {
+            key,
+            periods,
+            months,
+            pairs: None,
+            trades: None,
+        }
+    }
+
+    /// Periodos pedidos sin fila de la configuración en ningún mercado.
+    pub fn gaps(&self) -> Vec<&str> {
+        self.periods
+            .iter()
+            .filter(|period| !self.months.iter().any(|m| m.timerange == **period))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Totales por mercado y par, de más a menos profit absoluto.
+    pub fn pair_totals(&self) -> Vec<PairTotals> {
+        let mut totals: BTreeMap<(&str, &str), PairTotals> = BTreeMap::new();
+        for pair in self.pairs.iter().flatten() {
+            let total = totals
+                .entry((&pair.market, &pair.pair))
+                .or_insert_with(|| PairTotals {
+                    market: pair.market.clone(),
+                    pair: pair.pair.clone(),
+                    ..Default::default()
+                });
+            total.periods += 1;
+            total.trades += pair.trades;
+            total.wins += pair.wins;
+            total.draws += pair.draws;
+            total.losses += pair.losses;
+            total.profit_total += pair.profit_total;
+            total.profit_total_abs += pair.profit_total_abs;
+        }
+        let mut totals: Vec<PairTotals> = totals.into_values().collect();
+        totals.sort_by(|a, b| b.profit_total_abs.total_cmp(&a.profit_total_abs));
+        totals
+    }
+}
+
+/// Consulta de `params` limitada al mercado de `row`.
+pub fn row_query(params: &GridQuery, row: &StrategyGridRow) -> GridQuery {
+    GridQuery {
+        exchanges: vec![row.exchange.clone()],
+        currencies: vec![row.currency.clone()],
+        pairlists: vec![row.pairlist.clone()],
+        ..params.clone()
+    }
+}
+
+/// Detalle de la configuración `key` en los mercados y periodos de
+/// `params`. Solo se leen los periodos con datos en la fuente.
+pub async fn load_detail<S: BacktestSource>(
+    source: &S,
+    params: &GridQuery,
+    key: &StrategyKey,
+) -> Result<StrategyDetail> {
+    let timeranges = resolve_timeranges(source, params).await?;
+    let mut detail = if timeranges.present.is_empty() {
+        StrategyDetail::new(key.clone(), Vec::new(), Vec::new())
+    } else {
+        source.fetch_detail(params, &timeranges.present, key).await?
+    };
+    detail.periods = generate_timeranges(params)?;
+    detail.months.sort_by(|a, b| {
+        (&a.exchange, &a.stake_currency, &a.pairlist, &a.timerange).cmp(&(
+            &b.exchange,
+            &b.stake_currency,
+            &b.pairlist,
+            &b.timerange,
+        ))
+    });
+    Ok(detail)
+}
+
+/// Detalle de una fila del resumen en su mercado.
+pub async fn load_row_detail<S: BacktestSource>(
+    source: &S,
+    params: &GridQuery,
+    row: &StrategyGridRow,
+) -> Result<StrategyDetail> {
+    load_detail(source, &row_query(params, row), &row_key(row)).await
+}
+
+pub fn export_trades_to_csv(trades: &[TradeDetail], filename: &str) -> Result<()> {
+    let file = File::create(filename).map_err(|e| Error::export(filename, e))?;
+    let mut wtr = Writer::from_writer(file);
+
+    wtr.write_record([
+        "market",
+        "timerange",
+        "pair",
+        "is_short",
+        "open_date",
+        "close_date",
+        "open_rate",
+        "close_rate",
+        "amount",
+        "stake_amount",
+        "profit_ratio",
+        "profit_abs",
+        "trade_duration",
+        "exit_reason",
+        "enter_tag",
+    ])
+    .map_err(|e| Error::export(filename, e))?;
+
+    let date = |date: Option<DateTime<Utc>>| date.map(|d| d.to_rfc3339()).unwrap_or_default();
+    for trade in trades {
+        wtr.write_record([
+            trade.market.as_str(),
+            &trade.timerange,
+            &trade.pair,
+            &trade.is_short.to_string(),
+            &date(trade.open_date),
+            &date(trade.close_date),
+            &trade.open_rate.to_string(),
+            &optional(trade.close_rate),
+            &trade.amount.to_string(),
+            &trade.stake_amount.to_string(),
+            &trade.profit_ratio.to_string(),
+            &trade.profit_abs.to_string(),
+            &trade.trade_duration.map(|d| d.to_string()).unwrap_or_default(),
+            trade.exit_reason.as_deref().unwrap_or_default(),
+            trade.enter_tag.as_deref().unwrap_or_default(),
+        ])
+        .map_err(|e| Error::export(filename, e))?;
+    }
+
+    wtr.flush().map_err(|e| Error::export(filename, e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::aggregate::{strategy_key, summarize_records};
+    use crate::backtest::model::AggregationMode;
+    use crate::source::matches_query;
+
+    struct MemorySource(Vec<BacktestRecord>);
+
+    impl BacktestSource for MemorySource {
+        async fn fetch(
+            &self,
+            params: &GridQuery,
+            timeranges: &[String],
+        ) -> Result<Vec<BacktestRecord>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|r| {
+                    matches_query(
+                        params,
+                        Some(timeranges),
+                        &r.exchange,
+                        &r.stake_currency,
+                        &r.pairlist,
+                        &r.timerange,
+                    )
+                })
+                .cloned()
+                .collect())
+        }
+
+        async fn available_timeranges(&self, _params: &GridQuery) -> Result<Vec<String>> {
+            Ok(self.0.iter().map(|r| r.timerange.clone()).collect())
+        }
+    }
+
+    fn record(strategy: &str, pairlist: &str, timerange: &str) -> BacktestRecord {
+        BacktestRecord {
+            strategy: strategy.to_string(),
+            timeframe: "5m".to_string(),
+            minimal_roi: "{}".to_string(),
+            stoploss: "-0.1".to_string(),
+            max_open_trades: 3,
+            trailing_stop: true,
+            trailing_stop_positive: 0.01,
+            trailing_stop_positive_offset: 0.02,
+            trailing_only_offset_is_reached: false,
+            entry_pricing: "same".to_string(),
+            exit_pricing: "same".to_string(),
+            check_depth_of_market_enable: false,
+            profit_total: 0.05,
+            total_trades: 10,
+            wins: 5,
+            winner_holding_avg_s: None,
+            max_drawdown: None,
+            rejected_signals: None,
+            timerange: timerange.to_string(),
+            exchange: "BINANCE".to_string(),
+            stake_currency: "USDT".to_string(),
+            pairlist: pairlist.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn row_detail_keeps_its_configuration_and_market() {
+        let records = vec![
+            record("A", "BTC", "20240201-20240301"),
+            record("A", "BTC", "20240101-20240201"),
+            record("A", "ETH", "20240101-20240201"),
+            record("B", "BTC", "20240101-20240201"),
+        ];
+        let query = GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string(), "ETH".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 3,
+            aggregation: AggregationMode::Client,
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        };
+        let timeranges = generate_timeranges(&query).unwrap();
+        let row = summarize_records(records.clone(), &query, &timeranges)
+            .into_iter()
+            .find(|row| row.strategy == "A" && row.pairlist == "BTC")
+            .unwrap();
+        assert_eq!(row_key(&row), strategy_key(&records[0]));
+
+        let detail = load_row_detail(&MemorySource(records), &query, &row)
+            .await
+            .unwrap();
+        let months: Vec<&str> = detail.months.iter().map(|m| m.timerange.as_str()).collect();
+        assert_eq!(months, vec!["20240101-20240201", "20240201-20240301"]);
+        assert!(detail.months.iter().all(|m| m.pairlist == "BTC"));
+        assert_eq!(detail.gaps(), vec!["20240301-20240401"]);
+        assert_eq!(detail.pairs, None);
+        assert!(detail.pair_totals().is_empty());
+    }
+
+    #[test]
+    fn pair_totals_add_up_every_period() {
+        let pair = |pair: &str, timerange: &str, profit_total_abs: f64| PairDetail {
+            market: "BINANCE/USDT/BTC,ETH".to_string(),
+            timerange: timerange.to_string(),
+            pair: pair.to_string(),
+            trades: 4,
+            profit_mean: 0.01,
+            profit_total: profit_total_abs / 1000.0,
+            profit_total_abs,
+            wins: 3,
+            draws: 0,
+            losses: 1,
+            duration_avg: "1:00:00".to_string(),
+        };
+        let mut detail = StrategyDetail::new(row_key(&Default::default()), Vec::new(), Vec::new());
+        detail.pairs = Some(vec![
+            pair("BTC/USDT", "20240101-20240201", 10.0),
+            pair("ETH/USDT", "20240101-20240201", 30.0),
+            pair("BTC/USDT", "20240201-20240301", 25.0),
+        ]);
+
+        let totals = detail.pair_totals();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].pair, "BTC/USDT");
+        assert_eq!((totals[0].periods, totals[0].trades), (2, 8));
+        assert_eq!(totals[0].profit_total_abs, 35.0);
+        assert_eq!(totals[1].losses, 1);
+    }
+}
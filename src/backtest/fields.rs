@@ -0,0 +1,340 @@
+// src/backtest/fields.rs
+//
+// Campos de `StrategyGridRow` por nombre, para las fórmulas de puntuación, los
+// filtros y cualquier otra expresión escrita por el usuario: métricas
+// numéricas, campos de texto e indicadores booleanos. Los nombres son los de
+// los campos (y las columnas del CSV); algunos tienen un alias corto.
+
+use std::borrow::Cow;
+
+use crate::backtest::markets::market_label;
+use crate::backtest::model::StrategyGridRow;
+
+/// Una métrica con nombre. `read` devuelve `None` si la fila no la tiene
+/// (p. ej. un stoploss que no se pudo interpretar).
+#[derive(Debug)]
+pub struct Metric {
+    pub name: &'static str,
+    /// Fracción (`0.15` es un 15 %), como el profit o el drawdown.
+    pub ratio: bool,
+    read: fn(&StrategyGridRow) -> Option<f64>,
+}
+
+impl Metric {
+    /// Busca por nombre de campo o alias, sin distinguir mayúsculas.
+    pub fn get(name: &str) -> Option<&'static Metric> {
+        let name = resolve(name);
+        METRICS.iter().find(|metric| metric.name == name)
+    }
+
+    pub fn all() -> &'static [Metric] {
+        METRICS
+    }
+
+    pub fn value(&self, row: &StrategyGridRow) -> Option<f64> {
+        (self.read)(row)
+    }
+}
+
+/// Campo de texto: estrategia, timeframe, mercado...
+#[derive(Debug)]
+pub struct TextField {
+    pub name: &'static str,
+    read: fn(&StrategyGridRow) -> Cow<'_, str>,
+}
+
+impl TextField {
+    pub fn get(name: &str) -> Option<&'static TextField> {
+        let name = resolve(name);
+        TEXT_FIELDS.iter().find(|field| field.name == name)
+    }
+
+    pub fn all() -> &'static [TextField] {
+        TEXT_FIELDS
+    }
+
+    pub fn value<'a>(&self, row: &'a StrategyGridRow) -> Cow<'a, str> {
+        (self.read)(row)
+    }
+}
+
+/// Indicador booleano: trailing stop activado, pico aislado...
+#[derive(Debug)]
+pub struct Flag {
+    pub name: &'static str,
+    read: fn(&StrategyGridRow) -> bool,
+}
+
+impl Flag {
+    pub fn get(name: &str) -> Option<&'static Flag> {
+        let name = resolve(name);
+        FLAGS.iter().find(|flag| flag.name == name)
+    }
+
+    pub fn all() -> &'static [Flag] {
+        FLAGS
+    }
+
+    pub fn value(&self, row: &StrategyGridRow) -> bool {
+        (self.read)(row)
+    }
+}
+
+/// Nombre de campo en minúsculas, con los alias ya sustituidos.
+fn resolve(name: &str) -> String {
+    let name = name.to_lowercase();
+    ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, field)| field.to_string())
+        .unwrap_or(name)
+}
+
+const ALIASES: &[(&str, &str)] = &[
+    ("profit", "total_profit"),
+    ("trades", "total_trades"),
+    ("drawdown", "max_drawdown"),
+    ("pf", "profit_factor"),
+    ("stoploss", "stoploss_value"),
+    ("roi", "initial_roi"),
+    ("tf", "timeframe"),
+    ("ts", "trailing_stop"),
+    ("toor", "trailing_only_offset_is_reached"),
+    ("depth_mkt", "check_depth_of_market_enable"),
+];
+
+static METRICS: &[Metric] = &[
+    Metric {
+        name: "total_profit",
+        ratio: true,
+        read: |r| Some(r.total_profit),
+    },
+    Metric {
+        name: "total_trades",
+        ratio: false,
+        read: |r| Some(r.total_trades as f64),
+    },
+    Metric {
+        name: "wins",
+        ratio: false,
+        read: |r| Some(r.wins as f64),
+    },
+    Metric {
+        name: "losses",
+        ratio: false,
+        read: |r| Some(r.losses as f64),
+    },
+    Metric {
+        name: "win_rate",
+        ratio: true,
+        read: |r| Some(r.win_rate),
+    },
+    Metric {
+        name: "loss_rate",
+        ratio: true,
+        read: |r| Some(r.loss_rate),
+    },
+    Metric {
+        name: "win_time",
+        ratio: false,
+        read: |r| Some(r.win_time),
+    },
+    Metric {
+        name: "drawdown_perc",
+        ratio: true,
+        read: |r| Some(r.drawdown_perc),
+    },
+    Metric {
+        name: "rejected_signals",
+        ratio: false,
+        read: |r| Some(r.rejected_signals),
+    },
+    Metric {
+        name: "neg_months",
+        ratio: false,
+        read: |r| Some(r.neg_months as f64),
+    },
+    Metric {
+        name: "avg_monthly_profit",
+        ratio: true,
+        read: |r| Some(r.avg_monthly_profit),
+    },
+    Metric {
+        name: "std_monthly_profit",
+        ratio: true,
+        read: |r| Some(r.std_monthly_profit),
+    },
+    Metric {
+        name: "max_profit_month",
+        ratio: true,
+        read: |r| Some(r.max_profit_month),
+    },
+    Metric {
+        name: "min_profit_month",
+        ratio: true,
+        read: |r| Some(r.min_profit_month),
+    },
+    Metric {
+        name: "avg_trade_profit",
+        ratio: true,
+        read: |r| Some(r.avg_trade_profit),
+    },
+    Metric {
+        name: "expectancy",
+        ratio: true,
+        read: |r| Some(r.expectancy),
+    },
+    Metric {
+        name: "profit_factor",
+        ratio: false,
+        read: |r| Some(r.profit_factor),
+    },
+    Metric {
+        name: "sharpe",
+        ratio: false,
+        read: |r| Some(r.sharpe),
+    },
+    Metric {
+        name: "sortino",
+        ratio: false,
+        read: |r| Some(r.sortino),
+    },
+    Metric {
+        name: "calmar",
+        ratio: false,
+        read: |r| Some(r.calmar),
+    },
+    Metric {
+        name: "ulcer_index",
+        ratio: false,
+        read: |r| Some(r.ulcer_index),
+    },
+    Metric {
+        name: "recovery_factor",
+        ratio: false,
+        read: |r| Some(r.recovery_factor),
+    },
+    Metric {
+        name: "max_losing_streak",
+        ratio: false,
+        read: |r| Some(r.max_losing_streak as f64),
+    },
+    Metric {
+        name: "equity_return",
+        ratio: true,
+        read: |r| Some(r.equity_return),
+    },
+    Metric {
+        name: "max_drawdown",
+        ratio: true,
+        read: |r| Some(r.max_drawdown),
+    },
+    Metric {
+        name: "max_drawdown_months",
+        ratio: false,
+        read: |r| Some(r.max_drawdown_months as f64),
+    },
+    Metric {
+        name: "coverage",
+        ratio: true,
+        read: |r| Some(r.coverage.ratio()),
+    },
+    Metric {
+        name: "neighbours",
+        ratio: false,
+        read: |r| Some(r.sensitivity.neighbours as f64),
+    },
+    Metric {
+        name: "profit_stability",
+        ratio: true,
+        read: |r| Some(r.sensitivity.profit_stability),
+    },
+    Metric {
+        name: "drawdown_stability",
+        ratio: true,
+        read: |r| Some(r.sensitivity.drawdown_stability),
+    },
+    Metric {
+        name: "max_open_trades",
+        ratio: false,
+        read: |r| Some(r.max_open_trades as f64),
+    },
+    Metric {
+        name: "stoploss_value",
+        ratio: true,
+        read: |r| r.stoploss_value,
+    },
+    Metric {
+        name: "initial_roi",
+        ratio: true,
+        read: |r| r.initial_roi(),
+    },
+    Metric {
+        name: "trailing_stop_positive",
+        ratio: true,
+        read: |r| r.trailing_stop_positive,
+    },
+    Metric {
+        name: "trailing_stop_positive_offset",
+        ratio: true,
+        read: |r| r.trailing_stop_positive_offset,
+    },
+];
+
+static TEXT_FIELDS: &[TextField] = &[
+    TextField {
+        name: "strategy",
+        read: |r| Cow::Borrowed(&r.strategy),
+    },
+    TextField {
+        name: "timeframe",
+        read: |r| Cow::Borrowed(&r.timeframe),
+    },
+    TextField {
+        name: "exchange",
+        read: |r| Cow::Borrowed(&r.exchange),
+    },
+    TextField {
+        name: "currency",
+        read: |r| Cow::Borrowed(&r.currency),
+    },
+    TextField {
+        name: "pairlist",
+        read: |r| Cow::Borrowed(&r.pairlist),
+    },
+    TextField {
+        name: "market",
+        read: |r| Cow::Owned(market_label(r)),
+    },
+    TextField {
+        name: "minimal_roi",
+        read: |r| Cow::Borrowed(&r.minimal_roi),
+    },
+    TextField {
+        name: "entry_price",
+        read: |r| Cow::Borrowed(&r.entry_price),
+    },
+    TextField {
+        name: "exit_price",
+        read: |r| Cow::Borrowed(&r.exit_price),
+    },
+];
+
+static FLAGS: &[Flag] = &[
+    Flag {
+        name: "trailing_stop",
+        read: |r| r.trailing_stop,
+    },
+    Flag {
+        name: "trailing_only_offset_is_reached",
+        read: |r| r.trailing_only_offset_is_reached,
+    },
+    Flag {
+        name: "check_depth_of_market_enable",
+        read: |r| r.check_depth_of_market_enable,
+    },
+    Flag {
+        name: "isolated_peak",
+        read: |r| r.sensitivity.isolated_peak,
+    },
+];
@@ -0,0 +1,462 @@
+// src/backtest/filter.rs
+//
+// Filtros escritos por el usuario sobre las filas del resumen, como
+//
+//     trades >= 200 && drawdown < 0.15 && timeframe in ["5m", "15m"]
+//
+// La expresión se interpreta una vez como árbol (`Expr`) y se evalúa contra
+// cada `StrategyGridRow`. Los nombres de campo son los de `fields`; las
+// comparaciones de texto no distinguen mayúsculas. Los ratios son fracciones
+// (`0.15`) o porcentajes con `%` (`15%`).
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::backtest::fields::{Flag, Metric, TextField};
+use crate::backtest::lexer::{check_ratio, expression_error, tokenize, Token};
+use crate::backtest::model::StrategyGridRow;
+use crate::backtest::scoring::Comparison;
+use crate::error::{Error, Result};
+
+/// Lado de una comparación.
+#[derive(Debug, Clone)]
+pub enum Operand {
+    Number(f64),
+    Text(String),
+    Metric(&'static Metric),
+    Field(&'static TextField),
+}
+
+impl Operand {
+    fn is_numeric(&self) -> bool {
+        matches!(self, Operand::Number(_) | Operand::Metric(_))
+    }
+
+    fn number(&self, row: &StrategyGridRow) -> Option<f64> {
+        match self {
+            Operand::Number(value) => Some(*value),
+            Operand::Metric(metric) => metric.value(row),
+            _ => None,
+        }
+    }
+
+    fn text(&self, row: &StrategyGridRow) -> Option<String> {
+        match self {
+            Operand::Text(text) => Some(text.to_lowercase()),
+            Operand::Field(field) => Some(field.value(row).to_lowercase()),
+            _ => None,
+        }
+    }
+
+    /// Igualdad numérica o de texto sin mayúsculas; una métrica ausente no
+    /// es igual a nada.
+    fn equals(&self, other: &Operand, row: &StrategyGridRow) -> bool {
+        if self.is_numeric() {
+            matches!((self.number(row), other.number(row)), (Some(a), Some(b)) if a == b)
+        } else {
+            matches!((self.text(row), other.text(row)), (Some(a), Some(b)) if a == b)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Flag(&'static Flag),
+    Compare {
+        left: Operand,
+        comparison: Comparison,
+        right: Operand,
+    },
+    /// `campo in [valor, ...]`
+    In {
+        operand: Operand,
+        values: Vec<Operand>,
+    },
+    /// `campo ~ "texto"`: el texto aparece en el campo.
+    Contains {
+        field: Operand,
+        needle: Operand,
+    },
+}
+
+impl Expr {
+    pub fn matches(&self, row: &StrategyGridRow) -> bool {
+        match self {
+            Expr::And(left, right) => left.matches(row) && right.matches(row),
+            Expr::Or(left, right) => left.matches(row) || right.matches(row),
+            Expr::Not(inner) => !inner.matches(row),
+            Expr::Flag(flag) => flag.value(row),
+            Expr::Compare {
+                left,
+                comparison,
+                right,
+            } => {
+                if left.is_numeric() {
+                    match (left.number(row), right.number(row)) {
+                        (Some(a), Some(b)) => comparison.holds(a, b),
+                        _ => false,
+                    }
+                } else {
+                    let equal = left.equals(right, row);
+                    match comparison {
+                        Comparison::NotEqual => !equal,
+                        _ => equal,
+                    }
+                }
+            }
+            Expr::In { operand, values } => values.iter().any(|value| operand.equals(value, row)),
+            Expr::Contains { field, needle } => match (field.text(row), needle.text(row)) {
+                (Some(haystack), Some(needle)) => haystack.contains(&needle),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Expresión ya interpretada junto con su texto original.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    input: String,
+    pub expr: Expr,
+}
+
+impl Filter {
+    pub fn matches(&self, row: &StrategyGridRow) -> bool {
+        self.expr.matches(row)
+    }
+}
+
+/// Deja en `rows` solo las filas que cumplen `filter`.
+pub fn apply(rows: &mut Vec<StrategyGridRow>, filter: &Filter) {
+    rows.retain(|row| filter.matches(row));
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut parser = Parser {
+            input,
+            tokens: tokenize(input, 0..input.len())?,
+            next: 0,
+        };
+        let expr = parser.or()?;
+        if parser.next < parser.tokens.len() {
+            return Err(parser.error("sobra texto tras la expresión"));
+        }
+        Ok(Self {
+            input: input.trim().to_string(),
+            expr,
+        })
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.input)
+    }
+}
+
+/// Descenso recursivo, de menor a mayor precedencia:
+///
+/// ```text
+/// or      := and { ("||" | "or") and }
+/// and     := not { ("&&" | "and") not }
+/// not     := ("!" | "not") not | primary
+/// primary := "(" or ")" | flag | operand (comparación operand | "~" operand | "in" lista)
+/// ```
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    /// Consume el siguiente símbolo si es `token`.
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Posición del siguiente símbolo, o el final del texto.
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.input.len(), |(position, _)| *position)
+    }
+
+    fn error(&self, message: &str) -> Error {
+        expression_error(self.input, self.position(), message)
+    }
+
+    fn expect(&mut self, token: Token, message: &str) -> Result<()> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut left = self.and()?;
+        while self.eat(&Token::Or) {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut left = self.not()?;
+        while self.eat(&Token::And) {
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.eat(&Token::Not) {
+            Ok(Expr::Not(Box::new(self.not()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        if self.eat(&Token::LParen) {
+            let expr = self.or()?;
+            self.expect(Token::RParen, "se esperaba ')'")?;
+            return Ok(expr);
+        }
+        if let Some(Token::Ident(name)) = self.peek() {
+            if let Some(flag) = Flag::get(name) {
+                self.next += 1;
+                return Ok(Expr::Flag(flag));
+            }
+        }
+
+        let left_at = self.position();
+        let left = self.operand()?;
+        let left_bare = self.after_bare_number();
+        let at = self.position();
+        match self.peek().cloned() {
+            Some(Token::Compare(comparison)) => {
+                self.next += 1;
+                let right_at = self.position();
+                let right = self.operand()?;
+                self.check_ratio(&left, &right, self.after_bare_number(), right_at)?;
+                self.check_ratio(&right, &left, left_bare, left_at)?;
+                if left.is_numeric() != right.is_numeric() {
+                    return Err(expression_error(
+                        self.input,
+                        at,
+                        "no se puede comparar un número con un texto",
+                    ));
+                }
+                if !left.is_numeric()
+                    && !matches!(comparison, Comparison::Equal | Comparison::NotEqual)
+                {
+                    return Err(expression_error(
+                        self.input,
+                        at,
+                        "los textos solo admiten '==' y '!='",
+                    ));
+                }
+                Ok(Expr::Compare {
+                    left,
+                    comparison,
+                    right,
+                })
+            }
+            Some(Token::Tilde) => {
+                self.next += 1;
+                let needle = self.operand()?;
+                if left.is_numeric() || needle.is_numeric() {
+                    return Err(expression_error(self.input, at, "'~' solo admite textos"));
+                }
+                Ok(Expr::Contains {
+                    field: left,
+                    needle,
+                })
+            }
+            Some(Token::In) => {
+                self.next += 1;
+                self.expect(Token::LBracket, "se esperaba '['")?;
+                let mut values = Vec::new();
+                if !self.eat(&Token::RBracket) {
+                    loop {
+                        let value_at = self.position();
+                        let value = self.operand()?;
+                        self.check_ratio(&left, &value, self.after_bare_number(), value_at)?;
+                        if value.is_numeric() != left.is_numeric() {
+                            return Err(expression_error(
+                                self.input,
+                                value_at,
+                                "la lista mezcla números y textos",
+                            ));
+                        }
+                        values.push(value);
+                        if self.eat(&Token::RBracket) {
+                            break;
+                        }
+                        self.expect(Token::Comma, "se esperaba ',' o ']'")?;
+                    }
+                }
+                Ok(Expr::In {
+                    operand: left,
+                    values,
+                })
+            }
+            _ => Err(self.error("se esperaba una comparación, '~' o 'in'")),
+        }
+    }
+
+    /// El último símbolo consumido es un número sin `%`.
+    fn after_bare_number(&self) -> bool {
+        matches!(self.tokens.get(self.next - 1), Some((_, Token::Number(_))))
+    }
+
+    /// `metric` comparada con `number`, escrito sin `%` si `bare`, en `at`.
+    fn check_ratio(&self, metric: &Operand, number: &Operand, bare: bool, at: usize) -> Result<()> {
+        match (metric, number) {
+            (Operand::Metric(metric), Operand::Number(value)) if bare => {
+                check_ratio(self.input, metric, *value, at)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        let at = self.position();
+        let operand = match self.peek() {
+            Some(Token::Number(value) | Token::Percent(value)) => Operand::Number(*value),
+            Some(Token::Minus) => match self.tokens.get(self.next + 1) {
+                Some((_, Token::Number(value) | Token::Percent(value))) => {
+                    self.next += 1;
+                    Operand::Number(-value)
+                }
+                _ => return Err(self.error("se esperaba un número tras '-'")),
+            },
+            Some(Token::Text(text)) => Operand::Text(text.clone()),
+            Some(Token::Ident(name)) => {
+                if let Some(metric) = Metric::get(name) {
+                    Operand::Metric(metric)
+                } else if let Some(field) = TextField::get(name) {
+                    Operand::Field(field)
+                } else {
+                    return Err(expression_error(
+                        self.input,
+                        at,
+                        &format!("campo desconocido '{}'", name),
+                    ));
+                }
+            }
+            _ => return Err(self.error("se esperaba un campo, un número o un texto")),
+        };
+        self.next += 1;
+        Ok(operand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(timeframe: &str, trades: i32, drawdown: f64, trailing_stop: bool) -> StrategyGridRow {
+        StrategyGridRow {
+            strategy: "SuperTrend".to_string(),
+            timeframe: timeframe.to_string(),
+            exchange: "BINANCE".to_string(),
+            total_trades: trades,
+            max_drawdown: drawdown,
+            trailing_stop,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn expression_is_evaluated_against_rows() {
+        let filter: Filter = r#"trades >= 200 && drawdown < 0.15 && timeframe in ["5m", "15m"]"#
+            .parse()
+            .unwrap();
+        assert!(filter.matches(&row("5m", 250, 0.1, false)));
+        assert!(!filter.matches(&row("1h", 250, 0.1, false)));
+        assert!(!filter.matches(&row("15m", 150, 0.1, false)));
+
+        // Los ratios también se escriben en porcentaje
+        let filter: Filter = "drawdown < 15%".parse().unwrap();
+        assert!(filter.matches(&row("5m", 10, 0.1, false)));
+        assert!(!filter.matches(&row("5m", 10, 0.2, false)));
+
+        // `&&` liga más que `||`; los textos no distinguen mayúsculas
+        let filter: Filter = "exchange == 'binance' and !ts || trades > 1000"
+            .parse()
+            .unwrap();
+        assert!(filter.matches(&row("5m", 10, 0.1, false)));
+        assert!(!filter.matches(&row("5m", 10, 0.1, true)));
+        assert!(filter.matches(&row("5m", 2000, 0.1, true)));
+
+        let mut rows = vec![row("5m", 10, 0.1, false), row("1h", 10, 0.1, false)];
+        apply(
+            &mut rows,
+            &"strategy ~ 'trend' && tf != '5m'".parse().unwrap(),
+        );
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].timeframe, "1h");
+
+        // Una métrica ausente no cumple ninguna comparación
+        assert!(!"stoploss >= -0.2"
+            .parse::<Filter>()
+            .unwrap()
+            .matches(&row("5m", 10, 0.1, false)));
+    }
+
+    #[test]
+    fn errors_report_their_position() {
+        let position = |input: &str| match input.parse::<Filter>() {
+            Err(Error::Expression { position, .. }) => position,
+            other => panic!("se esperaba Expression, llegó {:?}", other),
+        };
+        assert_eq!(position("trades >= 200 && beneficio > 0"), 17);
+        assert_eq!(position("timeframe > '5m'"), 10);
+        assert_eq!(position("trades == '5m'"), 7);
+        assert_eq!(position("(trades > 1"), 11);
+        assert_eq!(position("trades > 1 trades"), 11);
+        assert_eq!(position("strategy ~ 'abc"), 11);
+
+        // `position` cuenta bytes; el mensaje, caracteres
+        let input = "strategy ~ 'señal' && beneficio > 0";
+        assert_eq!(position(input), 23);
+        let message = input.parse::<Filter>().unwrap_err().to_string();
+        assert!(message.contains("en la posición 22:"), "{}", message);
+    }
+
+    #[test]
+    fn ratios_compared_with_bare_numbers_need_percent() {
+        let input = "trades >= 200 && drawdown < 15";
+        match input.parse::<Filter>() {
+            Err(error @ Error::Expression { position: 28, .. }) => {
+                assert!(error.to_string().contains("15%"), "{}", error)
+            }
+            other => panic!("se esperaba Expression en 28, llegó {:?}", other),
+        }
+        for input in ["15 < drawdown", "profit > -2", "drawdown in [0.1, 20]"] {
+            assert!(input.parse::<Filter>().is_err(), "{}", input);
+        }
+
+        // Con `%`, dentro de [-1, 1] o sobre métricas que no son ratios, vale
+        for input in ["drawdown < 15%", "drawdown < 0.15", "stoploss >= -1", "trades > 15"] {
+            assert!(input.parse::<Filter>().is_ok(), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+// src/backtest/hyperparams.rs
+//
+// Hiperparámetros que la tabla `backtest` guarda como texto: la tabla ROI
+// (`{"0": 0.1, "30": 0.05}`, minutos → ratio) y el stoploss (`-0.1`). Se
+// interpretan una vez al construir cada fila; los valores que no se pueden
+// interpretar se informan en lugar de sustituirse por un número inventado.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::backtest::model::StrategyGridRow;
+use crate::error::{Error, Result};
+
+/// Tabla `minimal_roi` de freqtrade: a partir de cada minuto, el ratio de
+/// beneficio con el que se cierra la operación.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RoiTable(BTreeMap<u32, f64>);
+
+impl RoiTable {
+    /// Ratio vigente tras `minutes` minutos: el del mayor minuto que no lo supera.
+    pub fn ratio_at(&self, minutes: u32) -> Option<f64> {
+        self.0
+            .range(..=minutes)
+            .next_back()
+            .map(|(_, ratio)| *ratio)
+    }
+
+    /// Ratio al abrir la operación (minuto 0).
+    pub fn initial(&self) -> Option<f64> {
+        self.ratio_at(0)
+    }
+
+    pub fn steps(&self) -> impl Iterator<Item = (u32, f64)> + '_ {
+        self.0.iter().map(|(minutes, ratio)| (*minutes, *ratio))
+    }
+}
+
+impl FromStr for RoiTable {
+    type Err = Error;
+
+    /// Acepta el JSON que escribe freqtrade y también comillas simples.
+    fn from_str(input: &str) -> Result<Self> {
+        let invalid = |reason: String| Error::Hyperparameter {
+            field: "minimal_roi",
+            input: input.to_string(),
+            reason,
+        };
+        let raw: BTreeMap<String, f64> =
+            serde_json::from_str(&input.replace('\'', "\"")).map_err(|e| invalid(e.to_string()))?;
+
+        let mut steps = BTreeMap::new();
+        for (minutes, ratio) in raw {
+            let minutes = minutes
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| invalid(format!("'{}' no es un número de minutos", minutes)))?;
+            if !ratio.is_finite() {
+                return Err(invalid(format!("ratio no finito en el minuto {}", minutes)));
+            }
+            steps.insert(minutes, ratio);
+        }
+        Ok(RoiTable(steps))
+    }
+}
+
+/// Mismo formato que escribe el importador: `{"0": 0.1, "30": 0.05}`.
+impl fmt::Display for RoiTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let body = self
+            .0
+            .iter()
+            .map(|(minutes, ratio)| format!("\"{}\": {}", minutes, ratio))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{{{}}}", body)
+    }
+}
+
+/// Stoploss como ratio negativo entre -1 y 0.
+pub fn parse_stoploss(input: &str) -> Result<f64> {
+    let invalid = |reason: &str| Error::Hyperparameter {
+        field: "stoploss",
+        input: input.to_string(),
+        reason: reason.to_string(),
+    };
+    let value: f64 = input
+        .trim()
+        .parse()
+        .map_err(|_| invalid("no es un número"))?;
+    if !(-1.0..=0.0).contains(&value) {
+        return Err(invalid("debe estar entre -1 y 0"));
+    }
+    Ok(value)
+}
+
+/// Un error por cada ROI o stoploss distinto de `rows` que no se pudo
+/// interpretar, en orden de campo y texto.
+pub fn invalid_hyperparameters(rows: &[StrategyGridRow]) -> Vec<Error> {
+    let roi: BTreeSet<&str> = rows
+        .iter()
+        .filter(|row| row.roi_table.is_none())
+        .map(|row| row.minimal_roi.as_str())
+        .collect();
+    let stoploss: BTreeSet<&str> = rows
+        .iter()
+        .filter(|row| row.stoploss_value.is_none())
+        .map(|row| row.stoploss.as_str())
+        .collect();
+
+    roi.into_iter()
+        .filter_map(|input| input.parse::<RoiTable>().err())
+        .chain(
+            stoploss
+                .into_iter()
+                .filter_map(|input| parse_stoploss(input).err()),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roi_table_applies_the_latest_step() {
+        let roi: RoiTable = r#"{"30": 0.05, "0": 0.1, "120": 0}"#.parse().unwrap();
+        assert_eq!(roi.initial(), Some(0.1));
+        assert_eq!(roi.ratio_at(29), Some(0.1));
+        assert_eq!(roi.ratio_at(30), Some(0.05));
+        assert_eq!(roi.ratio_at(600), Some(0.0));
+        assert_eq!(roi.to_string(), r#"{"0": 0.1, "30": 0.05, "120": 0}"#);
+
+        let quoted: RoiTable = "{'0': 0.2}".parse().unwrap();
+        assert_eq!(quoted.initial(), Some(0.2));
+        assert_eq!("{}".parse::<RoiTable>().unwrap().initial(), None);
+    }
+
+    #[test]
+    fn unparsable_values_are_reported() {
+        assert!(matches!(
+            "{\"diez\": 0.1}".parse::<RoiTable>(),
+            Err(Error::Hyperparameter {
+                field: "minimal_roi",
+                ..
+            })
+        ));
+        assert!(matches!(
+            "0.1,0.2".parse::<RoiTable>(),
+            Err(Error::Hyperparameter { .. })
+        ));
+        assert_eq!(parse_stoploss(" -0.25 ").unwrap(), -0.25);
+        assert!(parse_stoploss("0.1").is_err());
+        assert!(parse_stoploss("sin stop").is_err());
+
+        let rows = [
+            StrategyGridRow {
+                minimal_roi: "roto".to_string(),
+                stoploss: "-0.1".to_string(),
+                stoploss_value: Some(-0.1),
+                ..Default::default()
+            },
+            StrategyGridRow {
+                minimal_roi: "roto".to_string(),
+                stoploss: "x".to_string(),
+                ..Default::default()
+            },
+        ];
+        let errors = invalid_hyperparameters(&rows);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[1].to_string().contains("'x'"));
+    }
+}
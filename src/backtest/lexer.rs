@@ -0,0 +1,176 @@
+// src/backtest/lexer.rs
+//
+// Análisis léxico común a los filtros (`filter`) y a las fórmulas de
+// puntuación (`scoring`). Cada gramática acepta solo los símbolos que usa y
+// da error en el resto con su posición.
+
+use std::ops::Range;
+
+use crate::backtest::fields::Metric;
+use crate::backtest::scoring::Comparison;
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// Sin signo: el `-` llega como `Minus` y lo aplica la gramática.
+    Number(f64),
+    /// Número con `%` detrás, ya dividido entre 100.
+    Percent(f64),
+    Text(String),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    And,
+    Or,
+    Not,
+    In,
+    Tilde,
+    Compare(Comparison),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+/// Símbolos de `input[range]` con su posición en bytes dentro de `input`.
+pub fn tokenize(input: &str, range: Range<usize>) -> Result<Vec<(usize, Token)>> {
+    let offset = range.start;
+    let chars: Vec<(usize, char)> = input[range].char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (at, c) = chars[i];
+        let position = offset + at;
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('>', Some('=')) => (Token::Compare(Comparison::GreaterOrEqual), 2),
+            ('<', Some('=')) => (Token::Compare(Comparison::LessOrEqual), 2),
+            ('=', Some('=')) => (Token::Compare(Comparison::Equal), 2),
+            ('!', Some('=')) => (Token::Compare(Comparison::NotEqual), 2),
+            ('>', _) => (Token::Compare(Comparison::Greater), 1),
+            ('<', _) => (Token::Compare(Comparison::Less), 1),
+            ('=', _) => return Err(expression_error(input, position, "se esperaba '=='")),
+            ('!', _) => (Token::Not, 1),
+            ('+', _) => (Token::Plus, 1),
+            ('-', _) => (Token::Minus, 1),
+            ('*', _) => (Token::Star, 1),
+            ('~', _) => (Token::Tilde, 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('[', _) => (Token::LBracket, 1),
+            (']', _) => (Token::RBracket, 1),
+            (',', _) => (Token::Comma, 1),
+            ('"' | '\'', _) => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|(_, quote)| *quote == c)
+                    .map(|len| i + 1 + len)
+                    .ok_or_else(|| expression_error(input, position, "texto sin cerrar"))?;
+                let text: String = chars[i + 1..end].iter().map(|(_, c)| c).collect();
+                (Token::Text(text), end + 1 - i)
+            }
+            (c, _) if c.is_ascii_digit() || c == '.' => {
+                let len = chars[i..]
+                    .iter()
+                    .position(|(_, c)| !(c.is_ascii_digit() || *c == '.'))
+                    .unwrap_or(chars.len() - i);
+                let text: String = chars[i..i + len].iter().map(|(_, c)| c).collect();
+                let number: f64 = text
+                    .parse()
+                    .map_err(|_| expression_error(input, position, "número inválido"))?;
+                // Los ratios son fracciones: `15%` es 0.15
+                match chars.get(i + len) {
+                    Some((_, '%')) => (Token::Percent(number / 100.0), len + 1),
+                    _ => (Token::Number(number), len),
+                }
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .position(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+                    .unwrap_or(chars.len() - i);
+                let word: String = chars[i..i + len].iter().map(|(_, c)| c).collect();
+                let token = match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "in" => Token::In,
+                    _ => Token::Ident(word),
+                };
+                (token, len)
+            }
+            (other, _) => {
+                return Err(expression_error(
+                    input,
+                    position,
+                    &format!("carácter inesperado '{}'", other),
+                ))
+            }
+        };
+        tokens.push((position, token));
+        i += len;
+    }
+    Ok(tokens)
+}
+
+pub fn expression_error(input: &str, position: usize, message: &str) -> Error {
+    Error::Expression {
+        input: input.to_string(),
+        position,
+        message: message.to_string(),
+    }
+}
+
+/// Un ratio comparado con un número sin `%` mayor que 1 casi siempre es un
+/// porcentaje mal escrito: `drawdown < 15` dejaría pasar todas las filas.
+pub fn check_ratio(input: &str, metric: &Metric, value: f64, position: usize) -> Result<()> {
+    if metric.ratio && value.abs() > 1.0 {
+        return Err(expression_error(
+            input,
+            position,
+            &format!(
+                "'{}' es un ratio: escribe {}% o {}",
+                metric.name,
+                value,
+                value / 100.0
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_are_relative_to_the_whole_input() {
+        let input = "profit; trades >= -10 && 'a b' 15%";
+        let tokens = tokenize(input, 8..input.len()).unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (8, Token::Ident("trades".to_string())),
+                (15, Token::Compare(Comparison::GreaterOrEqual)),
+                (18, Token::Minus),
+                (19, Token::Number(10.0)),
+                (22, Token::And),
+                (25, Token::Text("a b".to_string())),
+                (31, Token::Percent(0.15)),
+            ]
+        );
+        match tokenize(input, 0..input.len()) {
+            Err(Error::Expression { position, .. }) => assert_eq!(position, 6),
+            other => panic!("se esperaba Expression, llegó {:?}", other),
+        }
+    }
+}
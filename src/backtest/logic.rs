@@ -1,253 +1,151 @@
 // src/backtest/logic.rs
 
-use chrono::{Datelike, Months, NaiveDate};
 use csv::Writer;
-use deadpool_postgres::Client;
-use std::collections::HashMap;
 use std::collections::HashSet;
-use std::error::Error;
 use std::fs::File;
 
-use crate::backtest::model::{GridQuery, StrategyGridRow};
-use crate::backtest::sql::build_flat_sql;
-
-type StrategyKey = (
-    String,
-    String,
-    String,
-    String,
-    i32,
-    bool,
-    String,
-    String,
-    bool,
-    String,
-    String,
-    bool,
-);
-
-// --- FUNCIÓN PRINCIPAL MODIFICADA ---
-// Ahora acepta un `&Client` como primer argumento.
-pub async fn get_grid_summary(
-    client: &Client,
+use crate::backtest::aggregate::{no_progress, Progress, RecordGrouper};
+use crate::backtest::hyperparams::invalid_hyperparameters;
+use crate::backtest::model::{AggregationMode, CoveragePolicy, GridQuery, StrategyGridRow};
+use crate::backtest::planner::FetchPlan;
+use crate::backtest::sensitivity::apply_sensitivity;
+use crate::backtest::timerange::generate_timeranges;
+use crate::error::{Error, Result};
+use crate::source::BacktestSource;
+
+/// Periodos pedidos, separados según tengan filas en la fuente o no.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolvedTimeranges {
+    pub present: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+/// Cruza los periodos de `params` con los que existen en la fuente para el
+/// mismos exchanges, monedas y pairlists.
+pub async fn resolve_timeranges<S: BacktestSource>(
+    source: &S,
     params: &GridQuery,
-) -> Result<Vec<StrategyGridRow>, Box<dyn Error>> {
-    let timeranges = generate_timeranges(&params.start_date, params.months);
-    let sql = build_flat_sql(params, &timeranges);
-
-    // Ya no usamos el Singleton. El cliente viene como parámetro.
-    let rows = client.query(&sql, &[]).await?;
-
-    // El HashMap ahora usa nuestro alias para mayor claridad
-    let mut grouped: HashMap<StrategyKey, Vec<_>> = HashMap::new();
-
-    for row in rows {
-        let trailing_stop_positive: f64 = row.get("trailing_stop_positive");
-        let trailing_stop_positive_offset: f64 = row.get("trailing_stop_positive_offset");
-        let tsp_str = trailing_stop_positive.to_string();
-        let tspo_str = trailing_stop_positive_offset.to_string();
-
-        let key: StrategyKey = (
-            row.get("strategy"),
-            row.get("timeframe"),
-            row.get("minimal_roi"),
-            row.get("stoploss"),
-            row.get("max_open_trades"),
-            row.get("trailing_stop"),
-            tsp_str,
-            tspo_str,
-            row.get("trailing_only_offset_is_reached"),
-            row.get("entry_pricing"),
-            row.get("exit_pricing"),
-            row.get("check_depth_of_market_enable"),
-        );
+) -> Result<ResolvedTimeranges> {
+    let expected = generate_timeranges(params)?;
+    let available: HashSet<String> = source
+        .available_timeranges(params)
+        .await?
+        .into_iter()
+        .collect();
 
-        grouped.entry(key).or_default().push((
-            row,
-            trailing_stop_positive,
-            trailing_stop_positive_offset,
-        ));
-    }
+    let (present, missing) = expected
+        .into_iter()
+        .partition(|timerange| available.contains(timerange));
+    Ok(ResolvedTimeranges { present, missing })
+}
 
-    let mut result = Vec::new();
-    let mut total_profit_by_month: HashMap<StrategyKey, Vec<f64>> = HashMap::new();
+/// Calcula el resumen por configuración a partir de cualquier `BacktestSource`.
+/// Solo consulta los periodos que existen; los demás se avisan y se omiten.
+/// Las configuraciones con huecos se tratan según `params.coverage_policy` y
+/// cada una se compara con sus vecinas de la rejilla (`apply_sensitivity`).
+pub async fn get_grid_summary<S: BacktestSource>(
+    source: &S,
+    params: &GridQuery,
+) -> Result<Vec<StrategyGridRow>> {
+    get_grid_summary_with_progress(source, params, &no_progress).await
+}
 
-    // Calculamos los profits mensuales por separado
-    for (key, items) in &grouped {
-        let mut monthly_profits = Vec::new();
-        for (row, _, _) in items {
-            monthly_profits.push(row.get("profit_total"));
-        }
-        total_profit_by_month.insert(key.clone(), monthly_profits);
+/// `get_grid_summary` avisando a `on_progress` de las filas leídas y las
+/// configuraciones formadas mientras se agrupan.
+pub async fn get_grid_summary_with_progress<S: BacktestSource>(
+    source: &S,
+    params: &GridQuery,
+    on_progress: &(dyn Fn(Progress) + Sync),
+) -> Result<Vec<StrategyGridRow>> {
+    let timeranges = resolve_timeranges(source, params).await?;
+    if !timeranges.missing.is_empty() {
+        println!(
+            "⚠️ {} periodos sin datos omitidos: {}",
+            timeranges.missing.len(),
+            timeranges.missing.join(", ")
+        );
+    }
+    if timeranges.present.is_empty() {
+        return Ok(Vec::new());
     }
 
-    for (_key, items) in grouped.into_iter() {
-        // --- SOLUCIÓN AL ERROR DE TIPOS ---
-        // Le decimos explícitamente al compilador el tipo de la clave.
-        let key: &StrategyKey = &_key;
-        let (
-            strategy,
-            timeframe,
-            minimal_roi,
-            stoploss,
-            max_open_trades,
-            trailing_stop,
-            _,
-            _, // tsp_str y tspo_str
-            trailing_only_offset_is_reached,
-            entry_price,
-            exit_price,
-            check_depth_of_market_enable,
-        ) = key;
-
-        let mut monthly_map = HashMap::new();
-        let profits = total_profit_by_month.get(key).unwrap();
-
-        let mut total_trades = 0;
-        let mut wins = 0;
-        let mut winner_holding_avg_s = 0.0;
-        let mut max_drawdown = 0.0;
-        let mut rejected_signals = 0;
-
-        for (i, (row, _, _)) in items.iter().enumerate() {
-            let timerange: String = row.get("timerange");
-            let month = &timerange[0..8];
-            monthly_map.insert(month.to_string(), profits[i]);
-
-            total_trades += row.get::<_, i32>("total_trades");
-            wins += row.get::<_, i32>("wins");
-            winner_holding_avg_s += row
-                .get::<_, Option<f64>>("winner_holding_avg_s")
-                .unwrap_or(0.0);
-            max_drawdown += row.get::<_, Option<f64>>("max_drawdown").unwrap_or(0.0);
-            rejected_signals += row.get::<_, Option<i32>>("rejected_signals").unwrap_or(0);
+    let mut rows = match params.aggregation {
+        AggregationMode::Client => {
+            let mut grouper = RecordGrouper::new(params, &timeranges.present, on_progress);
+            // Las ventanas largas se leen por tramos en varias conexiones
+            let plan = FetchPlan::for_source(source);
+            if plan.is_parallel(timeranges.present.len()) {
+                let report = plan
+                    .execute(source, params, &timeranges.present, &mut grouper)
+                    .await?;
+                grouper.set_fetch_report(report);
+            } else {
+                source
+                    .fetch_each(params, &timeranges.present, &mut grouper)
+                    .await?;
+            }
+            grouper.finish()
         }
+        // Postgres entrega ya una fila por configuración
+        AggregationMode::Server => {
+            let rows = source.fetch_grouped(params, &timeranges.present).await?;
+            on_progress(Progress {
+                read: rows.len(),
+                total: Some(rows.len()),
+                groups: rows.len(),
+                done: true,
+                fetch: None,
+            });
+            rows
+        }
+    };
 
-        let profit_total: f64 = profits.iter().sum();
-        let losses = total_trades - wins;
-        let win_rate = if total_trades > 0 {
-            wins as f64 / total_trades as f64
-        } else {
-            0.0
-        };
-        let loss_rate = if total_trades > 0 {
-            losses as f64 / total_trades as f64
-        } else {
-            0.0
-        };
-        let avg_trade_profit = if total_trades > 0 {
-            profit_total / total_trades as f64
-        } else {
-            0.0
-        };
-        let expectancy = (win_rate * avg_trade_profit) - (loss_rate * avg_trade_profit.abs()); // Corregido
-
-        let total_gain: f64 = profits.iter().filter(|&&p| p > 0.0).sum();
-        let total_loss: f64 = profits.iter().filter(|&&p| p < 0.0).map(|p| p.abs()).sum();
-        let profit_factor = if total_loss > 0.0 {
-            total_gain / total_loss
-        } else {
-            999.0
-        }; // Valor alto para ganancias sin pérdidas
-
-        let avg_monthly_profit = mean(profits);
-        let std_monthly_profit = stddev(profits, avg_monthly_profit);
-        let max_profit_month = profits.iter().cloned().fold(f64::MIN, f64::max);
-        let min_profit_month = profits.iter().cloned().fold(f64::MAX, f64::min);
-        let neg_months = profits.iter().filter(|&&p| p < 0.0).count();
-
-        let (_, trailing_stop_positive, trailing_stop_positive_offset) = items[0];
-
-        result.push(StrategyGridRow {
-            strategy: strategy.clone(),
-            timeframe: timeframe.clone(),
-            minimal_roi: minimal_roi.clone(),
-            stoploss: stoploss.clone(),
-            max_open_trades: *max_open_trades,
-            trailing_stop: *trailing_stop,
-            trailing_stop_positive: Some(trailing_stop_positive),
-            trailing_stop_positive_offset: Some(trailing_stop_positive_offset),
-            trailing_only_offset_is_reached: *trailing_only_offset_is_reached,
-            entry_price: entry_price.clone(),
-            exit_price: exit_price.clone(),
-            check_depth_of_market_enable: *check_depth_of_market_enable,
-            total_profit: profit_total,
-            total_trades,
-            wins,
-            win_rate,
-            win_time: winner_holding_avg_s / items.len() as f64,
-            drawdown_perc: max_drawdown / items.len() as f64,
-            rejected_signals: rejected_signals as f64 / items.len() as f64,
-            neg_months,
-            avg_monthly_profit,
-            std_monthly_profit,
-            max_profit_month,
-            min_profit_month,
-            avg_trade_profit,
-            losses,
-            loss_rate,
-            expectancy,
-            profit_factor,
-            monthly: monthly_map,
-        });
+    let invalid = invalid_hyperparameters(&rows);
+    if !invalid.is_empty() {
+        println!("⚠️ {} valores de ROI o stoploss no interpretables:", invalid.len());
+        for error in &invalid {
+            println!("   {}", error);
+        }
     }
 
-    Ok(result)
-}
-
-fn generate_timeranges(start_date: &str, months: usize) -> Vec<String> {
-    let mut ranges = vec![];
-    let start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d").unwrap();
-
-    for i in 0..months {
-        let from = start
-            .with_day(1)
-            .unwrap()
-            .checked_add_months(Months::new(i as u32))
-            .unwrap();
-        let to = from.checked_add_months(Months::new(1)).unwrap();
-        ranges.push(format!("{}-{}", from.format("%Y%m%d"), to.format("%Y%m%d")));
+    if params.coverage_policy == CoveragePolicy::Exclude {
+        let before = rows.len();
+        rows.retain(|row| row.coverage.is_complete());
+        if rows.len() < before {
+            println!(
+                "⚠️ {} configuraciones excluidas por periodos sin resultado",
+                before - rows.len()
+            );
+        }
     }
-    ranges
-}
 
-fn mean(data: &[f64]) -> f64 {
-    if data.is_empty() {
-        0.0
-    } else {
-        data.iter().sum::<f64>() / data.len() as f64
-    }
+    apply_sensitivity(&mut rows);
+    Ok(rows)
 }
 
-fn stddev(data: &[f64], mean: f64) -> f64 {
-    if data.is_empty() {
-        return 0.0;
-    }
-    let variance = data
-        .iter()
-        .map(|value| {
-            let diff = mean - *value;
-            diff * diff
-        })
-        .sum::<f64>()
-        / data.len() as f64;
-    variance.sqrt()
+/// Celda CSV de un valor opcional; vacía si falta.
+pub(crate) fn optional(value: Option<f64>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
 }
 
 pub fn export_summary_to_csv(
     data: &[StrategyGridRow],
     filename: &str,
-) -> Result<(), Box<dyn Error>> {
-    let file = File::create(filename)?;
+) -> Result<()> {
+    let file = File::create(filename).map_err(|e| Error::export(filename, e))?;
     let mut wtr = Writer::from_writer(file);
 
     // --- START OF MISSING LOGIC ---
 
     // Define the base headers
     let mut headers = vec![
+        "exchange",
+        "currency",
+        "pairlist",
         "strategy",
         "tf",
         "roi",
+        "roi_0m",
         "sl",
         "max_open_trades",
         "ts",
@@ -274,6 +172,24 @@ pub fn export_summary_to_csv(
         "loss_rate",
         "expectancy",
         "profit_factor",
+        "sharpe",
+        "sortino",
+        "calmar",
+        "ulcer_index",
+        "recovery_factor",
+        "max_losing_streak",
+        "equity_return",
+        "max_drawdown",
+        "max_drawdown_months",
+        "coverage_expected",
+        "coverage_present",
+        "gaps",
+        "neighbours",
+        "neighbour_avg_profit",
+        "profit_stability",
+        "drawdown_stability",
+        "isolated_peak",
+        "score",
     ];
 
     // Collect all unique month keys from the data
@@ -289,11 +205,20 @@ pub fn export_summary_to_csv(
     headers.extend(all_months.iter().map(|s| s.as_str()));
 
     // Write the complete header record to the file
-    wtr.write_record(&headers)?;
+    wtr.write_record(&headers)
+        .map_err(|e| Error::export(filename, e))?;
 
     // Iterate through each data row to write it to the CSV
     for row in data {
         // Format all fields as strings for writing
+        // Los valores no interpretables quedan vacíos; `roi` conserva el texto
+        let roi = row
+            .roi_table
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| row.minimal_roi.clone());
+        let roi_0m = optional(row.initial_roi());
+        let sl = optional(row.stoploss_value);
         let max_open_trades = row.max_open_trades.to_string();
         let trailing_stop = row.trailing_stop.to_string();
         let tsp = row.trailing_stop_positive.unwrap_or(0.0).to_string();
@@ -317,13 +242,35 @@ pub fn export_summary_to_csv(
         let loss_rate = format!("{:.2}", row.loss_rate);
         let expectancy = format!("{:.2}", row.expectancy);
         let profit_factor = format!("{:.2}", row.profit_factor);
+        let sharpe = format!("{:.2}", row.sharpe);
+        let sortino = format!("{:.2}", row.sortino);
+        let calmar = format!("{:.2}", row.calmar);
+        let ulcer_index = format!("{:.2}", row.ulcer_index);
+        let recovery_factor = format!("{:.2}", row.recovery_factor);
+        let max_losing_streak = row.max_losing_streak.to_string();
+        let equity_return = format!("{:.4}", row.equity_return);
+        let max_drawdown = format!("{:.4}", row.max_drawdown);
+        let max_drawdown_months = row.max_drawdown_months.to_string();
+        let coverage_expected = row.coverage.expected.to_string();
+        let coverage_present = row.coverage.present.to_string();
+        let gaps = row.coverage.gaps.join(";");
+        let neighbours = row.sensitivity.neighbours.to_string();
+        let neighbour_avg_profit = format!("{:.4}", row.sensitivity.neighbour_avg_profit);
+        let profit_stability = format!("{:.2}", row.sensitivity.profit_stability);
+        let drawdown_stability = format!("{:.2}", row.sensitivity.drawdown_stability);
+        let isolated_peak = row.sensitivity.isolated_peak.to_string();
+        let score = optional(row.score);
 
         // Start building the record with the base fields
         let mut record = vec![
+            row.exchange.as_str(),
+            row.currency.as_str(),
+            row.pairlist.as_str(),
             row.strategy.as_str(),
             row.timeframe.as_str(),
-            row.minimal_roi.as_str(),
-            row.stoploss.as_str(),
+            &roi,
+            &roi_0m,
+            &sl,
             &max_open_trades,
             &trailing_stop,
             &tsp,
@@ -349,12 +296,35 @@ pub fn export_summary_to_csv(
             &loss_rate,
             &expectancy,
             &profit_factor,
+            &sharpe,
+            &sortino,
+            &calmar,
+            &ulcer_index,
+            &recovery_factor,
+            &max_losing_streak,
+            &equity_return,
+            &max_drawdown,
+            &max_drawdown_months,
+            &coverage_expected,
+            &coverage_present,
+            &gaps,
+            &neighbours,
+            &neighbour_avg_profit,
+            &profit_stability,
+            &drawdown_stability,
+            &isolated_peak,
+            &score,
         ];
 
-        // Get the monthly profit values in the correct order
+        // Get the monthly profit values in the correct order; gaps stay empty
         let formatted_months: Vec<String> = all_months
             .iter()
-            .map(|month| format!("{:.2}", row.monthly.get(month).cloned().unwrap_or(0.0)))
+            .map(|month| {
+                row.monthly
+                    .get(month)
+                    .map(|profit| format!("{:.2}", profit))
+                    .unwrap_or_default()
+            })
             .collect();
 
         // Add the monthly values to the record
@@ -362,12 +332,108 @@ pub fn export_summary_to_csv(
         record.extend(monthly_strs);
 
         // Write the full record for the current strategy row
-        wtr.write_record(&record)?;
+        wtr.write_record(&record)
+            .map_err(|e| Error::export(filename, e))?;
     }
 
     // Ensure all data is written to the file
-    wtr.flush()?;
+    wtr.flush().map_err(|e| Error::export(filename, e))?;
     println!("✅ Reporte exportado a '{}'", filename);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::PostgresSource;
+    use deadpool_postgres::{Config, Runtime};
+    use tokio_postgres::NoTls;
+
+    fn sort_key(row: &StrategyGridRow) -> String {
+        format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}|{:?}|{:?}|{}|{}|{}|{}",
+            row.exchange,
+            row.currency,
+            row.pairlist,
+            row.strategy,
+            row.timeframe,
+            row.minimal_roi,
+            row.stoploss,
+            row.max_open_trades,
+            row.trailing_stop,
+            row.trailing_stop_positive,
+            row.trailing_stop_positive_offset,
+            row.trailing_only_offset_is_reached,
+            row.entry_price,
+            row.exit_price,
+            row.check_depth_of_market_enable
+        )
+    }
+
+    fn assert_close(a: f64, b: f64, field: &str) {
+        assert!((a - b).abs() < 1e-9, "{}: {} != {}", field, a, b);
+    }
+
+    /// Ejecutar con `DATABASE_URL=... cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "requiere una base de datos con la tabla backtest"]
+    async fn client_and_server_aggregation_match() {
+        dotenvy::dotenv().ok();
+        let mut cfg = Config::new();
+        cfg.url = Some(std::env::var("DATABASE_URL").expect("DATABASE_URL"));
+        let pool = cfg.create_pool(Some(Runtime::Tokio1), NoTls).unwrap();
+        let source = PostgresSource::new(pool);
+
+        let mut query = GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 12,
+            aggregation: AggregationMode::Client,
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        };
+        let mut client_rows = get_grid_summary(&source, &query).await.unwrap();
+        query.aggregation = AggregationMode::Server;
+        let mut server_rows = get_grid_summary(&source, &query).await.unwrap();
+
+        assert!(!client_rows.is_empty());
+        assert_eq!(client_rows.len(), server_rows.len());
+        client_rows.sort_by_key(sort_key);
+        server_rows.sort_by_key(sort_key);
+
+        for (c, s) in client_rows.iter().zip(&server_rows) {
+            assert_eq!(sort_key(c), sort_key(s));
+            assert_eq!(c.total_trades, s.total_trades);
+            assert_eq!(c.wins, s.wins);
+            assert_eq!(c.losses, s.losses);
+            assert_eq!(c.neg_months, s.neg_months);
+            assert_eq!(c.monthly, s.monthly);
+            assert_close(c.total_profit, s.total_profit, "total_profit");
+            assert_close(c.win_rate, s.win_rate, "win_rate");
+            assert_close(c.win_time, s.win_time, "win_time");
+            assert_close(c.drawdown_perc, s.drawdown_perc, "drawdown_perc");
+            assert_close(c.rejected_signals, s.rejected_signals, "rejected_signals");
+            assert_close(
+                c.avg_monthly_profit,
+                s.avg_monthly_profit,
+                "avg_monthly_profit",
+            );
+            assert_close(
+                c.std_monthly_profit,
+                s.std_monthly_profit,
+                "std_monthly_profit",
+            );
+            assert_close(c.max_profit_month, s.max_profit_month, "max_profit_month");
+            assert_close(c.min_profit_month, s.min_profit_month, "min_profit_month");
+            assert_close(c.avg_trade_profit, s.avg_trade_profit, "avg_trade_profit");
+            assert_close(c.expectancy, s.expectancy, "expectancy");
+            assert_close(c.profit_factor, s.profit_factor, "profit_factor");
+        }
+    }
+}
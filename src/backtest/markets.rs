@@ -0,0 +1,297 @@
+// src/backtest/markets.rs
+//
+// Comparación de una misma configuración entre mercados (exchange, moneda y
+// pairlist): su resultado en cada uno, lado a lado, y una puntuación de
+// robustez que premia ganar en todos ellos de forma pareja.
+
+use csv::Writer;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+
+use crate::backtest::logic::optional;
+use crate::backtest::model::StrategyGridRow;
+use crate::error::{Error, Result};
+
+/// Resultado de una configuración en un mercado.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketResult {
+    pub exchange: String,
+    pub currency: String,
+    pub pairlist: String,
+    pub total_profit: f64,
+    pub avg_monthly_profit: f64,
+    pub sharpe: f64,
+    pub max_drawdown: f64,
+    pub total_trades: i32,
+}
+
+impl MarketResult {
+    pub fn label(&self) -> String {
+        format!("{}/{}/{}", self.exchange, self.currency, self.pairlist)
+    }
+}
+
+/// Una configuración con sus resultados por mercado.
+#[derive(Debug, Clone)]
+pub struct MarketComparison {
+    /// Primera fila de la configuración; sus campos de mercado no aplican.
+    pub config: StrategyGridRow,
+    /// Ordenados por etiqueta de mercado.
+    pub markets: Vec<MarketResult>,
+    /// Mercados del conjunto con profit total positivo.
+    pub positive_markets: usize,
+    /// Profit total medio entre los mercados donde hay resultado.
+    pub mean_profit: f64,
+    /// Entre 0 y 1: fracción de mercados positivos (sobre todos los del
+    /// conjunto) por `media / (media + desviación)` del profit. Vale 0 si la
+    /// media no es positiva.
+    pub robustness: f64,
+}
+
+/// Etiqueta `EXCHANGE/MONEDA/PAIRLIST` del mercado de una fila.
+pub fn market_label(row: &StrategyGridRow) -> String {
+    format!("{}/{}/{}", row.exchange, row.currency, row.pairlist)
+}
+
+/// Identifica la configuración de una fila sin tener en cuenta el mercado.
+pub fn config_key(row: &StrategyGridRow) -> String {
+    format!(
+        "{}|{}|{}|{}|{}|{}|{:?}|{:?}|{}|{}|{}|{}",
+        row.strategy,
+        row.timeframe,
+        row.minimal_roi,
+        row.stoploss,
+        row.max_open_trades,
+        row.trailing_stop,
+        row.trailing_stop_positive,
+        row.trailing_stop_positive_offset,
+        row.trailing_only_offset_is_reached,
+        row.entry_price,
+        row.exit_price,
+        row.check_depth_of_market_enable
+    )
+}
+
+/// Mercados distintos presentes en `rows`, ordenados.
+pub fn market_labels(rows: &[StrategyGridRow]) -> Vec<String> {
+    rows.iter()
+        .map(market_label)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Robustez de cada configuración de `rows`, por `config_key`.
+pub fn robustness_by_config(rows: &[StrategyGridRow]) -> HashMap<String, f64> {
+    compare_markets(rows)
+        .into_iter()
+        .map(|comparison| (config_key(&comparison.config), comparison.robustness))
+        .collect()
+}
+
+/// Agrupa las filas por configuración, ordenadas por robustez (mayor primero)
+/// y después por profit medio. Una configuración ausente en un mercado cuenta
+/// como no positiva en él.
+pub fn compare_markets(rows: &[StrategyGridRow]) -> Vec<MarketComparison> {
+    let total_markets = market_labels(rows).len();
+
+    let mut order = Vec::new();
+    let mut grouped: HashMap<String, Vec<&StrategyGridRow>> = HashMap::new();
+    for row in rows {
+        let key = config_key(row);
+        if !grouped.contains_key(&key) {
+            order.push(key.clone());
+        }
+        grouped.entry(key).or_default().push(row);
+    }
+
+    let mut comparisons: Vec<MarketComparison> = order
+        .into_iter()
+        .map(|key| {
+            let group = &grouped[&key];
+            let mut markets: Vec<MarketResult> = group
+                .iter()
+                .map(|row| MarketResult {
+                    exchange: row.exchange.clone(),
+                    currency: row.currency.clone(),
+                    pairlist: row.pairlist.clone(),
+                    total_profit: row.total_profit,
+                    avg_monthly_profit: row.avg_monthly_profit,
+                    sharpe: row.sharpe,
+                    max_drawdown: row.max_drawdown,
+                    total_trades: row.total_trades,
+                })
+                .collect();
+            markets.sort_by_key(MarketResult::label);
+
+            let profits: Vec<f64> = markets.iter().map(|m| m.total_profit).collect();
+            let positive_markets = profits.iter().filter(|&&p| p > 0.0).count();
+            let mean_profit = profits.iter().sum::<f64>() / profits.len() as f64;
+            let std = (profits
+                .iter()
+                .map(|p| (p - mean_profit).powi(2))
+                .sum::<f64>()
+                / profits.len() as f64)
+                .sqrt();
+            let consistency = if mean_profit > 0.0 {
+                mean_profit / (mean_profit + std)
+            } else {
+                0.0
+            };
+
+            MarketComparison {
+                config: group[0].clone(),
+                markets,
+                positive_markets,
+                mean_profit,
+                robustness: positive_markets as f64 / total_markets as f64 * consistency,
+            }
+        })
+        .collect();
+
+    comparisons.sort_by(|a, b| {
+        b.robustness
+            .partial_cmp(&a.robustness)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(
+                b.mean_profit
+                    .partial_cmp(&a.mean_profit)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+    });
+    comparisons
+}
+
+/// Una fila por configuración y una columna de profit total por mercado;
+/// las celdas de mercados sin resultado quedan vacías.
+pub fn export_market_comparison_to_csv(
+    comparisons: &[MarketComparison],
+    filename: &str,
+) -> Result<()> {
+    let file = File::create(filename).map_err(|e| Error::export(filename, e))?;
+    let mut wtr = Writer::from_writer(file);
+
+    let labels: Vec<String> = comparisons
+        .iter()
+        .flat_map(|c| c.markets.iter().map(MarketResult::label))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut headers: Vec<&str> = vec![
+        "strategy",
+        "tf",
+        "roi",
+        "roi_0m",
+        "sl",
+        "max_open_trades",
+        "ts",
+        "tsp",
+        "tspo",
+        "toor",
+        "entry_price",
+        "exit_price",
+        "depth_mkt",
+        "positive_markets",
+        "markets",
+        "mean_profit",
+        "robustness",
+    ];
+    headers.extend(labels.iter().map(String::as_str));
+    wtr.write_record(&headers)
+        .map_err(|e| Error::export(filename, e))?;
+
+    let total_markets = labels.len().to_string();
+    for comparison in comparisons {
+        let config = &comparison.config;
+        let mut record = vec![
+            config.strategy.clone(),
+            config.timeframe.clone(),
+            config.minimal_roi.clone(),
+            optional(config.initial_roi()),
+            optional(config.stoploss_value),
+            config.max_open_trades.to_string(),
+            config.trailing_stop.to_string(),
+            config.trailing_stop_positive.unwrap_or(0.0).to_string(),
+            config
+                .trailing_stop_positive_offset
+                .unwrap_or(0.0)
+                .to_string(),
+            config.trailing_only_offset_is_reached.to_string(),
+            config.entry_price.clone(),
+            config.exit_price.clone(),
+            config.check_depth_of_market_enable.to_string(),
+            comparison.positive_markets.to_string(),
+            total_markets.clone(),
+            format!("{:.4}", comparison.mean_profit),
+            format!("{:.3}", comparison.robustness),
+        ];
+        record.extend(labels.iter().map(|label| {
+            comparison
+                .markets
+                .iter()
+                .find(|market| market.label() == *label)
+                .map(|market| format!("{:.4}", market.total_profit))
+                .unwrap_or_default()
+        }));
+        wtr.write_record(&record)
+            .map_err(|e| Error::export(filename, e))?;
+    }
+
+    wtr.flush().map_err(|e| Error::export(filename, e))?;
+    println!("✅ Comparación por mercado exportada a '{}'", filename);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(strategy: &str, pairlist: &str, total_profit: f64) -> StrategyGridRow {
+        StrategyGridRow {
+            exchange: "BINANCE".to_string(),
+            currency: "USDT".to_string(),
+            pairlist: pairlist.to_string(),
+            strategy: strategy.to_string(),
+            total_profit,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn robust_configuration_wins_in_every_market() {
+        let rows = [
+            row("Spiky", "BTC", 0.90),
+            row("Spiky", "ETH", -0.10),
+            row("Steady", "BTC", 0.20),
+            row("Steady", "ETH", 0.20),
+            row("Steady", "SOL", 0.20),
+            row("Lonely", "SOL", 0.50),
+        ];
+        let comparisons = compare_markets(&rows);
+        assert_eq!(comparisons.len(), 3);
+
+        let steady = &comparisons[0];
+        assert_eq!(steady.config.strategy, "Steady");
+        assert_eq!(steady.positive_markets, 3);
+        assert!((steady.robustness - 1.0).abs() < 1e-9);
+        assert_eq!(
+            steady
+                .markets
+                .iter()
+                .map(MarketResult::label)
+                .collect::<Vec<_>>(),
+            vec!["BINANCE/USDT/BTC", "BINANCE/USDT/ETH", "BINANCE/USDT/SOL"]
+        );
+
+        // Lonely solo tiene un mercado de tres: consistencia 1, robustez 1/3
+        let lonely = &comparisons[1];
+        assert_eq!(lonely.config.strategy, "Lonely");
+        assert!((lonely.robustness - 1.0 / 3.0).abs() < 1e-9);
+
+        // Spiky: media 0.4, desviación 0.5, un mercado positivo de tres
+        let spiky = &comparisons[2];
+        assert_eq!(spiky.positive_markets, 1);
+        assert!((spiky.robustness - (1.0 / 3.0) * (0.4 / 0.9)).abs() < 1e-9);
+    }
+}
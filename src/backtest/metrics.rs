@@ -0,0 +1,244 @@
+// src/backtest/metrics.rs
+
+use std::collections::HashMap;
+
+use crate::backtest::model::AccumulationMode;
+
+/// Valor para ratios sin denominador (sin meses negativos o sin drawdown),
+/// igual que `profit_factor`.
+pub const UNBOUNDED_RATIO: f64 = 999.0;
+
+/// Métricas de riesgo calculadas sobre la serie de profits mensuales.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RiskMetrics {
+    /// Media / desviación típica por periodo, anualizado (× √periodos/año).
+    pub sharpe: f64,
+    /// Media / desviación a la baja por periodo, anualizado (× √periodos/año).
+    pub sortino: f64,
+    /// Rentabilidad anualizada / máximo drawdown de la curva mensual.
+    pub calmar: f64,
+    /// Raíz de la media de los drawdowns al cuadrado, en %.
+    pub ulcer_index: f64,
+    /// Rentabilidad final / máximo drawdown.
+    pub recovery_factor: f64,
+    /// Mayor número de meses negativos consecutivos.
+    pub max_losing_streak: usize,
+    /// Rentabilidad final de la curva de capital.
+    pub equity_return: f64,
+    /// Mayor caída de pico a valle, en fracción del pico.
+    pub max_drawdown: f64,
+    /// Meses desde el pico de esa caída hasta recuperarlo, o hasta el último
+    /// mes si no se recupera.
+    pub max_drawdown_months: usize,
+}
+
+/// Profits de `monthly` en orden cronológico (las claves son `YYYYMMDD`).
+pub fn ordered_profits(monthly: &HashMap<String, f64>) -> Vec<f64> {
+    let mut months: Vec<(&String, &f64)> = monthly.iter().collect();
+    months.sort_by(|a, b| a.0.cmp(b.0));
+    months.into_iter().map(|(_, profit)| *profit).collect()
+}
+
+/// Curva de capital partiendo de 1: el primer punto es el capital inicial y
+/// cada mes añade uno.
+pub fn equity_curve(profits: &[f64], mode: AccumulationMode) -> Vec<f64> {
+    let mut equity = 1.0;
+    let mut curve = Vec::with_capacity(profits.len() + 1);
+    curve.push(equity);
+    for profit in profits {
+        equity = match mode {
+            AccumulationMode::Compounded => equity * (1.0 + profit),
+            AccumulationMode::Simple => equity + profit,
+        };
+        curve.push(equity);
+    }
+    curve
+}
+
+/// Calcula las métricas de riesgo sobre la curva de capital del modo
+/// elegido; los drawdowns son fracciones del pico previo. `periods_per_year`
+/// anualiza los ratios (12 con periodos mensuales).
+pub fn risk_metrics(profits: &[f64], mode: AccumulationMode, periods_per_year: f64) -> RiskMetrics {
+    if profits.is_empty() {
+        return RiskMetrics::default();
+    }
+
+    let n = profits.len() as f64;
+    let mean = profits.iter().sum::<f64>() / n;
+    let std = (profits.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / n).sqrt();
+    let downside = (profits.iter().map(|p| p.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+
+    let curve = equity_curve(profits, mode);
+    let equity_return = curve[curve.len() - 1] - 1.0;
+    let annual_return = match mode {
+        AccumulationMode::Compounded => {
+            (1.0 + equity_return).max(0.0).powf(periods_per_year / n) - 1.0
+        }
+        AccumulationMode::Simple => mean * periods_per_year,
+    };
+
+    let drawdown = max_drawdown(&curve);
+    let ulcer_index = (drawdown
+        .per_month
+        .iter()
+        .map(|d| (d * 100.0).powi(2))
+        .sum::<f64>()
+        / n)
+        .sqrt();
+
+    RiskMetrics {
+        sharpe: ratio(mean * periods_per_year.sqrt(), std),
+        sortino: ratio(mean * periods_per_year.sqrt(), downside),
+        calmar: ratio(annual_return, drawdown.depth),
+        ulcer_index,
+        recovery_factor: ratio(equity_return, drawdown.depth),
+        max_losing_streak: max_losing_streak(profits),
+        equity_return,
+        max_drawdown: drawdown.depth,
+        max_drawdown_months: drawdown.months,
+    }
+}
+
+struct Drawdown {
+    depth: f64,
+    months: usize,
+    /// Drawdown al cierre de cada mes.
+    per_month: Vec<f64>,
+}
+
+fn max_drawdown(curve: &[f64]) -> Drawdown {
+    let mut peak = curve[0];
+    let mut peak_index = 0;
+    let mut depth = 0.0;
+    let mut deepest_peak = 0;
+    let mut per_month = Vec::with_capacity(curve.len() - 1);
+
+    for (i, &equity) in curve.iter().enumerate().skip(1) {
+        if equity >= peak {
+            peak = equity;
+            peak_index = i;
+        }
+        let drawdown = if peak > 0.0 { (peak - equity) / peak } else { 0.0 };
+        if drawdown > depth {
+            depth = drawdown;
+            deepest_peak = peak_index;
+        }
+        per_month.push(drawdown);
+    }
+
+    let months = if depth > 0.0 {
+        let recovered = curve[deepest_peak + 1..]
+            .iter()
+            .position(|&equity| equity >= curve[deepest_peak]);
+        match recovered {
+            Some(offset) => offset + 1,
+            None => curve.len() - 1 - deepest_peak,
+        }
+    } else {
+        0
+    };
+
+    Drawdown {
+        depth,
+        months,
+        per_month,
+    }
+}
+
+fn max_losing_streak(profits: &[f64]) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for profit in profits {
+        if *profit < 0.0 {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else if numerator > 0.0 {
+        UNBOUNDED_RATIO
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn metrics_of_a_known_series() {
+        let metrics = risk_metrics(&[0.10, -0.05, -0.05, 0.20], AccumulationMode::Simple, 12.0);
+
+        // media 0.05, desviación √(0.045/4), desviación a la baja √(0.005/4)
+        assert_close(metrics.sharpe, 0.05 / 0.01125f64.sqrt() * 12f64.sqrt());
+        assert_close(metrics.sortino, 0.05 / 0.00125f64.sqrt() * 12f64.sqrt());
+        // curva 1.10, 1.05, 1.00, 1.20 → drawdown máximo 0.1 / 1.1
+        let max_drawdown = 0.1 / 1.1;
+        assert_close(metrics.calmar, 0.6 / max_drawdown);
+        assert_close(metrics.recovery_factor, 0.2 / max_drawdown);
+        assert_close(metrics.equity_return, 0.2);
+        assert_close(metrics.max_drawdown, max_drawdown);
+        // pico en el mes 1, recuperado en el mes 4
+        assert_eq!(metrics.max_drawdown_months, 3);
+        let dd1 = 0.05 / 1.1 * 100.0;
+        let dd2 = max_drawdown * 100.0;
+        assert_close(metrics.ulcer_index, ((dd1 * dd1 + dd2 * dd2) / 4.0).sqrt());
+        assert_eq!(metrics.max_losing_streak, 2);
+    }
+
+    #[test]
+    fn ratios_without_losses_are_unbounded() {
+        let metrics = risk_metrics(&[0.02, 0.02], AccumulationMode::Compounded, 12.0);
+        assert_eq!(metrics.sharpe, UNBOUNDED_RATIO);
+        assert_eq!(metrics.sortino, UNBOUNDED_RATIO);
+        assert_eq!(metrics.calmar, UNBOUNDED_RATIO);
+        assert_eq!(metrics.ulcer_index, 0.0);
+        assert_eq!(metrics.max_losing_streak, 0);
+        assert_eq!(metrics.max_drawdown_months, 0);
+        assert_eq!(
+            risk_metrics(&[], AccumulationMode::Compounded, 12.0),
+            RiskMetrics::default()
+        );
+    }
+
+    #[test]
+    fn compounding_and_unrecovered_drawdown() {
+        let profits = [0.10, 0.10, -0.50, 0.20];
+
+        let simple = equity_curve(&profits, AccumulationMode::Simple);
+        assert_close(simple[4], 1.0 + 0.10 + 0.10 - 0.50 + 0.20);
+
+        let compounded = equity_curve(&profits, AccumulationMode::Compounded);
+        assert_close(compounded[4], 1.1 * 1.1 * 0.5 * 1.2);
+
+        let metrics = risk_metrics(&profits, AccumulationMode::Compounded, 12.0);
+        assert_close(metrics.equity_return, 1.1 * 1.1 * 0.5 * 1.2 - 1.0);
+        assert_close(metrics.max_drawdown, 0.5);
+        // pico en el mes 2 sin recuperar: meses 3 y 4 bajo el agua
+        assert_eq!(metrics.max_drawdown_months, 2);
+        let annual = (1.1f64 * 1.1 * 0.5 * 1.2).powf(3.0) - 1.0;
+        assert_close(metrics.calmar, annual / 0.5);
+    }
+
+    #[test]
+    fn months_are_ordered_by_date() {
+        let monthly = HashMap::from([
+            ("20240301".to_string(), 3.0),
+            ("20240101".to_string(), 1.0),
+            ("20240201".to_string(), 2.0),
+        ]);
+        assert_eq!(ordered_profits(&monthly), vec![1.0, 2.0, 3.0]);
+    }
+}
@@ -1,12 +1,25 @@
 use std::collections::HashMap;
-use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Clone)]
+use crate::backtest::hyperparams::RoiTable;
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct StrategyGridRow {
+    /// Mercado del resultado; una configuración tiene una fila por mercado.
+    pub exchange: String,
+    pub currency: String,
+    pub pairlist: String,
     pub strategy: String,
     pub timeframe: String,
+    /// Texto original de la fuente; identifica la configuración.
     pub minimal_roi: String,
     pub stoploss: String,
+    /// `minimal_roi` interpretado; `None` si no se pudo.
+    pub roi_table: Option<RoiTable>,
+    /// `stoploss` como ratio; `None` si no se pudo interpretar.
+    pub stoploss_value: Option<f64>,
     pub max_open_trades: i32,
     pub trailing_stop: bool,
     pub trailing_stop_positive: Option<f64>,
@@ -32,14 +45,272 @@ pub struct StrategyGridRow {
     pub loss_rate: f64,
     pub expectancy: f64,
     pub profit_factor: f64,
+    pub sharpe: f64,
+    pub sortino: f64,
+    pub calmar: f64,
+    pub ulcer_index: f64,
+    pub recovery_factor: f64,
+    pub max_losing_streak: usize,
+    /// Rentabilidad final de la curva de capital según `AccumulationMode`.
+    pub equity_return: f64,
+    /// Mayor caída de pico a valle de la curva de capital, en fracción.
+    pub max_drawdown: f64,
+    /// Meses desde ese pico hasta recuperarlo (o hasta el final si no se recupera).
+    pub max_drawdown_months: usize,
+    pub coverage: Coverage,
+    /// Estabilidad frente a las configuraciones vecinas de la rejilla.
+    pub sensitivity: Sensitivity,
+    /// Puntuación de la fórmula con la que se ordenó (`scoring::rank`);
+    /// `None` si no cumple sus requisitos.
+    pub score: Option<f64>,
     pub monthly: HashMap<String, f64>,
 }
 
-#[derive(Debug, serde::Deserialize, Clone)]
-pub struct GridQuery {
+impl StrategyGridRow {
+    /// Ratio ROI al abrir la operación, si la tabla es válida y lo define.
+    pub fn initial_roi(&self) -> Option<f64> {
+        self.roi_table.as_ref().and_then(RoiTable::initial)
+    }
+}
+
+/// Periodos con resultado de una configuración frente a los consultados.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coverage {
+    pub expected: usize,
+    pub present: usize,
+    /// Periodos sin fila, como las claves de `monthly` (`AAAAMMDD`).
+    pub gaps: Vec<String>,
+}
+
+impl Coverage {
+    /// Fracción de periodos presentes; 1 si no se esperaba ninguno.
+    pub fn ratio(&self) -> f64 {
+        if self.expected == 0 {
+            1.0
+        } else {
+            self.present as f64 / self.expected as f64
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.gaps.is_empty()
+    }
+}
+
+/// Comparación de una configuración con sus vecinas: las que solo cambian un
+/// parámetro en un paso de la rejilla, con la misma estrategia, timeframe y
+/// mercado.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sensitivity {
+    pub neighbours: usize,
+    /// Profit total medio y mínimo de las vecinas.
+    pub neighbour_avg_profit: f64,
+    pub neighbour_min_profit: f64,
+    /// Profit medio de las vecinas sobre el propio, como máximo 1. Vale 0 si
+    /// la configuración no gana o no tiene vecinas.
+    pub profit_stability: f64,
+    /// Drawdown máximo medio de las vecinas.
+    pub neighbour_avg_drawdown: f64,
+    /// Drawdown propio sobre el medio de las vecinas, como máximo 1: cerca de
+    /// 1 si las vecinas no caen más que ella.
+    pub drawdown_stability: f64,
+    /// Gana, pero ninguna vecina conserva la mitad de su profit.
+    pub isolated_peak: bool,
+}
+
+/// Fila mensual de la tabla `backtest`, independiente del almacenamiento.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestRecord {
+    pub strategy: String,
+    pub timeframe: String,
+    pub minimal_roi: String,
+    pub stoploss: String,
+    pub max_open_trades: i32,
+    pub trailing_stop: bool,
+    pub trailing_stop_positive: f64,
+    pub trailing_stop_positive_offset: f64,
+    pub trailing_only_offset_is_reached: bool,
+    pub entry_pricing: String,
+    pub exit_pricing: String,
+    pub check_depth_of_market_enable: bool,
+    pub profit_total: f64,
+    pub total_trades: i32,
+    pub wins: i32,
+    pub winner_holding_avg_s: Option<f64>,
+    pub max_drawdown: Option<f64>,
+    pub rejected_signals: Option<i32>,
+    pub timerange: String,
     pub exchange: String,
-    pub currency: String,
+    pub stake_currency: String,
     pub pairlist: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GridQuery {
+    /// Mercados a comparar: se consultan todas las combinaciones de
+    /// exchange, moneda y pairlist (cada pairlist es un valor completo de la
+    /// columna, p. ej. `BTC,ETH`).
+    pub exchanges: Vec<String>,
+    pub currencies: Vec<String>,
+    pub pairlists: Vec<String>,
     pub start_date: String,
+    /// Número de periodos cuando no hay `end_date`.
     pub months: usize,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub aggregation: AggregationMode,
+    #[serde(default)]
+    pub accumulation: AccumulationMode,
+    /// Fin del análisis (`AAAA-MM-DD`); tiene prioridad sobre `months`.
+    #[serde(default)]
+    pub end_date: Option<String>,
+    #[serde(default)]
+    pub bucket: Bucket,
+    /// Rangos `AAAAMMDD-AAAAMMDD` de `Bucket::Custom`.
+    #[serde(default)]
+    pub custom_ranges: Vec<String>,
+    #[serde(default)]
+    pub coverage_policy: CoveragePolicy,
+}
+
+/// Qué hacer con las configuraciones a las que les faltan periodos.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoveragePolicy {
+    /// Se descartan del resultado.
+    Exclude,
+    /// Profit total, profit medio por periodo y rentabilidad de la curva se
+    /// multiplican por la fracción de periodos presentes.
+    Penalize,
+    /// Los periodos ausentes cuentan como profit 0 en el profit medio, la
+    /// desviación, el mejor y el peor periodo y las métricas de la curva de
+    /// capital (Sharpe, Sortino, Calmar, Ulcer, recuperación, racha,
+    /// rentabilidad y drawdown). Los totales, trades, meses negativos y las
+    /// medias por fila (`win_time`, `drawdown_perc`, `rejected_signals`) solo
+    /// usan los periodos presentes, y `monthly` no incluye los huecos.
+    #[default]
+    FillZero,
+}
+
+impl fmt::Display for CoveragePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoveragePolicy::Exclude => write!(f, "exclude"),
+            CoveragePolicy::Penalize => write!(f, "penalize"),
+            CoveragePolicy::FillZero => write!(f, "fill_zero"),
+        }
+    }
+}
+
+impl FromStr for CoveragePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "exclude" => Ok(CoveragePolicy::Exclude),
+            "penalize" => Ok(CoveragePolicy::Penalize),
+            "fill_zero" | "zero" => Ok(CoveragePolicy::FillZero),
+            other => Err(format!("política de cobertura desconocida: {}", other)),
+        }
+    }
+}
+
+/// Tamaño de cada periodo (`timerange`) del análisis.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    Week,
+    #[default]
+    Month,
+    Quarter,
+    /// Los rangos de `GridQuery::custom_ranges`, tal cual.
+    Custom,
+}
+
+impl fmt::Display for Bucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bucket::Week => write!(f, "week"),
+            Bucket::Month => write!(f, "month"),
+            Bucket::Quarter => write!(f, "quarter"),
+            Bucket::Custom => write!(f, "custom"),
+        }
+    }
+}
+
+impl FromStr for Bucket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "week" => Ok(Bucket::Week),
+            "month" => Ok(Bucket::Month),
+            "quarter" => Ok(Bucket::Quarter),
+            "custom" => Ok(Bucket::Custom),
+            other => Err(format!("tamaño de periodo desconocido: {}", other)),
+        }
+    }
+}
+
+/// Dónde se agrupan las filas mensuales por configuración.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregationMode {
+    /// Se descargan todas las filas y se agrupan en Rust.
+    #[default]
+    Client,
+    /// Postgres hace el `GROUP BY` y devuelve una fila por configuración.
+    Server,
+}
+
+impl fmt::Display for AggregationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregationMode::Client => write!(f, "client"),
+            AggregationMode::Server => write!(f, "server"),
+        }
+    }
+}
+
+impl FromStr for AggregationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "client" => Ok(AggregationMode::Client),
+            "server" => Ok(AggregationMode::Server),
+            other => Err(format!("modo de agregación desconocido: {}", other)),
+        }
+    }
+}
+
+/// Cómo se encadenan los profits mensuales en la curva de capital.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccumulationMode {
+    /// Cada mes opera con el capital acumulado: `capital *= 1 + profit`.
+    #[default]
+    Compounded,
+    /// Cada mes opera con el capital inicial: `capital += profit`.
+    Simple,
+}
+
+impl fmt::Display for AccumulationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccumulationMode::Compounded => write!(f, "compounded"),
+            AccumulationMode::Simple => write!(f, "simple"),
+        }
+    }
+}
+
+impl FromStr for AccumulationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "compounded" => Ok(AccumulationMode::Compounded),
+            "simple" => Ok(AccumulationMode::Simple),
+            other => Err(format!("modo de acumulación desconocido: {}", other)),
+        }
+    }
+}
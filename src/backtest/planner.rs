@@ -0,0 +1,304 @@
+// src/backtest/planner.rs
+//
+// Plan de lectura de las ventanas largas: la lista de periodos se parte en
+// tramos que se consultan a la vez, cada uno con su conexión del pool, en
+// lugar de una única sentencia larga. Las filas se entregan al agrupador en
+// el orden de los tramos y se mide cuánto tarda cada uno para poder ajustar
+// `[fetch] chunk_periods`.
+
+use futures_util::stream::{self, StreamExt};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::backtest::model::{BacktestRecord, GridQuery};
+use crate::config::{self, FetchSettings};
+use crate::error::Result;
+use crate::source::{BacktestSource, RecordSink};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchPlan {
+    pub chunk_periods: usize,
+    pub concurrency: usize,
+}
+
+impl FetchPlan {
+    pub fn new(settings: &FetchSettings) -> Self {
+        Self {
+            chunk_periods: settings.chunk_periods.max(1),
+            concurrency: settings.concurrency.max(1),
+        }
+    }
+
+    /// Plan de `[fetch]`, limitado a las consultas que admite `source`.
+    pub fn for_source<S: BacktestSource>(source: &S) -> Self {
+        let settings = config::settings()
+            .map(|s| s.fetch.clone())
+            .unwrap_or_default();
+        let mut plan = Self::new(&settings);
+        plan.concurrency = plan.concurrency.min(source.concurrency().max(1));
+        plan
+    }
+
+    /// Solo compensa partir la lectura si hay más de un tramo y más de una
+    /// conexión para leerlos.
+    pub fn is_parallel(&self, periods: usize) -> bool {
+        self.concurrency > 1 && periods > self.chunk_periods
+    }
+
+    /// Lee `timeranges` por tramos, hasta `concurrency` a la vez, y entrega
+    /// las filas a `sink` en el orden de los tramos: el resultado no depende
+    /// de cuál termine antes. Si la fuente sabe contarlas, avisa antes a
+    /// `sink` del total de la ventana completa.
+    pub async fn execute<S: BacktestSource>(
+        &self,
+        source: &S,
+        params: &GridQuery,
+        timeranges: &[String],
+        sink: &mut dyn RecordSink,
+    ) -> Result<FetchReport> {
+        let started = Instant::now();
+        if let Some(total) = source.count(params, timeranges).await? {
+            sink.expect(total);
+        }
+        // Tramos propios: con préstamos de `timeranges` el futuro deja de
+        // ser `Send` y no se puede lanzar desde la GUI
+        let chunks: Vec<Vec<String>> = timeranges
+            .chunks(self.chunk_periods)
+            .map(<[String]>::to_vec)
+            .collect();
+        let mut chunks = stream::iter(chunks)
+            .map(|chunk| timed_fetch(source, params, chunk))
+            .buffered(self.concurrency);
+
+        let mut timings = Vec::new();
+        while let Some((chunk, records, elapsed)) = chunks.next().await {
+            let records = records?;
+            timings.push(ChunkTiming {
+                first: chunk[0].clone(),
+                last: chunk[chunk.len() - 1].clone(),
+                rows: records.len(),
+                elapsed,
+            });
+            for record in records {
+                sink.push(record);
+            }
+        }
+
+        Ok(FetchReport {
+            plan: *self,
+            chunks: timings,
+            elapsed: started.elapsed(),
+        })
+    }
+}
+
+async fn timed_fetch<S: BacktestSource>(
+    source: &S,
+    params: &GridQuery,
+    chunk: Vec<String>,
+) -> (Vec<String>, Result<Vec<BacktestRecord>>, Duration) {
+    let started = Instant::now();
+    let records = source.fetch(params, &chunk).await;
+    (chunk, records, started.elapsed())
+}
+
+/// Periodos, filas y duración de la consulta de un tramo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkTiming {
+    pub first: String,
+    pub last: String,
+    pub rows: usize,
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for ChunkTiming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}..{}: {} filas en {:.2} s",
+            &self.first[0..8],
+            &self.last[9..],
+            self.rows,
+            self.elapsed.as_secs_f64()
+        )
+    }
+}
+
+/// Tiempos de una lectura por tramos.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchReport {
+    pub plan: FetchPlan,
+    /// En el orden de los periodos.
+    pub chunks: Vec<ChunkTiming>,
+    pub elapsed: Duration,
+}
+
+impl FetchReport {
+    pub fn slowest(&self) -> Option<&ChunkTiming> {
+        self.chunks.iter().max_by_key(|chunk| chunk.elapsed)
+    }
+}
+
+impl fmt::Display for FetchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} tramos de {} periodos, {} a la vez: {:.2} s",
+            self.chunks.len(),
+            self.plan.chunk_periods,
+            self.plan.concurrency,
+            self.elapsed.as_secs_f64()
+        )?;
+        if let Some(slowest) = self.slowest() {
+            write!(f, " (el más lento, {})", slowest)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use crate::backtest::aggregate::{summarize_records, Progress, RecordGrouper};
+    use crate::backtest::model::AggregationMode;
+    use crate::source::matches_query;
+
+    /// Fuente en memoria; cada tramo tarda más cuanto antes empieza, para
+    /// que terminen en orden inverso.
+    struct MemorySource(Vec<BacktestRecord>);
+
+    impl BacktestSource for MemorySource {
+        async fn fetch(
+            &self,
+            params: &GridQuery,
+            timeranges: &[String],
+        ) -> Result<Vec<BacktestRecord>> {
+            let month: u64 = timeranges[0][4..6].parse().unwrap();
+            tokio::time::sleep(Duration::from_millis(40 - 5 * month)).await;
+            Ok(self
+                .0
+                .iter()
+                .filter(|r| {
+                    matches_query(
+                        params,
+                        Some(timeranges),
+                        &r.exchange,
+                        &r.stake_currency,
+                        &r.pairlist,
+                        &r.timerange,
+                    )
+                })
+                .cloned()
+                .collect())
+        }
+
+        async fn count(
+            &self,
+            _params: &GridQuery,
+            timeranges: &[String],
+        ) -> Result<Option<usize>> {
+            Ok(Some(
+                self.0
+                    .iter()
+                    .filter(|r| timeranges.contains(&r.timerange))
+                    .count(),
+            ))
+        }
+
+        async fn available_timeranges(&self, _params: &GridQuery) -> Result<Vec<String>> {
+            Ok(self.0.iter().map(|r| r.timerange.clone()).collect())
+        }
+    }
+
+    fn record(strategy: &str, timerange: &str, profit_total: f64) -> BacktestRecord {
+        BacktestRecord {
+            strategy: strategy.to_string(),
+            timeframe: "5m".to_string(),
+            minimal_roi: "{}".to_string(),
+            stoploss: "-0.1".to_string(),
+            max_open_trades: 3,
+            trailing_stop: false,
+            trailing_stop_positive: 0.0,
+            trailing_stop_positive_offset: 0.0,
+            trailing_only_offset_is_reached: false,
+            entry_pricing: "same".to_string(),
+            exit_pricing: "same".to_string(),
+            check_depth_of_market_enable: false,
+            profit_total,
+            total_trades: 10,
+            wins: 5,
+            winner_holding_avg_s: None,
+            max_drawdown: None,
+            rejected_signals: None,
+            timerange: timerange.to_string(),
+            exchange: "BINANCE".to_string(),
+            stake_currency: "USDT".to_string(),
+            pairlist: "BTC".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn chunks_merge_in_period_order() {
+        let timeranges: Vec<String> = (1..=7)
+            .map(|month| format!("2024{:02}01-2024{:02}01", month, month + 1))
+            .collect();
+        let records: Vec<BacktestRecord> = timeranges
+            .iter()
+            .enumerate()
+            .flat_map(|(i, timerange)| {
+                let profit = 0.01 * i as f64 - 0.02;
+                [record("A", timerange, profit), record("B", timerange, -profit)]
+            })
+            .collect();
+        let query = GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 7,
+            aggregation: AggregationMode::Client,
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        };
+        let source = MemorySource(records.clone());
+        let plan = FetchPlan::new(&FetchSettings {
+            chunk_periods: 3,
+            concurrency: 3,
+        });
+        assert!(plan.is_parallel(timeranges.len()));
+
+        let last = Mutex::new(Progress::default());
+        let on_progress = |progress: Progress| *last.lock().unwrap() = progress;
+        let mut grouper = RecordGrouper::new(&query, &timeranges, &on_progress);
+        let report = plan
+            .execute(&source, &query, &timeranges, &mut grouper)
+            .await
+            .unwrap();
+        grouper.set_fetch_report(report.clone());
+        let mut chunked = grouper.finish();
+
+        // El total de la ventana se conoce antes de leer el primer tramo
+        let last = last.into_inner().unwrap();
+        assert_eq!(last.total, Some(records.len()));
+        assert_eq!(last.fraction(), Some(1.0));
+        assert_eq!(last.fetch.as_ref(), Some(&report));
+        let mut single = summarize_records(records, &query, &timeranges);
+        chunked.sort_by(|a, b| a.strategy.cmp(&b.strategy));
+        single.sort_by(|a, b| a.strategy.cmp(&b.strategy));
+
+        let rows: Vec<usize> = report.chunks.iter().map(|chunk| chunk.rows).collect();
+        assert_eq!(rows, vec![6, 6, 2]);
+        assert!(report.chunks[2].to_string().starts_with("20240701..20240801: 2 filas"));
+        assert_eq!(chunked.len(), 2);
+        for (c, s) in chunked.iter().zip(&single) {
+            assert_eq!(c.monthly, s.monthly);
+            assert_eq!(c.total_profit, s.total_profit);
+            assert_eq!(c.std_monthly_profit, s.std_monthly_profit);
+        }
+    }
+}
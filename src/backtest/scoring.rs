@@ -0,0 +1,362 @@
+// src/backtest/scoring.rs
+//
+// Puntuación compuesta para ordenar configuraciones: una suma ponderada de
+// métricas más unos requisitos mínimos, escrita como
+//
+//     total_profit - 2 * max_drawdown; total_trades >= 100; profit_factor >= 1.3
+//
+// Las configuraciones que no cumplen algún requisito no puntúan y quedan al
+// final del ranking.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::backtest::fields::Metric;
+use crate::backtest::lexer::{check_ratio, expression_error, tokenize, Token};
+use crate::backtest::model::StrategyGridRow;
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    pub fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// `métrica <comparación> número`.
+#[derive(Debug, Clone)]
+pub struct Requirement {
+    pub metric: &'static Metric,
+    pub comparison: Comparison,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoreFormula {
+    /// Peso y métrica de cada sumando.
+    pub terms: Vec<(f64, &'static Metric)>,
+    pub requirements: Vec<Requirement>,
+}
+
+impl Default for ScoreFormula {
+    /// Solo el profit total: el orden de siempre.
+    fn default() -> Self {
+        Self {
+            terms: vec![(1.0, Metric::get("total_profit").expect("métrica conocida"))],
+            requirements: Vec::new(),
+        }
+    }
+}
+
+impl ScoreFormula {
+    /// Fórmula definida como tabla en la configuración: pesos por métrica y
+    /// requisitos como `"total_trades >= 100"`.
+    pub fn from_weights(weights: &BTreeMap<String, f64>, requirements: &[String]) -> Result<Self> {
+        let terms = weights
+            .iter()
+            .map(|(name, weight)| Ok((*weight, metric(name, name, 0)?)))
+            .collect::<Result<Vec<_>>>()?;
+        if terms.is_empty() {
+            return Err(expression_error("", 0, "la fórmula no tiene pesos"));
+        }
+        let requirements = requirements
+            .iter()
+            .map(|requirement| {
+                let tokens = tokenize(requirement, 0..requirement.len())?;
+                parse_requirement(requirement, &tokens, requirement.len())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            terms,
+            requirements,
+        })
+    }
+
+    /// Puntuación de `row`; `None` si no cumple algún requisito o le falta
+    /// alguna métrica.
+    pub fn score(&self, row: &StrategyGridRow) -> Option<f64> {
+        for requirement in &self.requirements {
+            let value = requirement.metric.value(row)?;
+            if !requirement.comparison.holds(value, requirement.value) {
+                return None;
+            }
+        }
+        self.terms
+            .iter()
+            .map(|(weight, metric)| metric.value(row).map(|value| weight * value))
+            .sum()
+    }
+}
+
+/// Puntúa cada fila en `score` y las ordena de mayor a menor; las que no
+/// puntúan van al final en su orden original.
+pub fn rank(rows: &mut [StrategyGridRow], formula: &ScoreFormula) {
+    for row in rows.iter_mut() {
+        row.score = formula.score(row);
+    }
+    rows.sort_by(|a, b| match (a.score, b.score) {
+        (Some(a), Some(b)) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+}
+
+impl FromStr for ScoreFormula {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut start = 0;
+        for (i, c) in input.char_indices() {
+            if c == ';' {
+                segments.push((start, &input[start..i]));
+                start = i + 1;
+            }
+        }
+        segments.push((start, &input[start..]));
+
+        let (offset, linear) = segments[0];
+        let terms = parse_terms(input, &tokenize(input, offset..offset + linear.len())?, offset + linear.len())?;
+        let requirements = segments[1..]
+            .iter()
+            .filter(|(_, segment)| !segment.trim().is_empty())
+            .map(|(offset, segment)| {
+                parse_requirement(
+                    input,
+                    &tokenize(input, *offset..offset + segment.len())?,
+                    offset + segment.len(),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            terms,
+            requirements,
+        })
+    }
+}
+
+impl fmt::Display for ScoreFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (weight, metric)) in self.terms.iter().enumerate() {
+            let sign = if *weight < 0.0 { "-" } else { "+" };
+            match i {
+                0 if sign == "-" => write!(f, "-")?,
+                0 => {}
+                _ => write!(f, " {} ", sign)?,
+            }
+            if weight.abs() == 1.0 {
+                write!(f, "{}", metric.name)?;
+            } else {
+                write!(f, "{} * {}", weight.abs(), metric.name)?;
+            }
+        }
+        for requirement in &self.requirements {
+            write!(
+                f,
+                "; {} {} {}",
+                requirement.metric.name, requirement.comparison, requirement.value
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// `[+|-] término {(+|-) término}`, con término `número [*] métrica`,
+/// `métrica [* número]` o `métrica`.
+fn parse_terms(
+    input: &str,
+    tokens: &[(usize, Token)],
+    end: usize,
+) -> Result<Vec<(f64, &'static Metric)>> {
+    let mut terms = Vec::new();
+    let mut i = 0;
+    loop {
+        let mut sign = 1.0;
+        match tokens.get(i) {
+            Some((_, Token::Minus)) => {
+                sign = -1.0;
+                i += 1;
+            }
+            Some((_, Token::Plus)) => i += 1,
+            Some((position, token)) if !terms.is_empty() => {
+                return Err(expression_error(
+                    input,
+                    *position,
+                    &format!("se esperaba '+' o '-' y llegó {:?}", token),
+                ))
+            }
+            _ => {}
+        }
+
+        let (weight, metric) = match (tokens.get(i), tokens.get(i + 1), tokens.get(i + 2)) {
+            (
+                Some((_, Token::Number(n) | Token::Percent(n))),
+                Some((_, Token::Star)),
+                Some((at, Token::Ident(name))),
+            ) => {
+                i += 3;
+                (*n, metric(input, name, *at)?)
+            }
+            (
+                Some((_, Token::Number(n) | Token::Percent(n))),
+                Some((at, Token::Ident(name))),
+                _,
+            ) => {
+                i += 2;
+                (*n, metric(input, name, *at)?)
+            }
+            (
+                Some((at, Token::Ident(name))),
+                Some((_, Token::Star)),
+                Some((_, Token::Number(n) | Token::Percent(n))),
+            ) => {
+                i += 3;
+                (*n, metric(input, name, *at)?)
+            }
+            (Some((at, Token::Ident(name))), _, _) => {
+                i += 1;
+                (1.0, metric(input, name, *at)?)
+            }
+            (Some((position, _)), _, _) => {
+                return Err(expression_error(input, *position, "se esperaba una métrica"))
+            }
+            (None, _, _) => return Err(expression_error(input, end, "se esperaba una métrica")),
+        };
+        terms.push((sign * weight, metric));
+
+        if i >= tokens.len() {
+            return Ok(terms);
+        }
+    }
+}
+
+/// `métrica <comparación> [-]número`.
+fn parse_requirement(input: &str, tokens: &[(usize, Token)], end: usize) -> Result<Requirement> {
+    let (at, name) = match tokens.first() {
+        Some((at, Token::Ident(name))) => (*at, name),
+        Some((at, _)) => return Err(expression_error(input, *at, "se esperaba una métrica")),
+        None => return Err(expression_error(input, end, "requisito vacío")),
+    };
+    let comparison = match tokens.get(1) {
+        Some((_, Token::Compare(comparison))) => *comparison,
+        Some((at, _)) => return Err(expression_error(input, *at, "se esperaba una comparación")),
+        None => return Err(expression_error(input, end, "se esperaba una comparación")),
+    };
+    let (sign, number_at) = match tokens.get(2) {
+        Some((_, Token::Minus)) => (-1.0, 3),
+        _ => (1.0, 2),
+    };
+    let metric = metric(input, name, at)?;
+    let value = match tokens.get(number_at) {
+        Some((_, Token::Percent(value))) => sign * value,
+        Some((at, Token::Number(value))) => {
+            check_ratio(input, metric, sign * value, *at)?;
+            sign * value
+        }
+        Some((at, _)) => return Err(expression_error(input, *at, "se esperaba un número")),
+        None => return Err(expression_error(input, end, "se esperaba un número")),
+    };
+    if let Some((at, _)) = tokens.get(number_at + 1) {
+        return Err(expression_error(input, *at, "sobra texto tras el requisito"));
+    }
+    Ok(Requirement {
+        metric,
+        comparison,
+        value,
+    })
+}
+
+fn metric(input: &str, name: &str, position: usize) -> Result<&'static Metric> {
+    Metric::get(name).ok_or_else(|| {
+        expression_error(input, position, &format!("métrica desconocida '{}'", name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(strategy: &str, profit: f64, drawdown: f64, trades: i32, pf: f64) -> StrategyGridRow {
+        StrategyGridRow {
+            strategy: strategy.to_string(),
+            total_profit: profit,
+            max_drawdown: drawdown,
+            total_trades: trades,
+            profit_factor: pf,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn formula_ranks_and_excludes_by_requirements() {
+        let formula: ScoreFormula =
+            "profit - 2*drawdown; trades >= 100; profit_factor >= 1.3".parse().unwrap();
+        assert_eq!(
+            formula.to_string(),
+            "total_profit - 2 * max_drawdown; total_trades >= 100; profit_factor >= 1.3"
+        );
+
+        let mut rows = vec![
+            row("Risky", 0.50, 0.30, 300, 1.5),
+            row("Calm", 0.30, 0.05, 200, 1.4),
+            row("Few", 0.90, 0.01, 50, 3.0),
+        ];
+        rank(&mut rows, &formula);
+        let order: Vec<&str> = rows.iter().map(|r| r.strategy.as_str()).collect();
+        assert_eq!(order, vec!["Calm", "Risky", "Few"]);
+        assert!((rows[0].score.unwrap() - 0.20).abs() < 1e-9);
+        assert_eq!(rows[2].score, None);
+    }
+
+    #[test]
+    fn config_weights_and_errors() {
+        let weights = BTreeMap::from([("sharpe".to_string(), 1.0), ("ulcer_index".to_string(), -0.5)]);
+        let formula =
+            ScoreFormula::from_weights(&weights, &["win_rate > 0.5".to_string()]).unwrap();
+        assert_eq!(formula.to_string(), "sharpe - 0.5 * ulcer_index; win_rate > 0.5");
+
+        match "profit - 2*beneficio".parse::<ScoreFormula>() {
+            Err(Error::Expression { position, .. }) => assert_eq!(position, 11),
+            other => panic!("se esperaba Expression, llegó {:?}", other),
+        }
+        assert!("profit; trades >=".parse::<ScoreFormula>().is_err());
+        assert!("profit drawdown".parse::<ScoreFormula>().is_err());
+
+        // Los requisitos sobre ratios necesitan `%` para pasar de 1
+        assert!("profit; drawdown < 15".parse::<ScoreFormula>().is_err());
+        assert!("profit; drawdown < 15%".parse::<ScoreFormula>().is_ok());
+    }
+}
@@ -0,0 +1,227 @@
+// src/backtest/sensitivity.rs
+//
+// Sensibilidad a los parámetros: cada configuración es un punto de la rejilla
+// de hyperopt y sus vecinas son las que cambian un solo parámetro en un paso.
+// Una configuración que solo gana en un punto aislado está sobreajustada a
+// ese valor concreto.
+
+use std::collections::HashMap;
+
+use crate::backtest::model::{Sensitivity, StrategyGridRow};
+
+/// Parámetros que forman la rejilla, en el orden de `levels`.
+const DIMENSIONS: usize = 10;
+
+/// Fracción del profit propio que debe conservar la mejor vecina para que la
+/// configuración no cuente como pico aislado.
+const ISOLATION_RETENTION: f64 = 0.5;
+
+/// Valor de un parámetro. Los numéricos se ordenan por valor; los de texto
+/// (y los numéricos que no se pudieron interpretar) alfabéticamente, después.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+enum Level {
+    Number(f64),
+    Text(String),
+}
+
+fn levels(row: &StrategyGridRow) -> [Level; DIMENSIONS] {
+    let flag = |value: bool| Level::Number(if value { 1.0 } else { 0.0 });
+    [
+        Level::Text(
+            row.roi_table
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| row.minimal_roi.clone()),
+        ),
+        match row.stoploss_value {
+            Some(stoploss) => Level::Number(stoploss),
+            None => Level::Text(row.stoploss.clone()),
+        },
+        Level::Number(row.max_open_trades as f64),
+        flag(row.trailing_stop),
+        Level::Number(row.trailing_stop_positive.unwrap_or(0.0)),
+        Level::Number(row.trailing_stop_positive_offset.unwrap_or(0.0)),
+        flag(row.trailing_only_offset_is_reached),
+        Level::Text(row.entry_price.clone()),
+        Level::Text(row.exit_price.clone()),
+        flag(row.check_depth_of_market_enable),
+    ]
+}
+
+/// Rellena `sensitivity` en cada fila. Las vecinas se buscan entre las filas
+/// de la misma estrategia, timeframe y mercado; los pasos de cada parámetro
+/// son sus valores distintos dentro de ese grupo, ordenados.
+pub fn apply_sensitivity(rows: &mut [StrategyGridRow]) {
+    let mut families: HashMap<(&str, &str, &str, &str, &str), Vec<usize>> = HashMap::new();
+    for (i, row) in rows.iter().enumerate() {
+        families
+            .entry((
+                row.exchange.as_str(),
+                row.currency.as_str(),
+                row.pairlist.as_str(),
+                row.strategy.as_str(),
+                row.timeframe.as_str(),
+            ))
+            .or_default()
+            .push(i);
+    }
+
+    let mut results = vec![Sensitivity::default(); rows.len()];
+    for members in families.values() {
+        let member_levels: Vec<[Level; DIMENSIONS]> =
+            members.iter().map(|&i| levels(&rows[i])).collect();
+
+        // Posición de cada fila en la rejilla: el índice de su valor entre
+        // los valores distintos de cada parámetro.
+        let coordinates: Vec<Vec<usize>> = {
+            let steps: Vec<Vec<&Level>> = (0..DIMENSIONS)
+                .map(|d| {
+                    let mut values: Vec<&Level> =
+                        member_levels.iter().map(|levels| &levels[d]).collect();
+                    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                    values.dedup();
+                    values
+                })
+                .collect();
+            member_levels
+                .iter()
+                .map(|levels| {
+                    (0..DIMENSIONS)
+                        .map(|d| {
+                            steps[d]
+                                .iter()
+                                .position(|value| **value == levels[d])
+                                .unwrap_or(0)
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        let by_coordinate: HashMap<&[usize], usize> = coordinates
+            .iter()
+            .zip(members)
+            .map(|(coordinate, &i)| (coordinate.as_slice(), i))
+            .collect();
+
+        for (coordinate, &i) in coordinates.iter().zip(members) {
+            let mut neighbours = Vec::new();
+            for d in 0..DIMENSIONS {
+                for step in [-1isize, 1] {
+                    let Some(value) = coordinate[d].checked_add_signed(step) else {
+                        continue;
+                    };
+                    let mut neighbour = coordinate.clone();
+                    neighbour[d] = value;
+                    if let Some(&j) = by_coordinate.get(neighbour.as_slice()) {
+                        neighbours.push(j);
+                    }
+                }
+            }
+            results[i] = sensitivity(&rows[i], neighbours.iter().map(|&j| &rows[j]));
+        }
+    }
+
+    for (row, sensitivity) in rows.iter_mut().zip(results) {
+        row.sensitivity = sensitivity;
+    }
+}
+
+fn sensitivity<'a>(
+    row: &StrategyGridRow,
+    neighbours: impl Iterator<Item = &'a StrategyGridRow>,
+) -> Sensitivity {
+    let (profits, drawdowns): (Vec<f64>, Vec<f64>) = neighbours
+        .map(|neighbour| (neighbour.total_profit, neighbour.max_drawdown))
+        .unzip();
+    if profits.is_empty() {
+        return Sensitivity::default();
+    }
+
+    let n = profits.len() as f64;
+    let avg_profit = profits.iter().sum::<f64>() / n;
+    let min_profit = profits.iter().cloned().fold(f64::MAX, f64::min);
+    let max_profit = profits.iter().cloned().fold(f64::MIN, f64::max);
+    let avg_drawdown = drawdowns.iter().sum::<f64>() / n;
+
+    let profit_stability = if row.total_profit > 0.0 {
+        (avg_profit / row.total_profit).min(1.0)
+    } else {
+        0.0
+    };
+    let drawdown_stability = if avg_drawdown <= row.max_drawdown {
+        1.0
+    } else {
+        row.max_drawdown / avg_drawdown
+    };
+
+    Sensitivity {
+        neighbours: profits.len(),
+        neighbour_avg_profit: avg_profit,
+        neighbour_min_profit: min_profit,
+        profit_stability,
+        neighbour_avg_drawdown: avg_drawdown,
+        drawdown_stability,
+        isolated_peak: row.total_profit > 0.0
+            && max_profit < row.total_profit * ISOLATION_RETENTION,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(stoploss: f64, max_open_trades: i32, total_profit: f64) -> StrategyGridRow {
+        StrategyGridRow {
+            strategy: "A".to_string(),
+            timeframe: "5m".to_string(),
+            stoploss: stoploss.to_string(),
+            stoploss_value: Some(stoploss),
+            max_open_trades,
+            total_profit,
+            max_drawdown: 0.1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn neighbours_differ_in_one_step_of_one_parameter() {
+        let mut rows = vec![
+            row(-0.3, 3, 0.20),
+            row(-0.2, 3, 0.22),
+            row(-0.1, 3, 0.18),
+            row(-0.2, 5, 0.21),
+            // Otra estrategia: no es vecina aunque coincidan los parámetros
+            StrategyGridRow {
+                strategy: "B".to_string(),
+                ..row(-0.2, 3, 0.9)
+            },
+        ];
+        apply_sensitivity(&mut rows);
+
+        // -0.2/3 tiene vecinas -0.3/3, -0.1/3 y -0.2/5; -0.3/5 no existe
+        let center = &rows[1].sensitivity;
+        assert_eq!(center.neighbours, 3);
+        assert!((center.neighbour_avg_profit - 0.59 / 3.0).abs() < 1e-9);
+        assert!((center.neighbour_min_profit - 0.18).abs() < 1e-9);
+        assert!(!center.isolated_peak);
+        assert!((center.drawdown_stability - 1.0).abs() < 1e-9);
+
+        // -0.3/3 solo llega a -0.2/3: -0.1/3 está a dos pasos
+        assert_eq!(rows[0].sensitivity.neighbours, 1);
+        assert_eq!(rows[0].sensitivity.profit_stability, 1.0);
+        assert_eq!(rows[4].sensitivity.neighbours, 0);
+    }
+
+    #[test]
+    fn isolated_peak_is_flagged() {
+        let mut rows = vec![row(-0.3, 3, -0.05), row(-0.2, 3, 0.40), row(-0.1, 3, 0.10)];
+        rows[2].max_drawdown = 0.3;
+        apply_sensitivity(&mut rows);
+
+        let peak = &rows[1].sensitivity;
+        assert!(peak.isolated_peak);
+        assert!((peak.profit_stability - 0.025 / 0.40).abs() < 1e-9);
+        assert!((peak.drawdown_stability - 0.1 / 0.2).abs() < 1e-9);
+        assert!(!rows[2].sensitivity.isolated_peak);
+    }
+}
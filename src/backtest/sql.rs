@@ -1,31 +1,341 @@
+// src/backtest/sql.rs
+
+use bytes::BytesMut;
+use std::error::Error;
+use tokio_postgres::types::{to_sql_checked, IsNull, ToSql, Type};
+
 use crate::backtest::model::GridQuery;
 
-pub fn build_flat_sql(params: &GridQuery, timeranges: &[String]) -> String {
-    let timerange_list = timeranges
+/// Columnas que el agrupador necesita de la tabla `backtest`.
+pub(crate) const FLAT_COLUMNS: &[&str] = &[
+    "strategy",
+    "timeframe",
+    "minimal_roi",
+    "stoploss",
+    "max_open_trades",
+    "trailing_stop",
+    "trailing_stop_positive",
+    "trailing_stop_positive_offset",
+    "trailing_only_offset_is_reached",
+    "entry_pricing",
+    "exit_pricing",
+    "check_depth_of_market_enable",
+    "profit_total",
+    "total_trades",
+    "wins",
+    "winner_holding_avg_s",
+    "max_drawdown",
+    "rejected_signals",
+    "timerange",
+    "exchange",
+    "stake_currency",
+    "pairlist",
+];
+
+/// Columnas que identifican el mercado de un resultado.
+const MARKET_COLUMNS: &[&str] = &["exchange", "stake_currency", "pairlist"];
+
+/// Columnas que identifican una configuración de estrategia.
+const KEY_COLUMNS: &[&str] = &[
+    "strategy",
+    "timeframe",
+    "minimal_roi",
+    "stoploss",
+    "max_open_trades",
+    "trailing_stop",
+    "trailing_stop_positive",
+    "trailing_stop_positive_offset",
+    "trailing_only_offset_is_reached",
+    "entry_pricing",
+    "exit_pricing",
+    "check_depth_of_market_enable",
+];
+
+/// Agregados que calcula Postgres en el modo `AggregationMode::Server`.
+/// Replican exactamente la agrupación que hace `logic.rs` en Rust.
+const AGGREGATE_COLUMNS: &[&str] = &[
+    "SUM(total_trades)::int4 AS total_trades",
+    "SUM(wins)::int4 AS wins",
+    "AVG(COALESCE(winner_holding_avg_s, 0)) AS win_time",
+    "AVG(COALESCE(max_drawdown, 0)) AS drawdown_perc",
+    "AVG(COALESCE(rejected_signals, 0))::float8 AS rejected_signals",
+    "SUM(profit_total) AS profit_total",
+    "AVG(profit_total) AS avg_monthly_profit",
+    "STDDEV_POP(profit_total) AS std_monthly_profit",
+    "MAX(profit_total) AS max_profit_month",
+    "MIN(profit_total) AS min_profit_month",
+    "COUNT(*) FILTER (WHERE profit_total < 0) AS neg_months",
+    "COALESCE(SUM(profit_total) FILTER (WHERE profit_total > 0), 0) AS total_gain",
+    "COALESCE(-SUM(profit_total) FILTER (WHERE profit_total < 0), 0) AS total_loss",
+    "ARRAY_AGG(timerange ORDER BY timerange) AS timeranges",
+    "ARRAY_AGG(profit_total ORDER BY timerange) AS profits",
+];
+
+/// Valor enlazado a un placeholder `$n`. Nunca se concatena al texto SQL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlParam {
+    Text(String),
+    TextArray(Vec<String>),
+}
+
+impl ToSql for SqlParam {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self {
+            SqlParam::Text(value) => value.to_sql(ty, out),
+            SqlParam::TextArray(values) => values.to_sql(ty, out),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <String as ToSql>::accepts(ty) || <Vec<String> as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+/// Sentencia SQL lista para `client.query(&query.sql, &query.params())`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundQuery {
+    pub sql: String,
+    pub values: Vec<SqlParam>,
+}
+
+impl BoundQuery {
+    /// Devuelve los parámetros en el formato que espera `tokio_postgres`.
+    pub fn params(&self) -> Vec<&(dyn ToSql + Sync)> {
+        self.values
+            .iter()
+            .map(|v| v as &(dyn ToSql + Sync))
+            .collect()
+    }
+}
+
+/// Constructor de `SELECT` con condiciones parametrizadas.
+///
+/// Los nombres de tabla y columna son `&'static str` definidos en el código;
+/// todo valor que provenga del usuario pasa por `SqlParam`.
+#[derive(Debug, Clone)]
+pub struct QueryBuilder {
+    table: &'static str,
+    columns: Vec<&'static str>,
+    conditions: Vec<String>,
+    group_by: Vec<&'static str>,
+    values: Vec<SqlParam>,
+}
+
+impl QueryBuilder {
+    pub fn select(table: &'static str, columns: &[&'static str]) -> Self {
+        Self {
+            table,
+            columns: columns.to_vec(),
+            conditions: Vec::new(),
+            group_by: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Añade `column = $n`.
+    pub fn eq(mut self, column: &'static str, value: impl Into<String>) -> Self {
+        let placeholder = self.bind(SqlParam::Text(value.into()));
+        self.conditions
+            .push(format!("{} = {}", column, placeholder));
+        self
+    }
+
+    /// Añade `column = ANY($n)` con un array de texto.
+    pub fn any(mut self, column: &'static str, values: &[String]) -> Self {
+        let placeholder = self.bind(SqlParam::TextArray(values.to_vec()));
+        self.conditions
+            .push(format!("{} = ANY({})", column, placeholder));
+        self
+    }
+
+    pub fn group_by(mut self, columns: &[&'static str]) -> Self {
+        self.group_by.extend_from_slice(columns);
+        self
+    }
+
+    pub fn build(self) -> BoundQuery {
+        let mut sql = format!("SELECT {} FROM {}", self.columns.join(", "), self.table);
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.conditions.join(" AND "));
+        }
+        if !self.group_by.is_empty() {
+            sql.push_str(" GROUP BY ");
+            sql.push_str(&self.group_by.join(", "));
+        }
+        BoundQuery {
+            sql,
+            values: self.values,
+        }
+    }
+
+    fn bind(&mut self, value: SqlParam) -> String {
+        self.values.push(value);
+        format!("${}", self.values.len())
+    }
+}
+
+pub fn build_flat_sql(params: &GridQuery, timeranges: &[String]) -> BoundQuery {
+    filtered(
+        QueryBuilder::select("backtest", FLAT_COLUMNS),
+        params,
+        timeranges,
+    )
+    .build()
+}
+
+/// Número de filas que devolverá `build_flat_sql`, para informar del avance.
+pub fn build_count_sql(params: &GridQuery, timeranges: &[String]) -> BoundQuery {
+    filtered(
+        QueryBuilder::select("backtest", &["COUNT(*)"]),
+        params,
+        timeranges,
+    )
+    .build()
+}
+
+/// Consulta con una fila por configuración y mercado para
+/// `AggregationMode::Server`.
+pub fn build_grouped_sql(params: &GridQuery, timeranges: &[String]) -> BoundQuery {
+    let columns: Vec<&'static str> = MARKET_COLUMNS
         .iter()
-        .map(|m| format!("'{}'", m))
-        .collect::<Vec<String>>()
-        .join(",");
-
-    format!(
-        r#"
-        SELECT
-            strategy, timeframe, minimal_roi, stoploss, max_open_trades,
-            trailing_stop, trailing_stop_positive, trailing_stop_positive_offset,
-            trailing_only_offset_is_reached, entry_pricing, exit_pricing,
-            check_depth_of_market_enable,
-            profit_total, total_trades, wins, winner_holding_avg_s,
-            max_drawdown, rejected_signals, timerange
-        FROM backtest
-        WHERE
-            exchange = '{exchange}' AND
-            stake_currency = '{currency}' AND
-            pairlist = '{pairlist}' AND
-            timerange IN ({timeranges})
-        "#,
-        exchange = params.exchange.to_uppercase(),
-        currency = params.currency.to_uppercase(),
-        pairlist = params.pairlist.to_uppercase(),
-        timeranges = timerange_list
+        .chain(KEY_COLUMNS)
+        .chain(AGGREGATE_COLUMNS)
+        .copied()
+        .collect();
+
+    filtered(
+        QueryBuilder::select("backtest", &columns),
+        params,
+        timeranges,
     )
+    .group_by(MARKET_COLUMNS)
+    .group_by(KEY_COLUMNS)
+    .build()
+}
+
+/// Filas mensuales de una estrategia, con su `id` (entero de cualquier
+/// tamaño, leído como `int8`) para buscar su detalle por par y por trade. El
+/// resto de la clave se compara en Rust.
+pub fn build_detail_sql(params: &GridQuery, timeranges: &[String], strategy: &str) -> BoundQuery {
+    let columns: Vec<&'static str> =
+        ["id::int8 AS id"].iter().chain(FLAT_COLUMNS).copied().collect();
+    filtered(QueryBuilder::select("backtest", &columns), params, timeranges)
+        .eq("strategy", strategy)
+        .build()
+}
+
+/// Periodos distintos con filas del mercado pedido.
+pub fn build_timeranges_sql(params: &GridQuery) -> BoundQuery {
+    market(QueryBuilder::select("backtest", &["DISTINCT timerange"]), params).build()
+}
+
+fn market(builder: QueryBuilder, params: &GridQuery) -> QueryBuilder {
+    builder
+        .any("exchange", &uppercase(&params.exchanges))
+        .any("stake_currency", &uppercase(&params.currencies))
+        .any("pairlist", &uppercase(&params.pairlists))
+}
+
+fn uppercase(values: &[String]) -> Vec<String> {
+    values.iter().map(|value| value.to_uppercase()).collect()
+}
+
+fn filtered(builder: QueryBuilder, params: &GridQuery, timeranges: &[String]) -> QueryBuilder {
+    market(builder, params).any("timerange", timeranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairlist: &str) -> GridQuery {
+        GridQuery {
+            exchanges: vec!["binance".to_string(), "kraken".to_string()],
+            currencies: vec!["usdt".to_string()],
+            pairlists: vec![pairlist.to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 2,
+            aggregation: Default::default(),
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        }
+    }
+
+    #[test]
+    fn flat_sql_binds_every_filter() {
+        let timeranges = vec![
+            "20240101-20240201".to_string(),
+            "20240201-20240301".to_string(),
+        ];
+        let bound = build_flat_sql(&query("btc,eth"), &timeranges);
+
+        assert_eq!(
+            bound.sql,
+            format!(
+                "SELECT {} FROM backtest WHERE exchange = ANY($1) AND stake_currency = ANY($2) \
+                 AND pairlist = ANY($3) AND timerange = ANY($4)",
+                FLAT_COLUMNS.join(", ")
+            )
+        );
+        assert_eq!(
+            bound.values,
+            vec![
+                SqlParam::TextArray(vec!["BINANCE".to_string(), "KRAKEN".to_string()]),
+                SqlParam::TextArray(vec!["USDT".to_string()]),
+                SqlParam::TextArray(vec!["BTC,ETH".to_string()]),
+                SqlParam::TextArray(timeranges),
+            ]
+        );
+        assert_eq!(bound.params().len(), 4);
+    }
+
+    #[test]
+    fn user_input_never_reaches_sql_text() {
+        let bound = build_flat_sql(
+            &query("BTC' OR '1'='1"),
+            &["x'); DROP TABLE backtest; --".to_string()],
+        );
+
+        assert!(!bound.sql.contains('\''));
+        assert!(!bound.sql.contains("DROP"));
+        assert_eq!(
+            bound.values[2],
+            SqlParam::TextArray(vec!["BTC' OR '1'='1".to_string()])
+        );
+    }
+
+    #[test]
+    fn grouped_sql_shares_filters_and_groups_by_key() {
+        let timeranges = vec!["20240101-20240201".to_string()];
+        let flat = build_flat_sql(&query("BTC"), &timeranges);
+        let grouped = build_grouped_sql(&query("BTC"), &timeranges);
+        let count = build_count_sql(&query("BTC"), &timeranges);
+
+        assert_eq!(grouped.values, flat.values);
+        assert_eq!(count.values, flat.values);
+        assert!(count.sql.starts_with("SELECT COUNT(*) FROM backtest WHERE"));
+        assert!(grouped
+            .sql
+            .ends_with(&format!(
+                "GROUP BY {}, {}",
+                MARKET_COLUMNS.join(", "),
+                KEY_COLUMNS.join(", ")
+            )));
+        assert!(grouped.sql.contains("timerange = ANY($4) GROUP BY"));
+
+        let detail = build_detail_sql(&query("BTC"), &timeranges, "Trend");
+        assert!(detail.sql.starts_with("SELECT id::int8 AS id, strategy, timeframe"));
+        assert!(detail.sql.ends_with("timerange = ANY($4) AND strategy = $5"));
+        assert_eq!(detail.values[..4], flat.values[..]);
+    }
 }
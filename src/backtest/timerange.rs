@@ -0,0 +1,256 @@
+// src/backtest/timerange.rs
+//
+// Periodos de la consulta en el formato `AAAAMMDD-AAAAMMDD` de la columna
+// `timerange`: semanas, meses o trimestres consecutivos, o rangos explícitos.
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+use crate::backtest::model::{Bucket, GridQuery};
+use crate::error::{Error, Result};
+
+/// Días medios por año, para anualizar periodos que no son meses.
+const DAYS_PER_YEAR: f64 = 365.25;
+
+/// Periodos que pide `params`: `custom_ranges` en `Bucket::Custom` o, si no,
+/// periodos consecutivos desde `start_date` que empiecen antes de `end_date`
+/// o, sin fecha de fin, `months` periodos. Los meses y trimestres se alinean al
+/// primer día; las semanas empiezan en `start_date`.
+pub fn generate_timeranges(params: &GridQuery) -> Result<Vec<String>> {
+    if params.bucket == Bucket::Custom {
+        return custom_timeranges(&params.custom_ranges);
+    }
+
+    let start = parse_date(&params.start_date)?;
+    let start = match params.bucket {
+        Bucket::Month => start.with_day(1),
+        Bucket::Quarter => start
+            .with_day(1)
+            .and_then(|d| d.with_month(d.month0() / 3 * 3 + 1)),
+        Bucket::Week | Bucket::Custom => Some(start),
+    }
+    .ok_or_else(|| out_of_range(&params.start_date))?;
+
+    let end = match &params.end_date {
+        Some(end_date) => {
+            let end = parse_date(end_date)?;
+            if end <= start {
+                return Err(Error::InvalidQuery(format!(
+                    "la fecha de fin {} no es posterior al inicio {}",
+                    end_date, params.start_date
+                )));
+            }
+            Some(end)
+        }
+        None => None,
+    };
+
+    let mut ranges = vec![];
+    let mut from = start;
+    loop {
+        match end {
+            Some(end) if from >= end => break,
+            None if ranges.len() >= params.months => break,
+            _ => {}
+        }
+        let to =
+            next_bucket(from, params.bucket).ok_or_else(|| out_of_range(&params.start_date))?;
+        ranges.push(format_range(from, to));
+        from = to;
+    }
+    Ok(ranges)
+}
+
+/// Fechas de inicio y fin de un `timerange`.
+pub fn parse_timerange(timerange: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let date_error = |reason: &str| Error::DateParse {
+        input: timerange.to_string(),
+        reason: reason.to_string(),
+    };
+    let (from, to) = timerange
+        .split_once('-')
+        .ok_or_else(|| date_error("se esperaba AAAAMMDD-AAAAMMDD"))?;
+    let from = NaiveDate::parse_from_str(from, "%Y%m%d").map_err(|e| date_error(&e.to_string()))?;
+    let to = NaiveDate::parse_from_str(to, "%Y%m%d").map_err(|e| date_error(&e.to_string()))?;
+    if to <= from {
+        return Err(date_error("el fin no es posterior al inicio"));
+    }
+    Ok((from, to))
+}
+
+/// Periodos por año según el tamaño de periodo, para anualizar métricas.
+/// Los rangos personalizados usan su duración media.
+pub fn periods_per_year(params: &GridQuery) -> f64 {
+    match params.bucket {
+        Bucket::Week => DAYS_PER_YEAR / 7.0,
+        Bucket::Month => 12.0,
+        Bucket::Quarter => 4.0,
+        Bucket::Custom => {
+            let days: Vec<i64> = params
+                .custom_ranges
+                .iter()
+                .filter_map(|range| parse_timerange(range).ok())
+                .map(|(from, to)| (to - from).num_days())
+                .collect();
+            if days.is_empty() {
+                12.0
+            } else {
+                DAYS_PER_YEAR * days.len() as f64 / days.iter().sum::<i64>() as f64
+            }
+        }
+    }
+}
+
+fn custom_timeranges(ranges: &[String]) -> Result<Vec<String>> {
+    if ranges.is_empty() {
+        return Err(Error::InvalidQuery(
+            "el periodo personalizado necesita al menos un rango AAAAMMDD-AAAAMMDD".into(),
+        ));
+    }
+    let mut parsed = ranges
+        .iter()
+        .map(|range| parse_timerange(range.trim()))
+        .collect::<Result<Vec<_>>>()?;
+    parsed.sort();
+    parsed.dedup();
+    // Los resultados de cada periodo se guardan por su fecha de inicio
+    if let Some(pair) = parsed.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(Error::InvalidQuery(format!(
+            "los rangos {} y {} empiezan el mismo día",
+            format_range(pair[0].0, pair[0].1),
+            format_range(pair[1].0, pair[1].1)
+        )));
+    }
+    Ok(parsed
+        .into_iter()
+        .map(|(from, to)| format_range(from, to))
+        .collect())
+}
+
+fn next_bucket(from: NaiveDate, bucket: Bucket) -> Option<NaiveDate> {
+    match bucket {
+        Bucket::Week => from.checked_add_days(Days::new(7)),
+        Bucket::Month => from.checked_add_months(Months::new(1)),
+        Bucket::Quarter => from.checked_add_months(Months::new(3)),
+        Bucket::Custom => None,
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| Error::DateParse {
+        input: date.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+fn out_of_range(date: &str) -> Error {
+    Error::DateParse {
+        input: date.to_string(),
+        reason: "fuera de rango".to_string(),
+    }
+}
+
+fn format_range(from: NaiveDate, to: NaiveDate) -> String {
+    format!("{}-{}", from.format("%Y%m%d"), to.format("%Y%m%d"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(start_date: &str, months: usize) -> GridQuery {
+        GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string()],
+            start_date: start_date.to_string(),
+            months,
+            aggregation: Default::default(),
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Bucket::Month,
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        }
+    }
+
+    #[test]
+    fn timeranges_start_on_the_first_of_the_month() {
+        assert_eq!(
+            generate_timeranges(&query("2024-11-15", 3)).unwrap(),
+            vec![
+                "20241101-20241201",
+                "20241201-20250101",
+                "20250101-20250201",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_start_date_is_a_date_parse_error() {
+        match generate_timeranges(&query("2024-13-01", 1)) {
+            Err(Error::DateParse { input, .. }) => assert_eq!(input, "2024-13-01"),
+            other => panic!("se esperaba DateParse, llegó {:?}", other),
+        }
+    }
+
+    #[test]
+    fn end_date_bounds_weeks_and_quarters() {
+        let mut weeks = query("2024-01-03", 0);
+        weeks.bucket = Bucket::Week;
+        weeks.end_date = Some("2024-01-20".to_string());
+        assert_eq!(
+            generate_timeranges(&weeks).unwrap(),
+            vec![
+                "20240103-20240110",
+                "20240110-20240117",
+                "20240117-20240124",
+            ]
+        );
+
+        let mut quarters = query("2024-05-10", 0);
+        quarters.bucket = Bucket::Quarter;
+        quarters.end_date = Some("2025-01-01".to_string());
+        assert_eq!(
+            generate_timeranges(&quarters).unwrap(),
+            vec![
+                "20240401-20240701",
+                "20240701-20241001",
+                "20241001-20250101"
+            ]
+        );
+
+        quarters.end_date = Some("2024-01-01".to_string());
+        assert!(matches!(
+            generate_timeranges(&quarters),
+            Err(Error::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn custom_ranges_are_validated_and_sorted() {
+        let mut custom = query("2024-01-01", 0);
+        custom.bucket = Bucket::Custom;
+        custom.custom_ranges = vec![
+            "20240301-20240315".to_string(),
+            "20240101-20240115".to_string(),
+        ];
+        assert_eq!(
+            generate_timeranges(&custom).unwrap(),
+            vec!["20240101-20240115", "20240301-20240315"]
+        );
+        assert!((periods_per_year(&custom) - 365.25 / 14.0).abs() < 1e-9);
+
+        custom.custom_ranges.push("20240101-20240131".to_string());
+        assert!(matches!(
+            generate_timeranges(&custom),
+            Err(Error::InvalidQuery(_))
+        ));
+
+        custom.custom_ranges.pop();
+        custom.custom_ranges.push("20240401".to_string());
+        assert!(matches!(
+            generate_timeranges(&custom),
+            Err(Error::DateParse { .. })
+        ));
+    }
+}
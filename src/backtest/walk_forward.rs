@@ -0,0 +1,328 @@
+// src/backtest/walk_forward.rs
+//
+// Validación walk-forward sobre los periodos de la consulta (meses por
+// defecto): en cada pliegue los periodos in-sample ordenan las configuraciones
+// y los out-of-sample siguientes las puntúan. La ventana avanza `out_of_sample_months` cada vez.
+
+use csv::Writer;
+use std::fs::File;
+
+use crate::backtest::logic::optional;
+use crate::backtest::timerange::generate_timeranges;
+use crate::backtest::model::{GridQuery, StrategyGridRow};
+use crate::error::{Error, Result};
+
+/// Tamaño de las ventanas, en periodos de `GridQuery::bucket`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalkForwardParams {
+    pub in_sample_months: usize,
+    pub out_of_sample_months: usize,
+}
+
+impl Default for WalkForwardParams {
+    fn default() -> Self {
+        Self {
+            in_sample_months: 6,
+            out_of_sample_months: 1,
+        }
+    }
+}
+
+/// Meses (`AAAAMMDD`) de un pliegue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkForwardFold {
+    pub in_sample: Vec<String>,
+    pub out_of_sample: Vec<String>,
+}
+
+/// Resultado de una configuración a lo largo de todos los pliegues.
+#[derive(Debug, Clone)]
+pub struct WalkForwardRow {
+    pub config: StrategyGridRow,
+    /// Posición media por profit in-sample (1 = mejor).
+    pub avg_in_sample_rank: f64,
+    pub best_in_sample_rank: usize,
+    /// Profit mensual medio en los meses in-sample.
+    pub in_sample_profit: f64,
+    /// Profit mensual medio en los meses out-of-sample.
+    pub out_of_sample_profit: f64,
+    /// Pliegues con profit out-of-sample positivo.
+    pub positive_folds: usize,
+    /// Profit out-of-sample / profit in-sample. Cerca de 1 es robusto; por
+    /// debajo de 0 indica sobreajuste. Vale 0 si el in-sample no es positivo.
+    pub degradation: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct WalkForwardReport {
+    pub folds: Vec<WalkForwardFold>,
+    /// Ordenadas por posición in-sample media.
+    pub rows: Vec<WalkForwardRow>,
+}
+
+/// Pliegues sobre los periodos de `query`; error si no cabe ninguno.
+pub fn folds(query: &GridQuery, params: &WalkForwardParams) -> Result<Vec<WalkForwardFold>> {
+    if params.in_sample_months == 0 || params.out_of_sample_months == 0 {
+        return Err(Error::InvalidQuery(
+            "las ventanas in-sample y out-of-sample deben tener al menos un periodo".into(),
+        ));
+    }
+
+    let months: Vec<String> = generate_timeranges(query)?
+        .into_iter()
+        .map(|timerange| timerange[0..8].to_string())
+        .collect();
+    let window = params.in_sample_months + params.out_of_sample_months;
+    if months.len() < window {
+        return Err(Error::InvalidQuery(format!(
+            "walk-forward necesita al menos {} periodos ({} in-sample + {} out-of-sample) y la consulta tiene {}",
+            window,
+            params.in_sample_months,
+            params.out_of_sample_months,
+            months.len()
+        )));
+    }
+
+    Ok((0..=months.len() - window)
+        .step_by(params.out_of_sample_months)
+        .map(|start| {
+            let split = start + params.in_sample_months;
+            WalkForwardFold {
+                in_sample: months[start..split].to_vec(),
+                out_of_sample: months[split..start + window].to_vec(),
+            }
+        })
+        .collect())
+}
+
+/// Evalúa las configuraciones de `rows` en cada pliegue. Los periodos sin
+/// resultado cuentan como profit 0.
+pub fn walk_forward(
+    rows: &[StrategyGridRow],
+    query: &GridQuery,
+    params: &WalkForwardParams,
+) -> Result<WalkForwardReport> {
+    let folds = folds(query, params)?;
+
+    let mut rank_sum = vec![0usize; rows.len()];
+    let mut best_rank = vec![usize::MAX; rows.len()];
+    let mut in_sample_sum = vec![0.0; rows.len()];
+    let mut out_of_sample_sum = vec![0.0; rows.len()];
+    let mut positive_folds = vec![0usize; rows.len()];
+
+    for fold in &folds {
+        let in_sample: Vec<f64> = rows
+            .iter()
+            .map(|row| profit(row, &fold.in_sample))
+            .collect();
+
+        let mut order: Vec<usize> = (0..rows.len()).collect();
+        order.sort_by(|&a, &b| {
+            in_sample[b]
+                .partial_cmp(&in_sample[a])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        for (position, &i) in order.iter().enumerate() {
+            let rank = position + 1;
+            rank_sum[i] += rank;
+            best_rank[i] = best_rank[i].min(rank);
+
+            let out_of_sample = profit(&rows[i], &fold.out_of_sample);
+            in_sample_sum[i] += in_sample[i];
+            out_of_sample_sum[i] += out_of_sample;
+            if out_of_sample > 0.0 {
+                positive_folds[i] += 1;
+            }
+        }
+    }
+
+    let n_folds = folds.len() as f64;
+    let in_sample_months = n_folds * params.in_sample_months as f64;
+    let out_of_sample_months = n_folds * params.out_of_sample_months as f64;
+
+    let mut report_rows: Vec<WalkForwardRow> = rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let in_sample_profit = in_sample_sum[i] / in_sample_months;
+            let out_of_sample_profit = out_of_sample_sum[i] / out_of_sample_months;
+            WalkForwardRow {
+                config: row.clone(),
+                avg_in_sample_rank: rank_sum[i] as f64 / n_folds,
+                best_in_sample_rank: best_rank[i],
+                in_sample_profit,
+                out_of_sample_profit,
+                positive_folds: positive_folds[i],
+                degradation: if in_sample_profit > 0.0 {
+                    out_of_sample_profit / in_sample_profit
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect();
+
+    report_rows.sort_by(|a, b| {
+        a.avg_in_sample_rank
+            .partial_cmp(&b.avg_in_sample_rank)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(WalkForwardReport {
+        folds,
+        rows: report_rows,
+    })
+}
+
+fn profit(row: &StrategyGridRow, months: &[String]) -> f64 {
+    months
+        .iter()
+        .map(|month| row.monthly.get(month).copied().unwrap_or(0.0))
+        .sum()
+}
+
+pub fn export_walk_forward_to_csv(report: &WalkForwardReport, filename: &str) -> Result<()> {
+    let file = File::create(filename).map_err(|e| Error::export(filename, e))?;
+    let mut wtr = Writer::from_writer(file);
+
+    wtr.write_record([
+        "strategy",
+        "tf",
+        "roi",
+        "roi_0m",
+        "sl",
+        "max_open_trades",
+        "ts",
+        "tsp",
+        "tspo",
+        "toor",
+        "entry_price",
+        "exit_price",
+        "depth_mkt",
+        "avg_is_rank",
+        "best_is_rank",
+        "is_profit",
+        "oos_profit",
+        "positive_folds",
+        "folds",
+        "degradation",
+    ])
+    .map_err(|e| Error::export(filename, e))?;
+
+    let folds = report.folds.len().to_string();
+    for row in &report.rows {
+        let config = &row.config;
+        wtr.write_record([
+            config.strategy.as_str(),
+            &config.timeframe,
+            &config.minimal_roi,
+            &optional(config.initial_roi()),
+            &optional(config.stoploss_value),
+            &config.max_open_trades.to_string(),
+            &config.trailing_stop.to_string(),
+            &config.trailing_stop_positive.unwrap_or(0.0).to_string(),
+            &config
+                .trailing_stop_positive_offset
+                .unwrap_or(0.0)
+                .to_string(),
+            &config.trailing_only_offset_is_reached.to_string(),
+            &config.entry_price,
+            &config.exit_price,
+            &config.check_depth_of_market_enable.to_string(),
+            &format!("{:.2}", row.avg_in_sample_rank),
+            &row.best_in_sample_rank.to_string(),
+            &format!("{:.4}", row.in_sample_profit),
+            &format!("{:.4}", row.out_of_sample_profit),
+            &row.positive_folds.to_string(),
+            &folds,
+            &format!("{:.2}", row.degradation),
+        ])
+        .map_err(|e| Error::export(filename, e))?;
+    }
+
+    wtr.flush().map_err(|e| Error::export(filename, e))?;
+    println!("✅ Reporte walk-forward exportado a '{}'", filename);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn query(months: usize) -> GridQuery {
+        GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months,
+            aggregation: Default::default(),
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        }
+    }
+
+    fn row(strategy: &str, profits: &[f64]) -> StrategyGridRow {
+        let monthly: HashMap<String, f64> = profits
+            .iter()
+            .enumerate()
+            .map(|(i, profit)| (format!("2024{:02}01", i + 1), *profit))
+            .collect();
+        StrategyGridRow {
+            strategy: strategy.to_string(),
+            monthly,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn folds_roll_by_the_out_of_sample_window() {
+        let params = WalkForwardParams {
+            in_sample_months: 2,
+            out_of_sample_months: 1,
+        };
+        let folds = folds(&query(4), &params).unwrap();
+        assert_eq!(folds.len(), 2);
+        assert_eq!(folds[0].in_sample, vec!["20240101", "20240201"]);
+        assert_eq!(folds[0].out_of_sample, vec!["20240301"]);
+        assert_eq!(folds[1].in_sample, vec!["20240201", "20240301"]);
+        assert_eq!(folds[1].out_of_sample, vec!["20240401"]);
+
+        assert!(matches!(
+            super::folds(&query(2), &params),
+            Err(Error::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn overfit_configuration_degrades_out_of_sample() {
+        let params = WalkForwardParams {
+            in_sample_months: 2,
+            out_of_sample_months: 1,
+        };
+        let rows = [
+            row("Overfit", &[0.10, 0.10, -0.05, -0.05]),
+            row("Robust", &[0.02, 0.02, 0.02, 0.02]),
+        ];
+        let report = walk_forward(&rows, &query(4), &params).unwrap();
+
+        let overfit = &report.rows[0];
+        assert_eq!(overfit.config.strategy, "Overfit");
+        // in-sample: 0.20 y 0.05 → rangos 1 y 1
+        assert_eq!(overfit.avg_in_sample_rank, 1.0);
+        assert!((overfit.in_sample_profit - 0.25 / 4.0).abs() < 1e-9);
+        assert!((overfit.out_of_sample_profit + 0.05).abs() < 1e-9);
+        assert!(overfit.degradation < 0.0);
+        assert_eq!(overfit.positive_folds, 0);
+
+        let robust = &report.rows[1];
+        assert_eq!(robust.avg_in_sample_rank, 2.0);
+        assert!((robust.degradation - 1.0).abs() < 1e-9);
+        assert_eq!(robust.positive_folds, 2);
+    }
+}
@@ -1,90 +1,739 @@
 #![cfg(feature = "cli")]
 use chrono::Local;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use freqdash::{
-    backtest::logic::export_summary_to_csv,
-    backtest::logic::{GridQuery, get_grid_summary},
-    config::init,
+    backtest::cache::cached_grid_summary,
+    backtest::drilldown::{export_trades_to_csv, load_row_detail},
+    backtest::filter::{self, Filter},
+    backtest::aggregate::Progress,
+    backtest::logic::{export_summary_to_csv, get_grid_summary_with_progress},
+    backtest::markets::{
+        compare_markets, export_market_comparison_to_csv, market_label, market_labels,
+    },
+    backtest::model::{
+        AccumulationMode, AggregationMode, Bucket, CoveragePolicy, GridQuery, StrategyGridRow,
+    },
+    backtest::scoring::{rank, ScoreFormula},
+    backtest::walk_forward::{
+        export_walk_forward_to_csv, folds, walk_forward, WalkForwardParams,
+    },
+    config, db, import,
+    presets::{self, Preset, SortOrder},
+    schema,
 };
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    #[arg(short, long, default_value = "BINANCE")]
-    exchange: String,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    #[arg(short, long, default_value = "USDT")]
-    currency: String,
+    /// Perfil de base de datos de la configuración (por defecto `default_profile`)
+    #[arg(long, global = true)]
+    profile: Option<String>,
 
-    #[arg(short, long, default_value = "BTC")]
-    pairlist: String,
+    /// Fórmula de puntuación para ordenar: nombre de `[scoring.formulas]` o expresión
+    /// (p. ej. "profit - 2*drawdown; trades >= 100"); por defecto `scoring.default`
+    #[arg(long, global = true)]
+    score: Option<String>,
 
-    #[arg(short, long, default_value = "2024-01-01")]
-    start_date: String,
+    /// Filtro sobre las configuraciones, p. ej. `trades >= 200 && timeframe in ["5m","15m"]`;
+    /// los ratios van como fracción o con `%` (`drawdown < 0.15` o `drawdown < 15%`)
+    #[arg(long, global = true)]
+    filter: Option<String>,
 
-    #[arg(short, long, default_value_t = 6)]
-    months: usize,
+    /// Preset guardado (consulta, filtros y orden); las demás opciones tienen prioridad
+    #[arg(long, global = true)]
+    preset: Option<String>,
+
+    /// Vuelve a calcular el resumen sin leer ni guardar la caché
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    // Sin valor se usan los de la sección `[query]` de la configuración.
+    /// Exchanges a consultar, separados por comas
+    #[arg(short, long = "exchange", value_delimiter = ',')]
+    exchanges: Vec<String>,
+
+    /// Monedas a consultar, separadas por comas
+    #[arg(short, long = "currency", value_delimiter = ',')]
+    currencies: Vec<String>,
+
+    /// Pairlist a consultar (repetible; cada valor puede contener comas, p. ej. `BTC,ETH`)
+    #[arg(short, long = "pairlist")]
+    pairlists: Vec<String>,
+
+    #[arg(short, long)]
+    start_date: Option<String>,
+
+    /// Número de periodos si no se indica `--end-date`
+    #[arg(short, long)]
+    months: Option<usize>,
+
+    /// Fecha de fin (AAAA-MM-DD); tiene prioridad sobre `--months`
+    #[arg(long)]
+    end_date: Option<String>,
+
+    /// Tamaño de periodo: `week`, `month`, `quarter` o `custom`
+    #[arg(short, long)]
+    bucket: Option<Bucket>,
+
+    /// Rango AAAAMMDD-AAAAMMDD para `--bucket custom` (repetible, sin repetir inicio);
+    /// implica `custom`
+    #[arg(long = "range")]
+    ranges: Vec<String>,
+
+    /// Configuraciones con periodos sin resultado: `fill_zero`, `penalize` o `exclude`
+    #[arg(long)]
+    coverage: Option<CoveragePolicy>,
+
+    /// Dónde agrupar las filas: `client` (Rust) o `server` (GROUP BY en Postgres)
+    #[arg(short, long)]
+    aggregation: Option<AggregationMode>,
+
+    /// Curva de capital: `compounded` (interés compuesto) o `simple`
+    #[arg(long)]
+    accumulation: Option<AccumulationMode>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Importa resultados de `freqtrade backtesting` (.json/.zip o directorios) en la tabla backtest
+    Import {
+        /// Ficheros o directorios `backtest-results`
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Exchange para resultados que no incluyen la configuración usada
+        #[arg(long)]
+        exchange: Option<String>,
+    },
+    /// Crea o actualiza la tabla backtest aplicando las migraciones pendientes
+    Migrate,
+    /// Lista los perfiles de base de datos configurados
+    Profiles,
+    /// Lista los presets guardados
+    Presets,
+    /// Validación walk-forward: ordena por los periodos in-sample y puntúa con los siguientes
+    WalkForward {
+        /// Periodos in-sample de cada pliegue
+        #[arg(long, default_value_t = 6)]
+        in_sample: usize,
+
+        /// Periodos out-of-sample de cada pliegue (también el avance de la ventana)
+        #[arg(long, default_value_t = 1)]
+        out_of_sample: usize,
+
+        /// Configuraciones a mostrar en consola
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Estabilidad de las mejores configuraciones frente a sus vecinas de la rejilla
+    Sensitivity {
+        /// Configuraciones a mostrar en consola
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Compara cada configuración entre los mercados consultados, ordenada por robustez
+    Compare {
+        /// Configuraciones a mostrar en consola
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Detalle de una configuración del resumen: sus periodos, pares y trades
+    Show {
+        /// Posición de la configuración en el resumen ordenado (la del Top, desde 1)
+        #[arg(default_value_t = 1)]
+        rank: usize,
+
+        /// Trades a mostrar en consola; todos se exportan a CSV
+        #[arg(long, default_value_t = 10)]
+        trades: usize,
+    },
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    init().await;
+async fn main() -> ExitCode {
     let args = Args::parse();
 
-    let params = GridQuery {
-        exchange: args.exchange,
-        currency: args.currency,
-        pairlist: args.pairlist,
-        start_date: args.start_date,
-        months: args.months,
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    config::init_config()?;
+    if let Some(profile) = &args.profile {
+        config::set_active_profile(profile)?;
+    }
+
+    match args.command {
+        Some(Command::Import { ref paths, ref exchange }) => {
+            run_import(paths, exchange.as_deref()).await
+        }
+        Some(Command::Migrate) => run_migrate().await,
+        Some(Command::Profiles) => run_profiles(),
+        Some(Command::Presets) => run_presets(),
+        Some(Command::WalkForward {
+            in_sample,
+            out_of_sample,
+            top,
+        }) => {
+            let params = WalkForwardParams {
+                in_sample_months: in_sample,
+                out_of_sample_months: out_of_sample,
+            };
+            run_walk_forward(&args, &params, top).await
+        }
+        Some(Command::Sensitivity { top }) => run_sensitivity(&args, top).await,
+        Some(Command::Compare { top }) => run_compare(&args, top).await,
+        Some(Command::Show { rank, trades }) => run_show(&args, rank, trades).await,
+        None => run_summary(args).await,
+    }
+}
+
+async fn run_import(
+    paths: &[PathBuf],
+    exchange: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    db::init_db_pool()?;
+    let pool = db::get_db_pool()?;
+    let mut client = pool.get().await?;
+
+    let report = import::import_paths(&mut client, paths, exchange).await?;
+    println!(
+        "✅ Importados {} ficheros: {} backtests, {} pares, {} trades",
+        report.files, report.backtests, report.pairs, report.trades
+    );
+    Ok(())
+}
+
+async fn run_migrate() -> Result<(), Box<dyn std::error::Error>> {
+    db::init_db_pool()?;
+    let pool = db::get_db_pool()?;
+    let mut client = pool.get().await?;
+
+    let applied = schema::migrate(&mut client).await?;
+    if applied.is_empty() {
+        println!("✅ El esquema ya estaba al día");
+    } else {
+        for version in &applied {
+            println!("🗄️ Migración V{} aplicada", version);
+        }
+    }
+
+    schema::verify(&client).await?;
+    schema::verify_id(&client).await?;
+    println!("✅ Tabla backtest verificada");
+    Ok(())
+}
+
+fn run_profiles() -> Result<(), Box<dyn std::error::Error>> {
+    let active = config::active_profile_name()?;
+    for (name, profile) in &config::settings()?.profiles {
+        let marker = if *name == active { "*" } else { " " };
+        println!("{} {} → {}", marker, name, profile.masked_url());
+    }
+    Ok(())
+}
+
+fn run_presets() -> Result<(), Box<dyn std::error::Error>> {
+    let all = presets::load_all()?;
+    if all.is_empty() {
+        println!("No hay presets guardados en {}", presets::path()?.display());
+    }
+    for (name, preset) in &all {
+        let filters = &preset.filters;
+        let mut parts = vec![
+            format!(
+                "{} desde {}",
+                preset.query.exchanges.join(","),
+                preset.query.start_date
+            ),
+            format!("orden {}", preset.sort.column),
+        ];
+        if !filters.switches.is_empty() {
+            parts.push(format!("filtros rápidos {}", filters.switches.join(",")));
+        }
+        if !filters.expression.is_empty() {
+            parts.push(format!("filtro '{}'", filters.expression));
+        }
+        println!("{}: {}", name, parts.join(" | "));
+    }
+    Ok(())
+}
+
+/// Fórmula de `--score` o, sin ella, la de `scoring.default`.
+fn score_formula(args: &Args) -> Result<ScoreFormula, Box<dyn std::error::Error>> {
+    let scoring = &config::settings()?.scoring;
+    Ok(match &args.score {
+        Some(score) => scoring.formula(score)?,
+        None => scoring.default_formula()?,
+    })
+}
+
+/// Preset de `--preset`, si se indicó.
+fn preset(args: &Args) -> Result<Option<Preset>, Box<dyn std::error::Error>> {
+    Ok(args.preset.as_deref().map(presets::load).transpose()?)
+}
+
+/// Filtros del preset seguidos del de `--filter`.
+fn row_filters(args: &Args) -> Result<Vec<Filter>, Box<dyn std::error::Error>> {
+    let mut filters = match preset(args)? {
+        Some(preset) => preset.filters.filters(&config::settings()?.filters)?,
+        None => Vec::new(),
     };
+    if let Some(filter) = &args.filter {
+        filters.push(filter.parse()?);
+    }
+    Ok(filters)
+}
 
-    println!("Ejecutando backtest con parámetros:");
-    println!("Exchange: {}", params.exchange);
-    println!("Moneda: {}", params.currency);
-    println!("Pares: {}", params.pairlist);
-    println!("Fecha inicio: {}", params.start_date);
-    println!("Meses: {}", params.months);
+/// Consulta, resumen y filtros; informa de las filas descartadas.
+async fn filtered_summary(
+    args: &Args,
+    query: &GridQuery,
+) -> Result<Vec<StrategyGridRow>, Box<dyn std::error::Error>> {
+    // Los filtros se validan antes de consultar la fuente
+    let filters = row_filters(args)?;
+    let mut summary = if args.no_cache {
+        get_grid_summary_with_progress(db::get_source()?.as_ref(), query, &print_progress).await?
+    } else {
+        let (summary, status) = cached_grid_summary(query, &print_progress).await?;
+        if status.is_hit() {
+            println!("⚡ Resumen desde la {} ({} configuraciones)", status, summary.len());
+        }
+        summary
+    };
+    for filter in filters {
+        let total = summary.len();
+        filter::apply(&mut summary, &filter);
+        println!(
+            "🔎 Filtro '{}': {} de {} configuraciones",
+            filter,
+            summary.len(),
+            total
+        );
+    }
+    Ok(summary)
+}
 
-    // Manejar el Result correctamente
-    let mut summary = match get_grid_summary(&params).await {
-        Ok(data) => {
-            println!("✅ Consulta ejecutada exitosamente. {} estrategias encontradas.", data.len());
-            data
+/// Línea de progreso de la agrupación, reescrita en el sitio.
+fn print_progress(progress: Progress) {
+    match progress.fraction() {
+        Some(fraction) => print!("\r⏳ {:>3.0}% · {}", fraction * 100.0, progress),
+        None => print!("\r⏳ {}", progress),
+    }
+    if progress.done {
+        println!();
+        if let Some(report) = &progress.fetch {
+            println!("⏱️ Lectura por tramos: {}", report);
         }
-        Err(e) => {
-            eprintln!("❌ Error ejecutando consulta: {}", e);
-            return Err(e);
+    }
+    io::stdout().flush().ok();
+}
+
+/// Puntúa con la fórmula y, si el preset ordena por otra columna, reordena.
+/// Devuelve la columna por la que quedan ordenadas.
+fn sort_summary(
+    args: &Args,
+    summary: &mut [StrategyGridRow],
+    formula: &ScoreFormula,
+) -> Result<String, Box<dyn std::error::Error>> {
+    rank(summary, formula);
+    let sort = preset(args)?.map(|preset| preset.sort).unwrap_or_default();
+    if sort != SortOrder::default() {
+        sort.apply(summary)?;
+        return Ok(format!(
+            "{} ({})",
+            sort.column,
+            if sort.descending { "desc" } else { "asc" }
+        ));
+    }
+    Ok("puntuación".to_string())
+}
+
+/// Consulta de la línea de comandos completada con la del preset o, sin
+/// él, con la sección `[query]`.
+fn grid_query(args: &Args) -> Result<GridQuery, Box<dyn std::error::Error>> {
+    let defaults = match preset(args)? {
+        Some(preset) => preset.query,
+        None => config::settings()?.query.grid_query(),
+    };
+    Ok(GridQuery {
+        exchanges: or_defaults(&args.exchanges, defaults.exchanges),
+        currencies: or_defaults(&args.currencies, defaults.currencies),
+        pairlists: or_defaults(&args.pairlists, defaults.pairlists),
+        start_date: args.start_date.clone().unwrap_or(defaults.start_date),
+        months: args.months.unwrap_or(defaults.months),
+        aggregation: args.aggregation.unwrap_or(defaults.aggregation),
+        accumulation: args.accumulation.unwrap_or(defaults.accumulation),
+        end_date: args.end_date.clone().or(defaults.end_date),
+        bucket: match args.bucket {
+            Some(bucket) => bucket,
+            None if !args.ranges.is_empty() => Bucket::Custom,
+            None => defaults.bucket,
+        },
+        custom_ranges: if args.ranges.is_empty() {
+            defaults.custom_ranges
+        } else {
+            args.ranges.clone()
+        },
+        coverage_policy: args.coverage.unwrap_or(defaults.coverage_policy),
+    })
+}
+
+fn or_defaults(values: &[String], defaults: Vec<String>) -> Vec<String> {
+    if values.is_empty() {
+        defaults
+    } else {
+        values.to_vec()
+    }
+}
+
+async fn run_sensitivity(args: &Args, top: usize) -> Result<(), Box<dyn std::error::Error>> {
+    db::init_source()?;
+    let query = grid_query(args)?;
+
+    let formula = score_formula(args)?;
+
+    println!("Perfil: {}", config::active_profile_name()?);
+    let mut summary = filtered_summary(args, &query).await?;
+    let order = sort_summary(args, &mut summary, &formula)?;
+
+    let isolated = summary
+        .iter()
+        .filter(|row| row.sensitivity.isolated_peak)
+        .count();
+    println!(
+        "✅ {} configuraciones analizadas, {} picos aislados.",
+        summary.len(),
+        isolated
+    );
+
+    println!("\n🧭 Top {} por {} frente a sus vecinas:", top, order);
+    for (i, row) in summary.iter().take(top).enumerate() {
+        let sensitivity = &row.sensitivity;
+        println!(
+            "{}. {} ({}) [{}] SL {} MOT {} - Profit: {:.2}% | Vecinas: {} (media {:.2}%, mín {:.2}%) | Estabilidad profit: {:.2} | Estabilidad DD: {:.2}{}",
+            i + 1,
+            row.strategy,
+            row.timeframe,
+            market_label(row),
+            row.stoploss,
+            row.max_open_trades,
+            row.total_profit,
+            sensitivity.neighbours,
+            sensitivity.neighbour_avg_profit,
+            sensitivity.neighbour_min_profit,
+            sensitivity.profit_stability,
+            sensitivity.drawdown_stability,
+            if sensitivity.isolated_peak {
+                " | ⚠️ pico aislado"
+            } else {
+                ""
+            }
+        );
+    }
+
+    let now = Local::now();
+    let filename = format!("sensibilidad_{}.csv", now.format("%Y%m%d_%H%M%S"));
+    export_summary_to_csv(&summary, &filename)?;
+    println!("\n💾 Reporte exportado a: {}", filename);
+    Ok(())
+}
+
+async fn run_compare(args: &Args, top: usize) -> Result<(), Box<dyn std::error::Error>> {
+    db::init_source()?;
+    let query = grid_query(args)?;
+
+    println!("Perfil: {}", config::active_profile_name()?);
+    let summary = filtered_summary(args, &query).await?;
+    let markets = market_labels(&summary);
+    let comparisons = compare_markets(&summary);
+    println!(
+        "✅ {} configuraciones comparadas en {} mercados: {}",
+        comparisons.len(),
+        markets.len(),
+        markets.join(", ")
+    );
+
+    println!("\n🌍 Top {} por robustez entre mercados:", top);
+    for (i, comparison) in comparisons.iter().take(top).enumerate() {
+        let profits = markets
+            .iter()
+            .map(|label| {
+                let profit = comparison
+                    .markets
+                    .iter()
+                    .find(|market| market.label() == *label)
+                    .map(|market| format!("{:.2}%", market.total_profit))
+                    .unwrap_or_else(|| "—".to_string());
+                format!("{}: {}", label, profit)
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+        println!(
+            "{}. {} ({}) - Robustez: {:.2} | Positivos: {}/{} | {}",
+            i + 1,
+            comparison.config.strategy,
+            comparison.config.timeframe,
+            comparison.robustness,
+            comparison.positive_markets,
+            markets.len(),
+            profits
+        );
+    }
+
+    let now = Local::now();
+    let filename = format!("comparacion_mercados_{}.csv", now.format("%Y%m%d_%H%M%S"));
+    export_market_comparison_to_csv(&comparisons, &filename)?;
+    println!("\n💾 Reporte exportado a: {}", filename);
+    Ok(())
+}
+
+async fn run_show(
+    args: &Args,
+    rank: usize,
+    trades: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    db::init_source()?;
+    let query = grid_query(args)?;
+    let formula = score_formula(args)?;
+
+    println!("Perfil: {}", config::active_profile_name()?);
+    let mut summary = filtered_summary(args, &query).await?;
+    let order = sort_summary(args, &mut summary, &formula)?;
+    let Some(row) = rank.checked_sub(1).and_then(|index| summary.get(index)) else {
+        return Err(format!(
+            "no hay configuración en la posición {} ({} configuraciones por {})",
+            rank,
+            summary.len(),
+            order
+        )
+        .into());
+    };
+
+    println!(
+        "\n🔍 {}. {} ({}) [{}] por {}",
+        rank,
+        row.strategy,
+        row.timeframe,
+        market_label(row),
+        order
+    );
+    println!(
+        "ROI {} | SL {} | MOT {} | Trailing: {} ({}/{}, solo offset {}) | Precios: {}/{} | Profundidad: {}",
+        row.minimal_roi,
+        row.stoploss,
+        row.max_open_trades,
+        row.trailing_stop,
+        row.trailing_stop_positive.unwrap_or(0.0),
+        row.trailing_stop_positive_offset.unwrap_or(0.0),
+        row.trailing_only_offset_is_reached,
+        row.entry_price,
+        row.exit_price,
+        row.check_depth_of_market_enable
+    );
+    let detail = load_row_detail(db::get_source()?.as_ref(), &query, row).await?;
+
+    println!("\n📅 Periodos:");
+    for period in &detail.periods {
+        match detail.months.iter().find(|month| month.timerange == *period) {
+            Some(month) => println!(
+                "{} - Profit: {:.2}% | Trades: {} | Win Rate: {:.1}% | Drawdown: {}",
+                period,
+                month.profit_total * 100.0,
+                month.total_trades,
+                if month.total_trades > 0 {
+                    month.wins as f64 / month.total_trades as f64 * 100.0
+                } else {
+                    0.0
+                },
+                month
+                    .max_drawdown
+                    .map_or_else(|| "—".to_string(), |dd| format!("{:.2}%", dd * 100.0))
+            ),
+            None => println!("{} - sin datos", period),
+        }
+    }
+
+    match &detail.pairs {
+        Some(_) => {
+            println!("\n💱 Pares:");
+            for pair in detail.pair_totals() {
+                println!(
+                    "{} - Profit: {:.2} {} ({:.2}%) | Trades: {} ({}/{}/{}) | Periodos: {}",
+                    pair.pair,
+                    pair.profit_total_abs,
+                    row.currency,
+                    pair.profit_total * 100.0,
+                    pair.trades,
+                    pair.wins,
+                    pair.draws,
+                    pair.losses,
+                    pair.periods
+                );
+            }
         }
+        None => println!("\nℹ️ La fuente no guarda resultados por par"),
+    }
+
+    let Some(all_trades) = &detail.trades else {
+        println!("ℹ️ La fuente no guarda los trades");
+        return Ok(());
     };
+    println!("\n📈 {} trades; los {} primeros:", all_trades.len(), trades.min(all_trades.len()));
+    for trade in all_trades.iter().take(trades) {
+        println!(
+            "{} {} {} - Profit: {:.2}% ({:.2} {}) | {} min | {}",
+            trade.open_date.map_or_else(
+                || "—".to_string(),
+                |date| date.format("%Y-%m-%d %H:%M").to_string()
+            ),
+            trade.pair,
+            if trade.is_short { "short" } else { "long" },
+            trade.profit_ratio * 100.0,
+            trade.profit_abs,
+            row.currency,
+            trade.trade_duration.map_or_else(|| "—".to_string(), |d| d.to_string()),
+            trade.exit_reason.as_deref().unwrap_or("—")
+        );
+    }
+
+    let now = Local::now();
+    let filename = format!("trades_{}.csv", now.format("%Y%m%d_%H%M%S"));
+    export_trades_to_csv(all_trades, &filename)?;
+    println!("\n💾 Trades exportados a: {}", filename);
+    Ok(())
+}
+
+async fn run_walk_forward(
+    args: &Args,
+    params: &WalkForwardParams,
+    top: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    db::init_source()?;
+    let query = grid_query(args)?;
 
-    // Ordenar por profit total (mayor a menor)
-    summary.sort_by(|a, b| {
-        b.total_profit
-            .partial_cmp(&a.total_profit)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    println!("Perfil: {}", config::active_profile_name()?);
+    println!(
+        "Walk-forward desde {} ({}): {} in-sample + {} out-of-sample",
+        query.start_date, query.bucket, params.in_sample_months, params.out_of_sample_months
+    );
+
+    // Valida las ventanas antes de consultar la fuente
+    folds(&query, params)?;
+
+    let summary = filtered_summary(args, &query).await?;
+    let report = walk_forward(&summary, &query, params)?;
+    println!(
+        "✅ {} estrategias evaluadas en {} pliegues.",
+        report.rows.len(),
+        report.folds.len()
+    );
+
+    println!("\n🔬 Top {} por posición in-sample media:", top);
+    for (i, row) in report.rows.iter().take(top).enumerate() {
+        println!(
+            "{}. {} ({}) - Rango IS: {:.1} (mejor {}) | IS: {:.2}%/mes | OOS: {:.2}%/mes | Degradación: {:.2} | OOS+: {}/{}",
+            i + 1,
+            row.config.strategy,
+            row.config.timeframe,
+            row.avg_in_sample_rank,
+            row.best_in_sample_rank,
+            row.in_sample_profit * 100.0,
+            row.out_of_sample_profit * 100.0,
+            row.degradation,
+            row.positive_folds,
+            report.folds.len()
+        );
+    }
+
+    let now = Local::now();
+    let filename = format!("walk_forward_{}.csv", now.format("%Y%m%d_%H%M%S"));
+    export_walk_forward_to_csv(&report, &filename)?;
+    println!("\n💾 Reporte exportado a: {}", filename);
+    Ok(())
+}
+
+async fn run_summary(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    db::init_source()?;
+
+    let params = grid_query(&args)?;
+    let formula = score_formula(&args)?;
+
+    println!("Perfil: {}", config::active_profile_name()?);
+
+    println!("Ejecutando backtest con parámetros:");
+    println!("Exchanges: {}", params.exchanges.join(", "));
+    println!("Monedas: {}", params.currencies.join(", "));
+    println!("Pares: {}", params.pairlists.join(" | "));
+    println!("Fecha inicio: {}", params.start_date);
+    match (&params.bucket, &params.end_date) {
+        (Bucket::Custom, _) => println!("Rangos: {}", params.custom_ranges.join(", ")),
+        (_, Some(end_date)) => println!("Fecha fin: {}", end_date),
+        (_, None) => println!("Periodos: {}", params.months),
+    }
+    println!("Periodo: {}", params.bucket);
+    println!("Agregación: {}", params.aggregation);
+    println!("Acumulación: {}", params.accumulation);
+    println!("Cobertura: {}", params.coverage_policy);
+    println!("Puntuación: {}", formula);
+
+    let mut summary = filtered_summary(&args, &params).await?;
+    println!("✅ Consulta ejecutada exitosamente. {} estrategias encontradas.", summary.len());
+
+    // Ordenar por puntuación (mayor a menor); las que no cumplen los requisitos, al final
+    let order = sort_summary(&args, &mut summary, &formula)?;
+    let scored = summary.iter().filter(|row| row.score.is_some()).count();
+    if scored < summary.len() {
+        println!(
+            "⚠️ {} configuraciones no cumplen los requisitos de la puntuación",
+            summary.len() - scored
+        );
+    }
 
     // Mostrar top 5 en consola
-    println!("\n🏆 Top 5 estrategias por profit total:");
+    println!("\n🏆 Top 5 estrategias por {}:", order);
     for (i, strategy) in summary.iter().take(5).enumerate() {
         println!(
-            "{}. {} ({}) - Profit: {:.2}% | Trades: {} | Win Rate: {:.1}%",
+            "{}. {} ({}) [{}] - Score: {} | Profit: {:.2}% | Trades: {} | Win Rate: {:.1}% | Sharpe: {:.2} | Calmar: {:.2} | Cobertura: {}/{}{}",
             i + 1,
             strategy.strategy,
             strategy.timeframe,
+            market_label(strategy),
+            strategy
+                .score
+                .map_or_else(|| "—".to_string(), |score| format!("{:.4}", score)),
             strategy.total_profit,
             strategy.total_trades,
-            strategy.win_rate * 100.0
+            strategy.win_rate * 100.0,
+            strategy.sharpe,
+            strategy.calmar,
+            strategy.coverage.present,
+            strategy.coverage.expected,
+            if strategy.sensitivity.isolated_peak {
+                " | ⚠️ pico aislado"
+            } else {
+                ""
+            }
         );
     }
 
     // Exportar a CSV
     let now = Local::now();
     let filename = format!("reporte_backtest_{}.csv", now.format("%Y%m%d_%H%M%S"));
-    export_summary_to_csv(&summary, &filename);
-    
+    export_summary_to_csv(&summary, &filename)?;
+
     println!("\n💾 Reporte exportado a: {}", filename);
     
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,23 +1,83 @@
 // src/db.rs
 
-use once_cell::sync::OnceCell;
 use deadpool_postgres::{Pool, Config, Runtime};
+use std::sync::{Arc, RwLock};
 use tokio_postgres::NoTls;
 use crate::config;
+use crate::error::{Error, Result};
+use crate::source::{FileSource, PostgresSource, Source, SourceConfig};
 
-static DB_POOL: OnceCell<Pool> = OnceCell::new();
+// Se reemplazan al cambiar de perfil; las consultas en curso conservan su
+// copia del pool o de la fuente anterior.
+static DB_POOL: RwLock<Option<Pool>> = RwLock::new(None);
+static SOURCE: RwLock<Option<Arc<Source>>> = RwLock::new(None);
 
-pub fn init_db_pool() {
+fn poisoned() -> Error {
+    Error::Config("estado de la conexión corrupto".into())
+}
+
+/// Crea el pool de Postgres del perfil activo con su tamaño y timeouts.
+pub fn init_db_pool() -> Result<()> {
+    let profile = config::active_profile()?;
     let mut cfg = Config::new();
-    cfg.url = Some(config::get_database_url().to_string());
-    
+    cfg.url = Some(profile.url.clone());
+    cfg.pool = Some(profile.pool.pool_config());
+
     let pool = cfg.create_pool(Some(Runtime::Tokio1), NoTls)
-        .expect("No se pudo crear el pool de la base de datos");
-    
-    DB_POOL.set(pool).expect("El pool de la base de datos ya estaba inicializado");
+        .map_err(Error::connection)?;
+
+    *DB_POOL.write().map_err(|_| poisoned())? = Some(pool);
     println!("✅ Pool de conexiones a la DB inicializado correctamente.");
+    Ok(())
+}
+
+pub fn get_db_pool() -> Result<Pool> {
+    DB_POOL
+        .read()
+        .map_err(|_| poisoned())?
+        .clone()
+        .ok_or_else(|| Error::Config("el pool de la base de datos no está inicializado".into()))
 }
 
-pub fn get_db_pool() -> &'static Pool {
-    DB_POOL.get().expect("El pool de la base de datos no está inicializado")
-}
\ No newline at end of file
+/// Inicializa la fuente de backtests del perfil activo.
+/// Para Postgres crea además el pool de conexiones.
+pub fn init_source() -> Result<()> {
+    let source = match SourceConfig::from_url(config::get_database_url()?)? {
+        SourceConfig::Postgres(_) => {
+            init_db_pool()?;
+            Source::Postgres(PostgresSource::new(get_db_pool()?))
+        }
+        #[cfg(feature = "sqlite")]
+        SourceConfig::Sqlite(path) => Source::Sqlite(crate::source::SqliteSource::new(path)),
+        #[cfg(not(feature = "sqlite"))]
+        SourceConfig::Sqlite(_) => {
+            return Err(Error::Config(
+                "freqdash se compiló sin la feature `sqlite`".into(),
+            ))
+        }
+        SourceConfig::File(path) => Source::File(FileSource::new(path)),
+    };
+
+    *SOURCE.write().map_err(|_| poisoned())? = Some(Arc::new(source));
+    Ok(())
+}
+
+pub fn get_source() -> Result<Arc<Source>> {
+    SOURCE
+        .read()
+        .map_err(|_| poisoned())?
+        .clone()
+        .ok_or_else(|| Error::Config("la fuente de backtests no está inicializada".into()))
+}
+
+/// Activa otro perfil y recrea la fuente. Si falla se mantiene el anterior.
+pub fn switch_profile(name: &str) -> Result<()> {
+    let previous = config::active_profile_name()?;
+    config::set_active_profile(name)?;
+    if let Err(e) = init_source() {
+        config::set_active_profile(&previous).ok();
+        return Err(e);
+    }
+    println!("🔀 Perfil activo: {}", name);
+    Ok(())
+}
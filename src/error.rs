@@ -0,0 +1,140 @@
+// src/error.rs
+
+use std::fmt;
+
+use crate::schema::SchemaError;
+
+/// Error de origen arbitrario (deadpool, rusqlite, csv, parquet...).
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errores de la biblioteca. Cada variante indica qué revisar: la
+/// configuración, la conexión, la consulta, el esquema, la fecha o el
+/// fichero exportado o importado.
+#[derive(Debug)]
+pub enum Error {
+    /// Falta `DATABASE_URL`, su valor no es válido o la inicialización se
+    /// hizo en un orden incorrecto.
+    Config(String),
+    /// No se pudo crear el pool o abrir una conexión con la fuente.
+    Connection(BoxError),
+    /// La fuente rechazó la consulta o devolvió datos ilegibles.
+    Query(BoxError),
+    /// La tabla `backtest` no tiene el esquema de `migrations/`.
+    Schema(SchemaError),
+    /// Parámetros de consulta o de análisis incompatibles entre sí.
+    InvalidQuery(String),
+    /// `minimal_roi` o `stoploss` de la fuente que no se pueden interpretar.
+    Hyperparameter {
+        field: &'static str,
+        input: String,
+        reason: String,
+    },
+    /// Fórmula o expresión del usuario mal escrita; `position` es el byte
+    /// de `input` (desde 0) donde se detectó el error. El mensaje lo da en
+    /// caracteres.
+    Expression {
+        input: String,
+        position: usize,
+        message: String,
+    },
+    /// Preset que no existe o cuyo contenido no se puede aplicar.
+    Preset { name: String, reason: String },
+    /// Fecha de inicio inválida o fuera de rango.
+    DateParse { input: String, reason: String },
+    /// No se pudo escribir el fichero exportado.
+    Export { path: String, source: BoxError },
+    /// Un resultado de freqtrade que no se pudo leer o interpretar.
+    Import { path: String, reason: String },
+}
+
+impl Error {
+    pub fn connection(e: impl Into<BoxError>) -> Self {
+        Error::Connection(e.into())
+    }
+
+    pub fn query(e: impl Into<BoxError>) -> Self {
+        Error::Query(e.into())
+    }
+
+    pub fn export(path: impl Into<String>, e: impl Into<BoxError>) -> Self {
+        Error::Export {
+            path: path.into(),
+            source: e.into(),
+        }
+    }
+
+    pub fn import(path: impl Into<String>, reason: impl fmt::Display) -> Self {
+        Error::Import {
+            path: path.into(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Config(message) => write!(f, "configuración inválida: {}", message),
+            Error::Connection(e) => write!(f, "no se pudo conectar con la fuente: {}", e),
+            Error::Query(e) => write!(f, "error ejecutando la consulta: {}", e),
+            Error::Schema(e) => write!(f, "{}", e),
+            Error::InvalidQuery(message) => write!(f, "consulta inválida: {}", message),
+            Error::Hyperparameter {
+                field,
+                input,
+                reason,
+            } => write!(f, "{} '{}' no interpretable: {}", field, input, reason),
+            Error::Expression {
+                input,
+                position,
+                message,
+            } => write!(
+                f,
+                "expresión '{}' inválida en la posición {}: {}",
+                input,
+                input
+                    .get(..*position)
+                    .map_or(*position, |before| before.chars().count()),
+                message
+            ),
+            Error::Preset { name, reason } => write!(f, "preset '{}': {}", name, reason),
+            Error::DateParse { input, reason } => write!(
+                f,
+                "fecha '{}' inválida ({}); use el formato AAAA-MM-DD",
+                input, reason
+            ),
+            Error::Export { path, source } => {
+                write!(f, "no se pudo exportar a '{}': {}", path, source)
+            }
+            Error::Import { path, reason } => {
+                write!(f, "no se pudo importar '{}': {}", path, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Connection(e) | Error::Query(e) | Error::Export { source: e, .. } => {
+                Some(e.as_ref())
+            }
+            Error::Schema(e) => Some(e),
+            Error::Config(_)
+            | Error::InvalidQuery(_)
+            | Error::Hyperparameter { .. }
+            | Error::Expression { .. }
+            | Error::Preset { .. }
+            | Error::DateParse { .. }
+            | Error::Import { .. } => None,
+        }
+    }
+}
+
+impl From<SchemaError> for Error {
+    fn from(e: SchemaError) -> Self {
+        Error::Schema(e)
+    }
+}
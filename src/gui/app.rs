@@ -13,8 +13,13 @@ use crate::gui::state::AppState;
 use crate::gui::{events, ui, utils};
 use crate::{config, db};
 
-use crate::backtest::logic::get_grid_summary;
+use crate::backtest::aggregate::Progress;
+use crate::backtest::cache::{cached_grid_summary, CacheStatus};
+use crate::backtest::drilldown::{load_row_detail, StrategyDetail};
+use crate::backtest::hyperparams::invalid_hyperparameters;
+use crate::backtest::markets::robustness_by_config;
 use crate::backtest::model::{GridQuery, StrategyGridRow};
+use crate::backtest::scoring::rank;
 use crate::gui::events::query::{self, BATCH_SIZE};
 
 static TOKIO_RUNTIME: OnceCell<Runtime> = OnceCell::new();
@@ -26,18 +31,30 @@ pub fn get_runtime() -> &'static Runtime {
 #[derive(Debug, Clone)]
 pub enum DatabaseCommand {
     RunBacktest(GridQuery),
+    /// Detalle de una fila de los resultados de la consulta.
+    ShowDetail(GridQuery, StrategyGridRow),
 }
 
 #[derive(Debug)]
 pub enum DatabaseResult {
-    Backtest(Result<Vec<StrategyGridRow>, String>),
+    /// Avance de la agrupación de la consulta en curso.
+    Progress(Progress),
+    Backtest(Result<(Vec<StrategyGridRow>, CacheStatus), String>),
+    Detail(Result<(StrategyGridRow, StrategyDetail), String>),
 }
 
 pub fn run() {
-    config::init_config();
-    let rt = Runtime::new().unwrap();
-    TOKIO_RUNTIME.set(rt).unwrap();
-    db::init_db_pool();
+    let rt = Runtime::new().expect("Failed to create Tokio runtime");
+    TOKIO_RUNTIME.set(rt).expect("Runtime already initialized");
+
+    // Sin fuente la ventana se abre igualmente y muestra el motivo.
+    let init_error = config::init_config()
+        .and_then(|_| db::init_source())
+        .err()
+        .map(|e| e.to_string());
+    if let Some(e) = &init_error {
+        eprintln!("❌ {}", e);
+    }
 
     let (command_tx, command_rx) = mpsc::channel::<DatabaseCommand>();
     let (result_tx, result_rx) = mpsc::channel::<DatabaseResult>();
@@ -50,7 +67,7 @@ pub fn run() {
     let result_rx = Rc::new(RefCell::new(result_rx));
 
     app.connect_activate(move |app| {
-        build_ui(app, command_tx.clone(), result_rx.clone());
+        build_ui(app, command_tx.clone(), result_rx.clone(), init_error.clone());
     });
     app.run();
 }
@@ -59,6 +76,7 @@ fn build_ui(
     app: &adw::Application,
     command_tx: mpsc::Sender<DatabaseCommand>,
     result_rx: Rc<RefCell<mpsc::Receiver<DatabaseResult>>>,
+    init_error: Option<String>,
 ) {
     let header_bar = ui::toolbar::create();
     let left_panel = ui::left_panel::create();
@@ -117,6 +135,9 @@ fn build_ui(
             &app_state_clone,
             command_tx_for_connect,
         );
+        if let Some(e) = &init_error {
+            query::update_status(&right_panel_clone, &format!("❌ Error: {}", e));
+        }
 
         // Clone variables for the timer
         let result_rx_for_timer = result_rx_clone.clone();
@@ -127,13 +148,33 @@ fn build_ui(
 
         glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
             if let Ok(rx) = result_rx_for_timer.try_borrow() {
-                if let Ok(msg) = rx.try_recv() {
+                // Los avisos de progreso llegan en ráfagas: se vacía la cola
+                while let Ok(msg) = rx.try_recv() {
                     let state = app_state_for_timer.clone();
                     let left_panel = left_panel_for_timer.clone();
                     let right_panel = right_panel_for_timer.clone();
                     let header_bar = header_bar_for_timer.clone();
 
                     match msg {
+                        DatabaseResult::Progress(progress) => {
+                            let progress_bar: gtk4::ProgressBar =
+                                utils::find_widget(&left_panel, "progress");
+                            match progress.fraction() {
+                                Some(fraction) => progress_bar.set_fraction(fraction),
+                                None => progress_bar.pulse(),
+                            }
+                            progress_bar.set_show_text(true);
+                            progress_bar.set_text(Some(&progress.to_string()));
+                            let message = if progress.done {
+                                format!("Agrupadas {}", progress)
+                            } else {
+                                format!("Leyendo {}", progress)
+                            };
+                            query::update_status(&right_panel, &message);
+                            if progress.fetch.is_some() {
+                                state.borrow_mut().fetch_report = progress.fetch;
+                            }
+                        }
                         DatabaseResult::Backtest(result) => {
                             let button: gtk4::Button = utils::find_widget(&left_panel, "execute");
                             let progress_bar: gtk4::ProgressBar =
@@ -141,16 +182,21 @@ fn build_ui(
                             let spinner: gtk4::Spinner =
                                 utils::find_widget(&right_panel, "spinner");
                             match result {
-                                Ok(mut rows) => {
-                                    rows.sort_by(|a, b| {
-                                        b.total_profit
-                                            .partial_cmp(&a.total_profit)
-                                            .unwrap_or(std::cmp::Ordering::Equal)
-                                    });
+                                Ok((mut rows, cache_status)) => {
+                                    // Orden inicial por la fórmula `scoring.default`
+                                    let formula = config::settings()
+                                        .and_then(|s| s.scoring.default_formula())
+                                        .unwrap_or_default();
+                                    rank(&mut rows, &formula);
+                                    if let Err(e) = state.borrow().sort.apply(&mut rows) {
+                                        eprintln!("⚠️ {}", e);
+                                    }
                                     let total_rows = rows.len();
                                     query::update_results_count(&header_bar, total_rows);
                                     state.borrow_mut().results = rows.clone();
-                                    
+                                    let robustness = robustness_by_config(&rows);
+                                    let invalid = invalid_hyperparameters(&rows).len();
+
                                     let batch_state = Rc::new(RefCell::new((rows, 0)));
                                     glib::idle_add_local(move || {
                                         let mut state_guard = batch_state.borrow_mut();
@@ -159,18 +205,34 @@ fn build_ui(
                                         query::populate_store_batch(
                                             &state.borrow().store,
                                             &all_rows[*current_index..end],
+                                            &robustness,
+                                            &state.borrow().months,
                                         );
                                         *current_index = end;
                                         if *current_index < all_rows.len() {
                                             glib::ControlFlow::Continue
                                         } else {
-                                            query::update_status(
-                                                &right_panel,
-                                                &format!(
-                                                    "✅ {} resultados encontrados",
-                                                    total_rows
-                                                ),
+                                            let mut message =
+                                                format!("✅ {} resultados encontrados", total_rows);
+                                            if cache_status.is_hit() {
+                                                message.push_str(&format!(" · ⚡ {}", cache_status));
+                                            }
+                                            if let Some(report) = &state.borrow().fetch_report {
+                                                message.push_str(&format!(" · ⏱️ {}", report));
+                                            }
+                                            if invalid > 0 {
+                                                message.push_str(&format!(
+                                                    " · ⚠️ {} valores de ROI o stoploss no interpretables",
+                                                    invalid
+                                                ));
+                                            }
+                                            query::update_status(&right_panel, &message);
+                                            // Reaplica los filtros activos a las filas nuevas
+                                            let expression: gtk4::Entry = utils::find_widget(
+                                                &left_panel,
+                                                "filter_expression",
                                             );
+                                            expression.emit_by_name::<()>("changed", &[]);
                                             query::enable_export_buttons(
                                                 &header_bar,
                                                 !all_rows.is_empty(),
@@ -197,6 +259,9 @@ fn build_ui(
                                 }
                             }
                         }
+                        DatabaseResult::Detail(result) => {
+                            events::detail::present(&right_panel, result);
+                        }
                     }
                 }
             }
@@ -215,16 +280,27 @@ fn database_worker(
     while let Ok(command) = command_rx.recv() {
         let result_tx = result_tx.clone();
         rt.spawn(async move {
-            let client = db::get_db_pool()
-                .get()
-                .await
-                .expect("Failed to get client from pool");
             match command {
                 DatabaseCommand::RunBacktest(query) => {
-                    let result = get_grid_summary(&client, &query)
+                    let progress_tx = result_tx.clone();
+                    let on_progress = move |progress| {
+                        let _ = progress_tx.send(DatabaseResult::Progress(progress));
+                    };
+                    let result = cached_grid_summary(&query, &on_progress)
                         .await
                         .map_err(|e| e.to_string());
-                    result_tx.send(DatabaseResult::Backtest(result)).unwrap();
+                    // La ventana puede haberse cerrado mientras tanto.
+                    let _ = result_tx.send(DatabaseResult::Backtest(result));
+                }
+                DatabaseCommand::ShowDetail(query, row) => {
+                    let result = match db::get_source() {
+                        Ok(source) => load_row_detail(source.as_ref(), &query, &row)
+                            .await
+                            .map(|detail| (row, detail))
+                            .map_err(|e| e.to_string()),
+                        Err(e) => Err(e.to_string()),
+                    };
+                    let _ = result_tx.send(DatabaseResult::Detail(result));
                 }
             }
         });
@@ -1,8 +1,12 @@
 // src/gui/events/mod.rs
 
+pub mod charts;
+pub mod columns;
+pub mod detail;
 pub mod export;
 pub mod filters;
 pub mod handlers;
+pub mod presets;
 pub mod query;
 
 pub use handlers::connect_all;
@@ -0,0 +1,87 @@
+// src/gui/events/charts.rs
+
+use adw::HeaderBar;
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Button, TreeView};
+use libadwaita as adw;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::backtest::charts::{equity_series, Heatmap};
+use crate::backtest::model::StrategyGridRow;
+use crate::gui::events::{detail, query};
+use crate::gui::state::AppState;
+use crate::gui::ui::charts_window;
+use crate::gui::utils;
+
+/// Filas del mapa de calor: las primeras visibles, en el orden de la vista.
+const MAX_HEATMAP_ROWS: usize = 200;
+/// Curvas de capital a la vez; sin selección, las de las primeras filas.
+const MAX_CURVES: usize = 8;
+
+pub fn connect(
+    header_bar: &HeaderBar,
+    right_panel: &GtkBox,
+    tree_view: &TreeView,
+    state: &Rc<RefCell<AppState>>,
+) {
+    let button: Button = utils::find_widget(header_bar, "charts");
+    let right_panel = right_panel.clone();
+    let tree_view = tree_view.clone();
+    let state = state.clone();
+
+    button.connect_clicked(move |_| {
+        let state = state.borrow();
+        let visible = visible_indices(&tree_view);
+        if visible.is_empty() {
+            query::update_status(&right_panel, "ℹ️ No hay filas visibles que dibujar");
+            return;
+        }
+        let (selected_rows, _) = tree_view.selection().selected_rows();
+        let mut curves: Vec<usize> = selected_rows
+            .iter()
+            .filter_map(|path| detail::result_index(&tree_view, path))
+            .collect();
+        let selected = !curves.is_empty();
+        if !selected {
+            curves = visible.clone();
+        }
+        curves.truncate(MAX_CURVES);
+
+        let rows_at = |indices: &[usize]| -> Vec<StrategyGridRow> {
+            indices.iter().filter_map(|i| state.results.get(*i).cloned()).collect()
+        };
+        let heatmap = Heatmap::from_rows(&rows_at(&visible[..visible.len().min(MAX_HEATMAP_ROWS)]));
+        let mode = state.query.as_ref().map(|q| q.accumulation).unwrap_or_default();
+        let (months, series) = equity_series(&rows_at(&curves), mode);
+
+        let summary = format!(
+            "Mapa de calor: {} de {} filas visibles · Curvas ({}): {} {}",
+            heatmap.rows.len(),
+            visible.len(),
+            mode,
+            series.len(),
+            if selected { "filas seleccionadas" } else { "primeras filas visibles" }
+        );
+        let parent = right_panel.root().and_downcast::<gtk4::Window>();
+        charts_window::create(parent.as_ref(), heatmap, months, series, &summary).present();
+    });
+}
+
+/// Posiciones en `AppState::results` de las filas que muestra la vista, en
+/// su orden actual.
+fn visible_indices(tree_view: &TreeView) -> Vec<usize> {
+    let Some(model) = tree_view.model() else {
+        return Vec::new();
+    };
+    let mut indices = Vec::new();
+    if let Some(iter) = model.iter_first() {
+        loop {
+            indices.extend(detail::result_index(tree_view, &model.path(&iter)));
+            if !model.iter_next(&iter) {
+                break;
+            }
+        }
+    }
+    indices
+}
@@ -0,0 +1,54 @@
+// src/gui/events/columns.rs
+
+use adw::HeaderBar;
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, CheckButton, MenuButton, TreeView};
+use libadwaita as adw;
+
+use crate::backtest::charts::month_label;
+use crate::gui::ui::table_view;
+use crate::gui::utils;
+
+/// "Todos los periodos" marca o desmarca la casilla de cada columna mensual
+pub fn connect(header_bar: &HeaderBar) {
+    let (all_months, month_checks) = month_widgets(header_bar);
+    all_months.connect_toggled(move |all_months| {
+        let mut child = month_checks.first_child();
+        while let Some(check) = child {
+            if let Some(check) = check.downcast_ref::<CheckButton>() {
+                check.set_active(all_months.is_active());
+            }
+            child = check.next_sibling();
+        }
+    });
+}
+
+/// Crea las columnas mensuales de `months` y una casilla por columna que la
+/// muestra u oculta. Las nuevas columnas siguen a "Todos los periodos".
+pub fn show_months(header_bar: &HeaderBar, tree_view: &TreeView, months: &[String]) {
+    let (all_months, month_checks) = month_widgets(header_bar);
+    while let Some(child) = month_checks.first_child() {
+        month_checks.remove(&child);
+    }
+
+    let columns = table_view::set_month_columns(tree_view, months, all_months.is_active());
+    for (month, col) in months.iter().zip(&columns) {
+        let check = CheckButton::with_label(&month_label(month));
+        check.set_active(col.is_visible());
+        check.bind_property("active", col, "visible").build();
+        month_checks.append(&check);
+    }
+
+    let menu: MenuButton = utils::find_widget(header_bar, "month_columns");
+    menu.set_sensitive(!months.is_empty());
+}
+
+/// La casilla "Todos los periodos" y la caja de casillas del menú.
+fn month_widgets(header_bar: &HeaderBar) -> (CheckButton, GtkBox) {
+    let menu: MenuButton = utils::find_widget(header_bar, "month_columns");
+    let popover = menu.popover().expect("El menú de columnas mensuales no tiene popover");
+    (
+        utils::find_widget(&popover, "all_months"),
+        utils::find_widget(&popover, "month_checks"),
+    )
+}
@@ -0,0 +1,76 @@
+// src/gui/events/detail.rs
+
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, TreeModelFilter, TreeModelSort, TreePath, TreeView};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc;
+
+use crate::backtest::drilldown::StrategyDetail;
+use crate::backtest::markets::market_label;
+use crate::backtest::model::StrategyGridRow;
+use crate::gui::app::DatabaseCommand;
+use crate::gui::events::query;
+use crate::gui::state::AppState;
+use crate::gui::ui::detail_window;
+
+/// Al activar una fila (doble clic o Intro) se pide su detalle al trabajador
+pub fn connect(
+    right_panel: &GtkBox,
+    tree_view: &TreeView,
+    state: &Rc<RefCell<AppState>>,
+    command_tx: mpsc::Sender<DatabaseCommand>,
+) {
+    let right_panel = right_panel.clone();
+    let state = state.clone();
+    tree_view.connect_row_activated(move |view, path, _| {
+        let state = state.borrow();
+        let (Some(index), Some(params)) = (result_index(view, path), &state.query) else {
+            return;
+        };
+        let Some(row) = state.results.get(index) else {
+            return;
+        };
+        query::update_status(
+            &right_panel,
+            &format!("🔍 Cargando el detalle de {} ({})...", row.strategy, row.timeframe),
+        );
+        command_tx
+            .send(DatabaseCommand::ShowDetail(params.clone(), row.clone()))
+            .expect("Failed to send command");
+    });
+}
+
+/// Abre la ventana de detalle o informa del error.
+pub fn present(
+    right_panel: &GtkBox,
+    result: Result<(StrategyGridRow, StrategyDetail), String>,
+) {
+    match result {
+        Ok((row, detail)) => {
+            query::update_status(
+                right_panel,
+                &format!(
+                    "🔍 Detalle de {} ({}) [{}]: {} periodos",
+                    row.strategy,
+                    row.timeframe,
+                    market_label(&row),
+                    detail.months.len()
+                ),
+            );
+            let parent = right_panel.root().and_downcast::<gtk4::Window>();
+            detail_window::create(parent.as_ref(), &row, &detail).present();
+        }
+        Err(e) => query::update_status(right_panel, &format!("❌ Error: {}", e)),
+    }
+}
+
+/// Posición en `AppState::results` de la fila `path` de la vista: el
+/// store sigue el orden de los resultados, por debajo del filtro y el orden.
+pub fn result_index(view: &TreeView, path: &TreePath) -> Option<usize> {
+    let sort = view.model()?.downcast::<TreeModelSort>().ok()?;
+    let filter = sort.model().downcast::<TreeModelFilter>().ok()?;
+    let filtered = sort.convert_path_to_child_path(path)?;
+    let path = filter.convert_path_to_child_path(&filtered)?;
+    path.indices().first().map(|index| *index as usize)
+}
@@ -2,7 +2,7 @@
 
 use adw::HeaderBar;
 use gtk4::prelude::*;
-use gtk4::{Button, TreeModelFilter, TreeView}; // <-- FIX: Added TreeModelFilter
+use gtk4::{Button, TreeView};
 use libadwaita as adw;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -10,6 +10,7 @@ use std::rc::Rc;
 use crate::backtest::logic::export_summary_to_csv;
 use crate::backtest::model::StrategyGridRow;
 use crate::gui::state::AppState;
+use crate::gui::ui::table_view;
 use crate::gui::utils;
 
 pub fn connect(
@@ -114,10 +115,7 @@ fn connect_clear(
             .collect();
 
         let new_store = gtk4::ListStore::new(&column_types);
-        let new_filter_model = TreeModelFilter::new(&new_store, None);
-        new_filter_model.set_visible_column(30);
-
-        tree_view_clone.set_model(Some(&new_filter_model));
+        let new_filter_model = table_view::attach_store(&tree_view_clone, &new_store);
 
         state.store = new_store;
         state.filter_model = new_filter_model;
@@ -128,8 +126,10 @@ fn connect_clear(
 
         let export_selection: Button = utils::find_widget(&header_bar_clone, "export_selection");
         let export_all: Button = utils::find_widget(&header_bar_clone, "export_all");
+        let charts: Button = utils::find_widget(&header_bar_clone, "charts");
         export_selection.set_sensitive(false);
         export_all.set_sensitive(false);
+        charts.set_sensitive(false);
 
         println!("Limpieza completada.");
     });
@@ -1,41 +1,75 @@
 // src/gui/events/filters.rs
 
 use gtk4::prelude::*;
+use gtk4::pango::{AttrInt, AttrList, Underline};
 use std::rc::Rc;
 use std::cell::RefCell;
 use glib::value::ToValue;
 
+use crate::backtest::filter::Filter;
+use crate::config;
+use crate::error::Error;
 use crate::gui::state::AppState;
 use crate::gui::utils;
 
 /// Conecta los eventos de filtrado con componentes Adwaita
 pub fn connect(panel: &gtk4::Box, state: &Rc<RefCell<AppState>>) {
     let search: gtk4::SearchEntry = utils::find_widget(panel, "search");
-    
-    // Buscar switches en lugar de checkbuttons
-    let profit_switch: gtk4::Switch = utils::find_widget(panel, "filter_profit");
-    let winrate_switch: gtk4::Switch = utils::find_widget(panel, "filter_winrate");
-    let trades_switch: gtk4::Switch = utils::find_widget(panel, "filter_trades");
-    let pf_switch: gtk4::Switch = utils::find_widget(panel, "filter_pf");
-    let expectancy_switch: gtk4::Switch = utils::find_widget(panel, "filter_expectancy");
+    let expression: gtk4::Entry = utils::find_widget(panel, "filter_expression");
+    let thresholds = config::settings()
+        .map(|s| s.filters.clone())
+        .unwrap_or_default();
+
+    // Cada switch `filter_<nombre>` activa un filtro ya interpretado
+    let switches: Vec<(gtk4::Switch, Filter)> = thresholds
+        .switches()
+        .into_iter()
+        .filter_map(|(name, source)| {
+            let switch: gtk4::Switch = utils::find_widget(panel, &format!("filter_{}", name));
+            match source.parse::<Filter>() {
+                Ok(filter) => Some((switch, filter)),
+                Err(e) => {
+                    eprintln!("⚠️ Filtro '{}' ignorado: {}", name, e);
+                    None
+                }
+            }
+        })
+        .collect();
 
     let state_clone = state.clone();
 
     // Clonar widgets para el closure
     let search_clone = search.clone();
-    let profit_switch_clone = profit_switch.clone();
-    let winrate_switch_clone = winrate_switch.clone();
-    let trades_switch_clone = trades_switch.clone();
-    let pf_switch_clone = pf_switch.clone();
-    let expectancy_switch_clone = expectancy_switch.clone();
+    let expression_clone = expression.clone();
+    let switches_clone = switches.clone();
 
     let apply_filters = move || {
         let search_text = search_clone.text().to_string().to_lowercase();
-        let filter_profit = profit_switch_clone.is_active();
-        let filter_winrate = winrate_switch_clone.is_active();
-        let filter_trades = trades_switch_clone.is_active();
-        let filter_pf = pf_switch_clone.is_active();
-        let filter_expectancy = expectancy_switch_clone.is_active();
+
+        // Expresión del usuario; si no se puede interpretar se marca y no se aplica
+        let typed = expression_clone.text().to_string();
+        let custom = if typed.trim().is_empty() {
+            show_expression_error(&expression_clone, None);
+            None
+        } else {
+            match typed.parse::<Filter>() {
+                Ok(filter) => {
+                    show_expression_error(&expression_clone, None);
+                    Some(filter)
+                }
+                Err(e) => {
+                    show_expression_error(&expression_clone, Some(&e));
+                    None
+                }
+            }
+        };
+
+        let active: Vec<&Filter> = switches_clone
+            .iter()
+            .filter(|(switch, _)| switch.is_active())
+            .map(|(_, filter)| filter)
+            .chain(custom.as_ref())
+            .collect();
 
         let state = state_clone.borrow();
         let store = &state.store;
@@ -43,67 +77,16 @@ pub fn connect(panel: &gtk4::Box, state: &Rc<RefCell<AppState>>) {
         let mut visible_count = 0;
         let total_count = store.iter_n_children(None);
 
+        // Las filas del store están en el mismo orden que `results`
         if let Some(iter) = store.iter_first() {
-            loop {
-                let mut visible = true;
-
-                // Filtro de búsqueda por texto
-                if !search_text.is_empty() {
-                    if let Ok(strategy) = store.get_value(&iter, 29).get::<String>() {
-                        if !strategy.contains(&search_text) {
-                            visible = false;
-                        }
-                    }
-                }
-
-                // Filtro por profit positivo
-                if visible && filter_profit {
-                    if let Ok(profit) = store.get_value(&iter, 12).get::<f64>() {
-                        if profit <= 0.0 {
-                            visible = false;
-                        }
-                    }
-                }
-
-                // Filtro por win rate
-                if visible && filter_winrate {
-                    if let Ok(winrate) = store.get_value(&iter, 15).get::<f64>() {
-                        if winrate <= 0.5 {
-                            visible = false;
-                        }
-                    }
-                }
-
-                // Filtro por número de trades
-                if visible && filter_trades {
-                    if let Ok(trades) = store.get_value(&iter, 13).get::<i32>() {
-                        if trades <= 100 {
-                            visible = false;
-                        }
-                    }
-                }
-
-                // Filtro por profit factor
-                if visible && filter_pf {
-                    if let Ok(pf) = store.get_value(&iter, 28).get::<f64>() {
-                        if pf <= 1.0 {
-                            visible = false;
-                        }
-                    }
-                }
-                
-                // Filtro por expectancy
-                if visible && filter_expectancy {
-                    if let Ok(expectancy) = store.get_value(&iter, 27).get::<f64>() {
-                        if expectancy <= 0.0 {
-                            visible = false;
-                        }
-                    }
-                }
+            for row in &state.results {
+                let visible = (search_text.is_empty()
+                    || row.strategy.to_lowercase().contains(&search_text))
+                    && active.iter().all(|filter| filter.matches(row));
 
                 // Actualizar visibilidad en el store
                 store.set_value(&iter, 30, &visible.to_value());
-                
+
                 if visible {
                     visible_count += 1;
                 }
@@ -113,7 +96,7 @@ pub fn connect(panel: &gtk4::Box, state: &Rc<RefCell<AppState>>) {
                 }
             }
         }
-        
+
         // Mostrar información sobre filtros aplicados
         println!("Filtros aplicados: {} de {} visibles", visible_count, total_count);
     };
@@ -129,29 +112,42 @@ pub fn connect(panel: &gtk4::Box, state: &Rc<RefCell<AppState>>) {
         }
     });
 
-    // Conectar switches con notificación de estado
-    profit_switch.connect_state_notify({
-        let filters = filters_clone.clone();
-        move |_| filters()
-    });
-
-    winrate_switch.connect_state_notify({
+    expression.connect_changed({
         let filters = filters_clone.clone();
         move |_| filters()
     });
 
-    trades_switch.connect_state_notify({
-        let filters = filters_clone.clone();
-        move |_| filters()
-    });
-
-    pf_switch.connect_state_notify({
-        let filters = filters_clone.clone();
-        move |_| filters()
-    });
-    
-    expectancy_switch.connect_state_notify({
-        let filters = filters_clone.clone();
-        move |_| filters()
-    });
-}
\ No newline at end of file
+    // Conectar switches con notificación de estado
+    for (switch, _) in &switches {
+        switch.connect_state_notify({
+            let filters = filters_clone.clone();
+            move |_| filters()
+        });
+    }
+}
+
+/// Marca la entrada como errónea y subraya desde la posición del error hasta
+/// el final; con `None` la deja limpia.
+fn show_expression_error(entry: &gtk4::Entry, error: Option<&Error>) {
+    match error {
+        Some(error) => {
+            entry.add_css_class("error");
+            entry.set_tooltip_text(Some(&error.to_string()));
+            if let Error::Expression { position, .. } = error {
+                let text_len = entry.text().len();
+                let start = (*position).min(text_len.saturating_sub(1)) as u32;
+                let mut underline = AttrInt::new_underline(Underline::Error);
+                underline.set_start_index(start);
+                underline.set_end_index(text_len.max(start as usize + 1) as u32);
+                let attributes = AttrList::new();
+                attributes.insert(underline);
+                entry.set_attributes(&attributes);
+            }
+        }
+        None => {
+            entry.remove_css_class("error");
+            entry.set_tooltip_text(None);
+            entry.set_attributes(&AttrList::new());
+        }
+    }
+}
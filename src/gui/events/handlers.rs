@@ -5,7 +5,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::mpsc; // <-- Use the standard library's MPSC
 
-use super::{export, filters, query};
+use super::{charts, columns, detail, export, filters, presets, query};
 use crate::gui::app::DatabaseCommand;
 use crate::gui::state::AppState;
 
@@ -25,6 +25,10 @@ pub fn connect_all(
         state,
         command_tx.clone(),
     );
+    detail::connect(right_panel, tree_view, state, command_tx.clone());
     filters::connect(left_panel, state);
+    presets::connect(left_panel, right_panel, tree_view, state);
     export::connect(header_bar, right_panel, tree_view, state);
+    charts::connect(header_bar, right_panel, tree_view, state);
+    columns::connect(header_bar);
 }
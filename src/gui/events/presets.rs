@@ -0,0 +1,186 @@
+// src/gui/events/presets.rs
+
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Button, SortColumn, SortType, TreeModelSort, TreeView};
+use libadwaita::prelude::*;
+use libadwaita::ComboRow;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::config;
+use crate::gui::events::query;
+use crate::gui::state::AppState;
+use crate::gui::ui::table_view::SORT_COLUMNS;
+use crate::gui::utils;
+use crate::presets::{self, FilterState, Preset, SortOrder};
+
+/// Conecta los botones de cargar, borrar y guardar presets
+pub fn connect(
+    left_panel: &GtkBox,
+    right_panel: &GtkBox,
+    tree_view: &TreeView,
+    state: &Rc<RefCell<AppState>>,
+) {
+    let preset_row: ComboRow = utils::find_widget(left_panel, "preset");
+    let load_button: Button = utils::find_widget(left_panel, "preset_load");
+    let delete_button: Button = utils::find_widget(left_panel, "preset_delete");
+    let save_button: Button = utils::find_widget(left_panel, "preset_save");
+    let name_entry: gtk4::Entry = utils::find_widget(left_panel, "preset_name");
+
+    load_button.connect_clicked({
+        let preset_row = preset_row.clone();
+        let left_panel = left_panel.clone();
+        let right_panel = right_panel.clone();
+        let tree_view = tree_view.clone();
+        let state = state.clone();
+        move |_| {
+            let name = utils::get_combo_row_text(&preset_row);
+            if name.is_empty() {
+                return;
+            }
+            let message = match presets::load(&name) {
+                Ok(preset) => {
+                    set_filter_state(&left_panel, &preset.filters);
+                    query::set_query_params(&left_panel, &preset.query);
+                    set_sort(&tree_view, &preset.sort);
+                    state.borrow_mut().sort = preset.sort;
+                    format!("📂 Preset '{}' cargado", name)
+                }
+                Err(e) => format!("❌ Error: {}", e),
+            };
+            query::update_status(&right_panel, &message);
+        }
+    });
+
+    delete_button.connect_clicked({
+        let preset_row = preset_row.clone();
+        let right_panel = right_panel.clone();
+        move |_| {
+            let name = utils::get_combo_row_text(&preset_row);
+            if name.is_empty() {
+                return;
+            }
+            let message = match presets::delete(&name) {
+                Ok(_) => {
+                    refresh(&preset_row, None);
+                    format!("🗑️ Preset '{}' borrado", name)
+                }
+                Err(e) => format!("❌ Error: {}", e),
+            };
+            query::update_status(&right_panel, &message);
+        }
+    });
+
+    save_button.connect_clicked({
+        let left_panel = left_panel.clone();
+        let right_panel = right_panel.clone();
+        let tree_view = tree_view.clone();
+        let state = state.clone();
+        move |_| {
+            let name = name_entry.text().trim().to_string();
+            if name.is_empty() {
+                query::update_status(&right_panel, "⚠️ Escriba un nombre para el preset");
+                return;
+            }
+            let preset = Preset {
+                query: query::get_query_params(&left_panel),
+                filters: filter_state(&left_panel),
+                sort: current_sort(&tree_view).unwrap_or_else(|| state.borrow().sort.clone()),
+            };
+            let message = match presets::save(&name, &preset) {
+                Ok(()) => {
+                    refresh(&preset_row, Some(&name));
+                    name_entry.set_text("");
+                    format!("💾 Preset '{}' guardado", name)
+                }
+                Err(e) => format!("❌ Error: {}", e),
+            };
+            query::update_status(&right_panel, &message);
+        }
+    });
+}
+
+/// Vuelve a leer los nombres y selecciona `selected` si se indica.
+fn refresh(preset_row: &ComboRow, selected: Option<&str>) {
+    let names: Vec<String> = presets::load_all()
+        .map(|presets| presets.into_keys().collect())
+        .unwrap_or_default();
+    let refs: Vec<&str> = names.iter().map(String::as_str).collect();
+    preset_row.set_model(Some(&gtk4::StringList::new(&refs)));
+    if let Some(index) = selected.and_then(|name| names.iter().position(|n| n == name)) {
+        preset_row.set_selected(index as u32);
+    }
+}
+
+/// Búsqueda, expresión y switches activos del panel de filtros.
+fn filter_state(panel: &GtkBox) -> FilterState {
+    let search: gtk4::SearchEntry = utils::find_widget(panel, "search");
+    let expression: gtk4::Entry = utils::find_widget(panel, "filter_expression");
+    let thresholds = config::settings()
+        .map(|s| s.filters.clone())
+        .unwrap_or_default();
+    FilterState {
+        search: search.text().to_string(),
+        expression: expression.text().to_string(),
+        switches: thresholds
+            .switches()
+            .iter()
+            .map(|(name, _)| *name)
+            .filter(|name| {
+                let switch: gtk4::Switch = utils::find_widget(panel, &format!("filter_{}", name));
+                switch.is_active()
+            })
+            .map(String::from)
+            .collect(),
+    }
+}
+
+/// Los controles de filtro disparan el refiltrado al cambiar.
+fn set_filter_state(panel: &GtkBox, filters: &FilterState) {
+    let search: gtk4::SearchEntry = utils::find_widget(panel, "search");
+    search.set_text(&filters.search);
+    let expression: gtk4::Entry = utils::find_widget(panel, "filter_expression");
+    expression.set_text(&filters.expression);
+    let thresholds = config::settings()
+        .map(|s| s.filters.clone())
+        .unwrap_or_default();
+    for (name, _) in thresholds.switches() {
+        let switch: gtk4::Switch = utils::find_widget(panel, &format!("filter_{}", name));
+        switch.set_active(filters.switches.iter().any(|active| active == name));
+    }
+}
+
+/// Orden elegido en las cabeceras de la tabla, si corresponde a un campo.
+fn current_sort(tree_view: &TreeView) -> Option<SortOrder> {
+    let model = tree_view.model()?.downcast::<TreeModelSort>().ok()?;
+    let (SortColumn::Index(index), sort_type) = model.sort_column_id()? else {
+        return None;
+    };
+    SORT_COLUMNS
+        .iter()
+        .find(|(_, column)| *column == index)
+        .map(|(name, _)| SortOrder {
+            column: name.to_string(),
+            descending: sort_type == SortType::Descending,
+        })
+}
+
+/// Ordena la tabla actual por la columna del preset, si se muestra.
+fn set_sort(tree_view: &TreeView, sort: &SortOrder) {
+    let Some(model) = tree_view
+        .model()
+        .and_then(|model| model.downcast::<TreeModelSort>().ok())
+    else {
+        return;
+    };
+    let column = SORT_COLUMNS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(&sort.column))
+        .map(|(_, column)| SortColumn::Index(*column));
+    let sort_type = if sort.descending {
+        SortType::Descending
+    } else {
+        SortType::Ascending
+    };
+    model.set_sort_column_id(column.unwrap_or(SortColumn::Default), sort_type);
+}
@@ -2,20 +2,27 @@
 
 use gtk4::prelude::*;
 use gtk4::{
-    glib, Box as GtkBox, Button, Label, ListStore, ProgressBar, Spinner, TreeModelFilter, TreeView,
+    glib, Box as GtkBox, Button, Label, ListStore, ProgressBar, Spinner, TreeView,
 };
+use libadwaita::prelude::*;
 use libadwaita::ComboRow;
 use libadwaita::HeaderBar;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::mpsc;
 use std::thread;
 use tokio::runtime::Runtime;
 
 use crate::backtest::logic::get_grid_summary;
-use crate::backtest::model::{GridQuery, StrategyGridRow};
+use crate::backtest::markets::{config_key, market_label};
+use crate::backtest::model::{AccumulationMode, AggregationMode, GridQuery, StrategyGridRow};
 use crate::gui::app::{get_runtime, DatabaseCommand};
+use crate::db;
+use crate::gui::events::columns;
 use crate::gui::state::AppState;
+use crate::gui::ui::left_panel::{BUCKETS, COVERAGE_POLICIES};
+use crate::gui::ui::table_view;
 use crate::gui::utils;
 use glib::value::ToValue;
 
@@ -31,6 +38,21 @@ pub fn connect(
 ) {
     let execute_button: Button = utils::find_widget(left_panel, "execute");
 
+    // Cambiar de perfil recrea la fuente; la próxima consulta ya la usa.
+    let profile: ComboRow = utils::find_widget(left_panel, "profile");
+    let right_panel_for_profile = right_panel.clone();
+    profile.connect_selected_notify(move |row| {
+        let name = utils::get_combo_row_text(row);
+        if name.is_empty() {
+            return;
+        }
+        let message = match db::switch_profile(&name) {
+            Ok(()) => format!("🔀 Perfil activo: {}", name),
+            Err(e) => format!("❌ Error: {}", e),
+        };
+        update_status(&right_panel_for_profile, &message);
+    });
+
     let tree_view_clone = tree_view.clone();
     let state_clone = state.clone();
     let left_panel_clone = left_panel.clone();
@@ -42,20 +64,22 @@ pub fn connect(
             return;
         }
 
+        let query = get_query_params(&left_panel_clone);
         let mut state = state_clone.borrow_mut();
         state.is_loading = true;
 
-        let column_types: Vec<glib::Type> = (0..state.store.n_columns())
-            .map(|i| state.store.column_type(i))
-            .collect();
-        let new_store = ListStore::new(&column_types);
-        let new_filter_model = TreeModelFilter::new(&new_store, None);
-        new_filter_model.set_visible_column(30);
-        tree_view_clone.set_model(Some(&new_filter_model));
+        // Una columna mensual por periodo de la consulta
+        let months = table_view::month_keys(&query);
+        let new_store = table_view::create_list_store(months.len());
+        let new_filter_model = table_view::attach_store(&tree_view_clone, &new_store);
         state.store = new_store;
         state.filter_model = new_filter_model;
+        state.months = months;
         state.results.clear();
+        state.fetch_report = None;
+        state.query = Some(query.clone());
         drop(state);
+        columns::show_months(&header_bar_clone, &tree_view_clone, &state_clone.borrow().months);
 
         let execute_button: Button = utils::find_widget(&left_panel_clone, "execute");
         let progress_bar: ProgressBar = utils::find_widget(&left_panel_clone, "progress");
@@ -63,13 +87,14 @@ pub fn connect(
         let spinner: Spinner = utils::find_widget(&right_panel_clone, "spinner");
 
         execute_button.set_sensitive(false);
+        progress_bar.set_fraction(0.0);
+        progress_bar.set_text(None);
         progress_bar.set_visible(true);
         status_label.set_text("Enviando consulta al trabajador...");
         spinner.set_visible(true);
         spinner.start();
         enable_export_buttons(&header_bar_clone, false);
 
-        let query = get_query_params(&left_panel_clone);
         command_tx
             .send(DatabaseCommand::RunBacktest(query))
             .expect("Failed to send command");
@@ -77,72 +102,170 @@ pub fn connect(
 }
 
 pub fn get_query_params(panel: &GtkBox) -> GridQuery {
-    let exchange: ComboRow = utils::find_widget(panel, "exchange");
-    let currency: ComboRow = utils::find_widget(panel, "currency");
-    let exchange_text = utils::get_combo_row_text(&exchange);
-    let currency_text = utils::get_combo_row_text(&currency);
-    let pairlist: gtk4::Entry = utils::find_widget(panel, "pairlist");
+    let exchanges: gtk4::Entry = utils::find_widget(panel, "exchanges");
+    let currencies: gtk4::Entry = utils::find_widget(panel, "currencies");
+    let pairlists: gtk4::Entry = utils::find_widget(panel, "pairlists");
     let start_date: gtk4::Entry = utils::find_widget(panel, "start_date");
     let months: gtk4::SpinButton = utils::find_widget(panel, "months");
+    let end_date: gtk4::Entry = utils::find_widget(panel, "end_date");
+    let bucket: ComboRow = utils::find_widget(panel, "bucket");
+    let custom_ranges: gtk4::Entry = utils::find_widget(panel, "custom_ranges");
+    let end_date = end_date.text().trim().to_string();
+    let server_aggregation: gtk4::Switch = utils::find_widget(panel, "server_aggregation");
+    let compounded: gtk4::Switch = utils::find_widget(panel, "compounded");
+    let coverage_policy: ComboRow = utils::find_widget(panel, "coverage_policy");
 
     GridQuery {
-        exchange: if exchange_text.is_empty() {
-            "BINANCE".to_string()
+        exchanges: split_or(&exchanges.text(), ',', "BINANCE"),
+        currencies: split_or(&currencies.text(), ',', "USDT"),
+        pairlists: split_or(&pairlists.text(), ';', "BTC"),
+        start_date: start_date.text().to_string(),
+        months: months.value() as usize,
+        aggregation: if server_aggregation.is_active() {
+            AggregationMode::Server
         } else {
-            exchange_text
+            AggregationMode::Client
         },
-        currency: if currency_text.is_empty() {
-            "USDT".to_string()
+        accumulation: if compounded.is_active() {
+            AccumulationMode::Compounded
         } else {
-            currency_text
+            AccumulationMode::Simple
         },
-        pairlist: pairlist.text().to_string(),
-        start_date: start_date.text().to_string(),
-        months: months.value() as usize,
+        end_date: (!end_date.is_empty()).then_some(end_date),
+        bucket: BUCKETS
+            .get(bucket.selected() as usize)
+            .map(|(bucket, _)| *bucket)
+            .unwrap_or_default(),
+        custom_ranges: custom_ranges
+            .text()
+            .split(',')
+            .map(str::trim)
+            .filter(|range| !range.is_empty())
+            .map(String::from)
+            .collect(),
+        coverage_policy: COVERAGE_POLICIES
+            .get(coverage_policy.selected() as usize)
+            .map(|(policy, _)| *policy)
+            .unwrap_or_default(),
     }
 }
 
-pub fn populate_store_batch(store: &ListStore, batch: &[StrategyGridRow]) {
+/// Inversa de `get_query_params`: rellena los controles con `query`.
+pub fn set_query_params(panel: &GtkBox, query: &GridQuery) {
+    let entry = |name: &str, text: &str| {
+        let entry: gtk4::Entry = utils::find_widget(panel, name);
+        entry.set_text(text);
+    };
+    entry("exchanges", &query.exchanges.join(","));
+    entry("currencies", &query.currencies.join(","));
+    entry("pairlists", &query.pairlists.join(";"));
+    entry("start_date", &query.start_date);
+    entry("end_date", query.end_date.as_deref().unwrap_or_default());
+    entry("custom_ranges", &query.custom_ranges.join(","));
+
+    let months: gtk4::SpinButton = utils::find_widget(panel, "months");
+    months.set_value(query.months as f64);
+    let server_aggregation: gtk4::Switch = utils::find_widget(panel, "server_aggregation");
+    server_aggregation.set_active(query.aggregation == AggregationMode::Server);
+    let compounded: gtk4::Switch = utils::find_widget(panel, "compounded");
+    compounded.set_active(query.accumulation == AccumulationMode::Compounded);
+
+    let bucket: ComboRow = utils::find_widget(panel, "bucket");
+    if let Some(index) = BUCKETS.iter().position(|(b, _)| *b == query.bucket) {
+        bucket.set_selected(index as u32);
+    }
+    let coverage_policy: ComboRow = utils::find_widget(panel, "coverage_policy");
+    if let Some(index) = COVERAGE_POLICIES
+        .iter()
+        .position(|(policy, _)| *policy == query.coverage_policy)
+    {
+        coverage_policy.set_selected(index as u32);
+    }
+}
+
+/// Valores no vacíos de `text` separados por `separator`, o `fallback` si no hay.
+fn split_or(text: &str, separator: char, fallback: &str) -> Vec<String> {
+    let values: Vec<String> = text
+        .split(separator)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_uppercase())
+        .collect();
+    if values.is_empty() {
+        vec![fallback.to_string()]
+    } else {
+        values
+    }
+}
+
+/// `robustness` es la robustez entre mercados por `markets::config_key`.
+pub fn populate_store_batch(
+    store: &ListStore,
+    batch: &[StrategyGridRow],
+    robustness: &HashMap<String, f64>,
+    months: &[String],
+) {
     for r in batch {
-        store.insert_with_values(
-            None,
-            &[
-                (0, &r.strategy.to_value()),
-                (1, &r.timeframe.to_value()),
-                (2, &r.minimal_roi.to_value()),
-                (3, &r.stoploss.parse::<f64>().unwrap_or(-99.0).to_value()),
-                (4, &r.max_open_trades.to_value()),
-                (5, &r.trailing_stop.to_value()),
-                (6, &r.trailing_stop_positive.unwrap_or(0.0).to_value()),
-                (
-                    7,
-                    &r.trailing_stop_positive_offset.unwrap_or(0.0).to_value(),
-                ),
-                (8, &r.trailing_only_offset_is_reached.to_value()),
-                (9, &r.entry_price.to_value()),
-                (10, &r.exit_price.to_value()),
-                (11, &r.check_depth_of_market_enable.to_value()),
-                (12, &r.total_profit.to_value()),
-                (13, &r.total_trades.to_value()),
-                (14, &r.wins.to_value()),
-                (15, &r.win_rate.to_value()),
-                (16, &r.win_time.to_value()),
-                (17, &r.drawdown_perc.to_value()),
-                (18, &(r.rejected_signals as i32).to_value()),
-                (19, &(r.neg_months as i32).to_value()),
-                (20, &r.avg_monthly_profit.to_value()),
-                (21, &r.std_monthly_profit.to_value()),
-                (22, &r.max_profit_month.to_value()),
-                (23, &r.min_profit_month.to_value()),
-                (24, &r.avg_trade_profit.to_value()),
-                (25, &r.losses.to_value()),
-                (26, &r.loss_rate.to_value()),
-                (27, &r.expectancy.to_value()),
-                (28, &r.profit_factor.to_value()),
-                (29, &r.strategy.to_lowercase().to_value()),
-                (30, &true.to_value()),
-            ],
-        );
+        let mut values: Vec<(u32, glib::Value)> = vec![
+            (0, r.strategy.to_value()),
+            (1, r.timeframe.to_value()),
+            (2, r.minimal_roi.to_value()),
+            // NaN marca un stoploss o ROI no interpretable
+            (3, r.stoploss_value.unwrap_or(f64::NAN).to_value()),
+            (4, r.max_open_trades.to_value()),
+            (5, r.trailing_stop.to_value()),
+            (6, r.trailing_stop_positive.unwrap_or(0.0).to_value()),
+            (7, r.trailing_stop_positive_offset.unwrap_or(0.0).to_value()),
+            (8, r.trailing_only_offset_is_reached.to_value()),
+            (9, r.entry_price.to_value()),
+            (10, r.exit_price.to_value()),
+            (11, r.check_depth_of_market_enable.to_value()),
+            (12, r.total_profit.to_value()),
+            (13, r.total_trades.to_value()),
+            (14, r.wins.to_value()),
+            (15, r.win_rate.to_value()),
+            (16, r.win_time.to_value()),
+            (17, r.drawdown_perc.to_value()),
+            (18, (r.rejected_signals as i32).to_value()),
+            (19, (r.neg_months as i32).to_value()),
+            (20, r.avg_monthly_profit.to_value()),
+            (21, r.std_monthly_profit.to_value()),
+            (22, r.max_profit_month.to_value()),
+            (23, r.min_profit_month.to_value()),
+            (24, r.avg_trade_profit.to_value()),
+            (25, r.losses.to_value()),
+            (26, r.loss_rate.to_value()),
+            (27, r.expectancy.to_value()),
+            (28, r.profit_factor.to_value()),
+            (29, r.strategy.to_lowercase().to_value()),
+            (30, true.to_value()),
+            (31, r.sharpe.to_value()),
+            (32, r.sortino.to_value()),
+            (33, r.calmar.to_value()),
+            (34, r.ulcer_index.to_value()),
+            (35, r.recovery_factor.to_value()),
+            (36, (r.max_losing_streak as i32).to_value()),
+            (37, r.equity_return.to_value()),
+            (38, r.max_drawdown.to_value()),
+            (39, (r.max_drawdown_months as i32).to_value()),
+            (40, (r.coverage.present as i32).to_value()),
+            (41, (r.coverage.expected as i32).to_value()),
+            (42, r.coverage.gaps.join(", ").to_value()),
+            (43, market_label(r).to_value()),
+            (44, robustness.get(&config_key(r)).copied().unwrap_or(0.0).to_value()),
+            (45, r.initial_roi().unwrap_or(f64::NAN).to_value()),
+            (46, r.score.unwrap_or(f64::NAN).to_value()),
+        ];
+        // NaN en los periodos sin resultado
+        values.extend(months.iter().enumerate().map(|(index, month)| {
+            let profit = r.monthly.get(month).copied().unwrap_or(f64::NAN);
+            (table_view::MONTH_COLUMN + index as u32, profit.to_value())
+        }));
+        let columns: Vec<(u32, &dyn ToValue)> = values
+            .iter()
+            .map(|(column, value)| (*column, value as &dyn ToValue))
+            .collect();
+        store.insert_with_values(None, &columns);
     }
 }
 
@@ -160,7 +283,9 @@ pub fn enable_export_buttons(toolbar: &HeaderBar, enable: bool) {
     let export_selection: Button = utils::find_widget(toolbar, "export_selection");
     let export_all: Button = utils::find_widget(toolbar, "export_all");
     let clear: Button = utils::find_widget(toolbar, "clear");
+    let charts: Button = utils::find_widget(toolbar, "charts");
     export_selection.set_sensitive(enable);
     export_all.set_sensitive(enable);
+    charts.set_sensitive(enable);
     clear.set_sensitive(enable);
 }
@@ -1,14 +1,24 @@
 // src/gui/state.rs
 
 use gtk4::{TreeModelFilter, ListStore};
-use crate::backtest::model::StrategyGridRow;
+use crate::backtest::model::{GridQuery, StrategyGridRow};
+use crate::backtest::planner::FetchReport;
+use crate::presets::SortOrder;
 
 #[derive(Clone)]
 pub struct AppState {
     pub is_loading: bool,
     pub results: Vec<StrategyGridRow>,
+    /// Consulta con la que se obtuvieron `results`; la usa el detalle.
+    pub query: Option<GridQuery>,
     pub store: ListStore,
     pub filter_model: TreeModelFilter,
+    /// Periodos de las columnas mensuales de `store`, desde `MONTH_COLUMN`.
+    pub months: Vec<String>,
+    /// Tiempos de la última lectura por tramos; se muestran con el resultado.
+    pub fetch_report: Option<FetchReport>,
+    /// Orden inicial de los resultados (el del último preset cargado).
+    pub sort: SortOrder,
 }
 
 impl AppState {
@@ -16,8 +26,12 @@ impl AppState {
         Self {
             is_loading: false,
             results: Vec::new(),
+            query: None,
             store,
             filter_model,
+            months: Vec::new(),
+            fetch_report: None,
+            sort: SortOrder::default(),
         }
     }
 
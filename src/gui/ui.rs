@@ -1,5 +1,8 @@
 // src/gui/ui/mod.rs
 
+pub mod charts;
+pub mod charts_window;
+pub mod detail_window;
 pub mod left_panel;
 pub mod right_panel;
 pub mod table_view;
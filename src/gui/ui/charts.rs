@@ -0,0 +1,296 @@
+// src/gui/ui/charts.rs
+//
+// Dibujo con cairo del mapa de calor y de las curvas de capital. Las mismas
+// funciones pintan las DrawingArea y los ficheros PNG/SVG exportados.
+
+use std::fs::File;
+use std::path::Path;
+
+use gtk4::cairo::{self, Context, FontSlant, FontWeight, Format, ImageSurface, SvgSurface};
+
+use crate::backtest::charts::{month_label, EquitySeries, Heatmap};
+
+const FONT: &str = "Sans";
+const MARGIN: f64 = 12.0;
+
+// Mapa de calor: etiquetas a la izquierda, meses arriba y escala debajo
+const LABEL_WIDTH: f64 = 340.0;
+const CELL_WIDTH: f64 = 60.0;
+const CELL_HEIGHT: f64 = 22.0;
+const HEADER_HEIGHT: f64 = 26.0;
+const SCALE_HEIGHT: f64 = 40.0;
+
+// Curvas: márgenes del área del gráfico y ancho de la leyenda
+const AXIS_LEFT: f64 = 64.0;
+const AXIS_BOTTOM: f64 = 40.0;
+const LEGEND_WIDTH: f64 = 280.0;
+
+/// Paleta de GNOME para las curvas, en el orden en que se asigna.
+const PALETTE: [(f64, f64, f64); 8] = [
+    (0.21, 0.52, 0.89),
+    (0.20, 0.82, 0.48),
+    (1.00, 0.47, 0.00),
+    (0.57, 0.25, 0.67),
+    (0.88, 0.11, 0.14),
+    (0.96, 0.83, 0.18),
+    (0.60, 0.42, 0.27),
+    (0.13, 0.56, 0.64),
+];
+
+/// Tamaño completo del mapa de calor, en píxeles.
+pub fn heatmap_size(heatmap: &Heatmap) -> (i32, i32) {
+    let width = LABEL_WIDTH + CELL_WIDTH * heatmap.months.len() as f64 + 2.0 * MARGIN;
+    let height =
+        HEADER_HEIGHT + CELL_HEIGHT * heatmap.rows.len() as f64 + SCALE_HEIGHT + 2.0 * MARGIN;
+    (width.ceil() as i32, height.ceil() as i32)
+}
+
+/// Fila y mes de la celda bajo el punto `(x, y)`.
+pub fn heatmap_cell_at(heatmap: &Heatmap, x: f64, y: f64) -> Option<(usize, usize)> {
+    let column = (x - MARGIN - LABEL_WIDTH) / CELL_WIDTH;
+    let row = (y - MARGIN - HEADER_HEIGHT) / CELL_HEIGHT;
+    if column < 0.0 || row < 0.0 {
+        return None;
+    }
+    let (row, column) = (row as usize, column as usize);
+    (row < heatmap.rows.len() && column < heatmap.months.len()).then_some((row, column))
+}
+
+pub fn draw_heatmap(cr: &Context, heatmap: &Heatmap) -> Result<(), cairo::Error> {
+    let (width, height) = heatmap_size(heatmap);
+    background(cr, width as f64, height as f64)?;
+    let max_abs = heatmap.max_abs();
+    let grid_x = MARGIN + LABEL_WIDTH;
+    let grid_y = MARGIN + HEADER_HEIGHT;
+
+    cr.select_font_face(FONT, FontSlant::Normal, FontWeight::Bold);
+    cr.set_font_size(11.0);
+    cr.set_source_rgb(0.2, 0.2, 0.2);
+    for (column, month) in heatmap.months.iter().enumerate() {
+        let x = grid_x + CELL_WIDTH * (column as f64 + 0.5);
+        text(cr, &month_label(month), x, grid_y - 8.0, Align::Center)?;
+    }
+
+    cr.set_font_size(10.0);
+    for (index, row) in heatmap.rows.iter().enumerate() {
+        let y = grid_y + CELL_HEIGHT * index as f64;
+        cr.select_font_face(FONT, FontSlant::Normal, FontWeight::Normal);
+        cr.set_source_rgb(0.2, 0.2, 0.2);
+        let label = truncate(&row.label, 52);
+        text(cr, &label, grid_x - 8.0, y + CELL_HEIGHT * 0.7, Align::Right)?;
+
+        for (column, profit) in row.profits.iter().enumerate() {
+            let x = grid_x + CELL_WIDTH * column as f64;
+            let (r, g, b) = profit_color(*profit, max_abs);
+            cr.set_source_rgb(r, g, b);
+            cr.rectangle(x + 1.0, y + 1.0, CELL_WIDTH - 2.0, CELL_HEIGHT - 2.0);
+            cr.fill()?;
+
+            if let Some(profit) = profit {
+                let strong = max_abs > 0.0 && profit.abs() / max_abs > 0.6;
+                let shade = if strong { 1.0 } else { 0.1 };
+                cr.set_source_rgb(shade, shade, shade);
+                let value = format!("{:.1}%", profit * 100.0);
+                text(cr, &value, x + CELL_WIDTH / 2.0, y + CELL_HEIGHT * 0.7, Align::Center)?;
+            }
+        }
+    }
+
+    // Escala de color de -máx a +máx
+    let scale_y = grid_y + CELL_HEIGHT * heatmap.rows.len() as f64 + 12.0;
+    let scale_width = (CELL_WIDTH * heatmap.months.len() as f64).clamp(120.0, 360.0);
+    let steps = 60;
+    for step in 0..steps {
+        let value = max_abs * (2.0 * step as f64 / (steps - 1) as f64 - 1.0);
+        let (r, g, b) = profit_color(Some(value), max_abs);
+        cr.set_source_rgb(r, g, b);
+        let step_width = scale_width / steps as f64;
+        cr.rectangle(grid_x + step_width * step as f64, scale_y, step_width + 0.5, 10.0);
+        cr.fill()?;
+    }
+    cr.set_source_rgb(0.2, 0.2, 0.2);
+    let label_y = scale_y + 24.0;
+    text(cr, &format!("{:.1}%", -max_abs * 100.0), grid_x, label_y, Align::Left)?;
+    text(cr, "0%", grid_x + scale_width / 2.0, label_y, Align::Center)?;
+    let max_label = format!("+{:.1}%", max_abs * 100.0);
+    text(cr, &max_label, grid_x + scale_width, label_y, Align::Right)?;
+    text(cr, "sin datos", grid_x + scale_width + 44.0, scale_y + 9.0, Align::Left)?;
+    let (r, g, b) = profit_color(None, max_abs);
+    cr.set_source_rgb(r, g, b);
+    cr.rectangle(grid_x + scale_width + 20.0, scale_y, 18.0, 10.0);
+    cr.fill()
+}
+
+/// Mes más cercano al punto `x` del gráfico de curvas de ancho `width`.
+pub fn equity_month_at(months: usize, width: f64, x: f64) -> Option<usize> {
+    let (left, plot_width) = plot_span(width);
+    if months == 0 || x < left - 8.0 || x > left + plot_width + 8.0 {
+        return None;
+    }
+    let step = plot_width / (months.max(2) - 1) as f64;
+    let month = ((x - left) / step).round().max(0.0) as usize;
+    Some(month.min(months - 1))
+}
+
+/// Curvas de capital en `width × height`; `hover` resalta un mes.
+pub fn draw_equity(
+    cr: &Context,
+    months: &[String],
+    series: &[EquitySeries],
+    width: f64,
+    height: f64,
+    hover: Option<usize>,
+) -> Result<(), cairo::Error> {
+    background(cr, width, height)?;
+    let (left, plot_width) = plot_span(width);
+    let top = MARGIN;
+    let plot_height = (height - top - AXIS_BOTTOM).max(1.0);
+    if months.is_empty() || series.is_empty() {
+        return Ok(());
+    }
+
+    // Rango vertical con el capital inicial siempre a la vista
+    let (mut low, mut high) = series
+        .iter()
+        .flat_map(|s| s.points.iter())
+        .fold((1.0_f64, 1.0_f64), |(low, high), &v| (low.min(v), high.max(v)));
+    let padding = ((high - low) * 0.05).max(0.01);
+    low -= padding;
+    high += padding;
+    let step = plot_width / (months.len().max(2) - 1) as f64;
+    let x_of = |month: usize| left + step * month as f64;
+    let y_of = |equity: f64| top + plot_height * (high - equity) / (high - low);
+
+    // Rejilla y ejes
+    cr.select_font_face(FONT, FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(10.0);
+    cr.set_line_width(1.0);
+    for tick in 0..=5 {
+        let equity = low + (high - low) * tick as f64 / 5.0;
+        let y = y_of(equity);
+        cr.set_source_rgb(0.9, 0.9, 0.9);
+        cr.move_to(left, y);
+        cr.line_to(left + plot_width, y);
+        cr.stroke()?;
+        cr.set_source_rgb(0.3, 0.3, 0.3);
+        let label = format!("{:+.0}%", (equity - 1.0) * 100.0);
+        text(cr, &label, left - 8.0, y + 4.0, Align::Right)?;
+    }
+    let every = (months.len() as f64 / (plot_width / 70.0).max(1.0)).ceil().max(1.0) as usize;
+    for (index, month) in months.iter().enumerate().step_by(every) {
+        text(cr, &month_label(month), x_of(index), top + plot_height + 18.0, Align::Center)?;
+    }
+    cr.set_source_rgb(0.5, 0.5, 0.5);
+    cr.set_dash(&[4.0, 4.0], 0.0);
+    cr.move_to(left, y_of(1.0));
+    cr.line_to(left + plot_width, y_of(1.0));
+    cr.stroke()?;
+    cr.set_dash(&[], 0.0);
+
+    if let Some(month) = hover {
+        cr.set_source_rgba(0.2, 0.2, 0.2, 0.4);
+        cr.move_to(x_of(month), top);
+        cr.line_to(x_of(month), top + plot_height);
+        cr.stroke()?;
+    }
+
+    // Curvas y leyenda
+    let legend_x = left + plot_width + 24.0;
+    for (index, serie) in series.iter().enumerate() {
+        let (r, g, b) = PALETTE[index % PALETTE.len()];
+        cr.set_source_rgb(r, g, b);
+        cr.set_line_width(2.0);
+        for (month, equity) in serie.points.iter().enumerate() {
+            if month == 0 {
+                cr.move_to(x_of(month), y_of(*equity));
+            } else {
+                cr.line_to(x_of(month), y_of(*equity));
+            }
+        }
+        cr.stroke()?;
+        if let Some((month, equity)) = hover.and_then(|m| Some((m, serie.points.get(m)?))) {
+            cr.arc(x_of(month), y_of(*equity), 4.0, 0.0, std::f64::consts::TAU);
+            cr.fill()?;
+        }
+
+        let y = top + 8.0 + 18.0 * index as f64;
+        cr.rectangle(legend_x, y - 8.0, 12.0, 12.0);
+        cr.fill()?;
+        cr.set_source_rgb(0.2, 0.2, 0.2);
+        text(cr, &truncate(&serie.label, 40), legend_x + 18.0, y + 2.0, Align::Left)?;
+    }
+    Ok(())
+}
+
+/// Pinta `draw` en `path`, PNG o SVG según la extensión, a `width × height`.
+pub fn export(
+    path: &Path,
+    width: i32,
+    height: i32,
+    draw: impl Fn(&Context) -> Result<(), cairo::Error>,
+) -> Result<(), String> {
+    let svg = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    if svg {
+        let surface =
+            SvgSurface::new(width as f64, height as f64, Some(path)).map_err(|e| e.to_string())?;
+        draw(&Context::new(&surface).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+        surface.finish();
+        Ok(())
+    } else {
+        let surface =
+            ImageSurface::create(Format::ARgb32, width, height).map_err(|e| e.to_string())?;
+        draw(&Context::new(&surface).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+        let mut file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        surface.write_to_png(&mut file).map_err(|e| e.to_string())
+    }
+}
+
+/// Inicio horizontal y ancho del área de las curvas.
+fn plot_span(width: f64) -> (f64, f64) {
+    let left = MARGIN + AXIS_LEFT;
+    (left, (width - left - LEGEND_WIDTH - MARGIN).max(1.0))
+}
+
+/// Blanco a verde para los profits positivos, blanco a rojo para los
+/// negativos y gris para los meses sin datos.
+fn profit_color(profit: Option<f64>, max_abs: f64) -> (f64, f64, f64) {
+    let Some(profit) = profit else {
+        return (0.82, 0.82, 0.82);
+    };
+    let t = if max_abs > 0.0 { (profit.abs() / max_abs).min(1.0) } else { 0.0 };
+    let (r, g, b) = if profit >= 0.0 { (0.15, 0.64, 0.41) } else { (0.88, 0.11, 0.14) };
+    (1.0 + (r - 1.0) * t, 1.0 + (g - 1.0) * t, 1.0 + (b - 1.0) * t)
+}
+
+fn background(cr: &Context, width: f64, height: f64) -> Result<(), cairo::Error> {
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.rectangle(0.0, 0.0, width, height);
+    cr.fill()
+}
+
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+fn text(cr: &Context, value: &str, x: f64, y: f64, align: Align) -> Result<(), cairo::Error> {
+    let width = cr.text_extents(value)?.x_advance();
+    let x = match align {
+        Align::Left => x,
+        Align::Center => x - width / 2.0,
+        Align::Right => x - width,
+    };
+    cr.move_to(x, y);
+    cr.show_text(value)
+}
+
+fn truncate(value: &str, max_chars: usize) -> String {
+    if value.chars().count() <= max_chars {
+        value.to_string()
+    } else {
+        let mut truncated: String = value.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+}
@@ -0,0 +1,215 @@
+// src/gui/ui/charts_window.rs
+
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use gtk4::prelude::*;
+use gtk4::{
+    Align, Box as GtkBox, Button, DrawingArea, EventControllerMotion, Label, Orientation,
+    PolicyType, ScrolledWindow,
+};
+use libadwaita as adw;
+
+use super::charts;
+use crate::backtest::charts::{month_label, EquitySeries, Heatmap};
+
+/// Ventana con el mapa de calor de las filas visibles y las curvas de
+/// capital de las seleccionadas; `summary` explica qué filas se dibujan.
+pub fn create(
+    parent: Option<&gtk4::Window>,
+    heatmap: Heatmap,
+    months: Vec<String>,
+    series: Vec<EquitySeries>,
+    summary: &str,
+) -> adw::Window {
+    let heatmap = Rc::new(heatmap);
+    let equity = Rc::new((months, series));
+    let heatmap_page = heatmap_page(&heatmap);
+    let equity_area = equity_page(&equity);
+
+    let stack = adw::ViewStack::new();
+    stack.add_titled_with_icon(
+        &heatmap_page,
+        Some("heatmap"),
+        "Mapa de calor",
+        "view-grid-symbolic",
+    );
+    stack.add_titled_with_icon(
+        &equity_area,
+        Some("equity"),
+        "Curva de capital",
+        "utilities-system-monitor-symbolic",
+    );
+
+    let switcher = adw::ViewSwitcher::builder()
+        .stack(&stack)
+        .policy(adw::ViewSwitcherPolicy::Wide)
+        .build();
+    let header = adw::HeaderBar::new();
+    header.set_title_widget(Some(&switcher));
+
+    let toast_overlay = adw::ToastOverlay::new();
+    for extension in ["svg", "png"] {
+        let button = Button::builder()
+            .label(extension.to_uppercase())
+            .tooltip_text(format!("Exportar el gráfico a {}", extension.to_uppercase()))
+            .build();
+        let stack = stack.clone();
+        let heatmap = heatmap.clone();
+        let equity = equity.clone();
+        let equity_area = equity_area.clone();
+        let toast_overlay = toast_overlay.clone();
+        button.connect_clicked(move |_| {
+            let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+            let result = if stack.visible_child_name().as_deref() == Some("equity") {
+                let path = PathBuf::from(format!("curva_capital_{}.{}", timestamp, extension));
+                // Al tamaño de la ventana, con un mínimo legible
+                let width = equity_area.width().max(800);
+                let height = equity_area.height().max(500);
+                let (months, series) = &*equity;
+                charts::export(&path, width, height, |cr| {
+                    charts::draw_equity(cr, months, series, width as f64, height as f64, None)
+                })
+                .map(|_| path)
+            } else {
+                let path = PathBuf::from(format!("mapa_calor_{}.{}", timestamp, extension));
+                let (width, height) = charts::heatmap_size(&heatmap);
+                charts::export(&path, width, height, |cr| charts::draw_heatmap(cr, &heatmap))
+                    .map(|_| path)
+            };
+            let message = match result {
+                Ok(path) => format!("💾 Gráfico exportado a: {}", path.display()),
+                Err(e) => format!("❌ Error al exportar el gráfico: {}", e),
+            };
+            println!("{}", message);
+            toast_overlay.add_toast(adw::Toast::new(&message));
+        });
+        header.pack_end(&button);
+    }
+
+    let summary = Label::new(Some(summary));
+    summary.set_halign(Align::Start);
+    summary.set_margin_start(12);
+    summary.set_margin_top(6);
+    summary.set_margin_bottom(6);
+    summary.add_css_class("dim-label");
+
+    let content = GtkBox::new(Orientation::Vertical, 0);
+    content.append(&summary);
+    content.append(&stack);
+    toast_overlay.set_child(Some(&content));
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&toast_overlay));
+
+    let window = adw::Window::builder()
+        .title("Gráficos")
+        .default_width(1100)
+        .default_height(700)
+        .content(&toolbar_view)
+        .build();
+    window.set_transient_for(parent);
+    window
+}
+
+/// Mapa de calor a tamaño completo dentro de un scroll; el tooltip da la
+/// configuración, el mes y su profit.
+fn heatmap_page(heatmap: &Rc<Heatmap>) -> ScrolledWindow {
+    let (width, height) = charts::heatmap_size(heatmap);
+    let area = DrawingArea::builder()
+        .content_width(width)
+        .content_height(height)
+        .has_tooltip(true)
+        .build();
+
+    let data = heatmap.clone();
+    area.set_draw_func(move |_, cr, _, _| {
+        if let Err(e) = charts::draw_heatmap(cr, &data) {
+            eprintln!("⚠️ Error al dibujar el mapa de calor: {}", e);
+        }
+    });
+
+    let data = heatmap.clone();
+    area.connect_query_tooltip(move |_, x, y, _, tooltip| {
+        let Some((row, column)) = charts::heatmap_cell_at(&data, x as f64, y as f64) else {
+            return false;
+        };
+        let profit = data.rows[row].profits[column]
+            .map(|profit| format!("{:+.2}%", profit * 100.0))
+            .unwrap_or_else(|| "sin datos".to_string());
+        tooltip.set_text(Some(&format!(
+            "{}\n{}: {}",
+            data.rows[row].label,
+            month_label(&data.months[column]),
+            profit
+        )));
+        true
+    });
+
+    ScrolledWindow::builder()
+        .child(&area)
+        .vexpand(true)
+        .hscrollbar_policy(PolicyType::Automatic)
+        .vscrollbar_policy(PolicyType::Automatic)
+        .build()
+}
+
+/// Curvas de capital ajustadas a la ventana: al pasar el ratón se marca el
+/// mes más cercano y el tooltip da el capital de cada curva.
+fn equity_page(equity: &Rc<(Vec<String>, Vec<EquitySeries>)>) -> DrawingArea {
+    let hover: Rc<Cell<Option<usize>>> = Rc::new(Cell::new(None));
+    let area = DrawingArea::builder()
+        .hexpand(true)
+        .vexpand(true)
+        .has_tooltip(true)
+        .build();
+
+    let data = equity.clone();
+    let hovered = hover.clone();
+    area.set_draw_func(move |_, cr, width, height| {
+        let (months, series) = &*data;
+        let result =
+            charts::draw_equity(cr, months, series, width as f64, height as f64, hovered.get());
+        if let Err(e) = result {
+            eprintln!("⚠️ Error al dibujar las curvas de capital: {}", e);
+        }
+    });
+
+    let motion = EventControllerMotion::new();
+    let data = equity.clone();
+    let hovered = hover.clone();
+    let target = area.clone();
+    motion.connect_motion(move |_, x, _| {
+        let month = charts::equity_month_at(data.0.len(), target.width() as f64, x);
+        if month != hovered.get() {
+            hovered.set(month);
+            target.queue_draw();
+        }
+    });
+    let hovered = hover.clone();
+    let target = area.clone();
+    motion.connect_leave(move |_| {
+        hovered.set(None);
+        target.queue_draw();
+    });
+    area.add_controller(motion);
+
+    let data = equity.clone();
+    area.connect_query_tooltip(move |area, x, _, _, tooltip| {
+        let (months, series) = &*data;
+        let Some(month) = charts::equity_month_at(months.len(), area.width() as f64, x as f64)
+        else {
+            return false;
+        };
+        let mut lines = vec![month_label(&months[month])];
+        lines.extend(series.iter().map(|serie| {
+            format!("{:+.2}% · {}", (serie.points[month] - 1.0) * 100.0, serie.label)
+        }));
+        tooltip.set_text(Some(&lines.join("\n")));
+        true
+    });
+
+    area
+}
@@ -0,0 +1,290 @@
+// src/gui/ui/detail_window.rs
+
+use chrono::{DateTime, Utc};
+use glib::Type;
+use gtk4::prelude::*;
+use gtk4::{
+    Align, Box as GtkBox, CellRendererText, Label, ListStore, Orientation, PolicyType,
+    ScrolledWindow, TreeModelSort, TreeView, TreeViewColumn,
+};
+use libadwaita as adw;
+
+use crate::backtest::drilldown::StrategyDetail;
+use crate::backtest::markets::market_label;
+use crate::backtest::model::StrategyGridRow;
+
+/// Ventana con las filas mensuales, los pares y los trades de una fila
+pub fn create(
+    parent: Option<&gtk4::Window>,
+    row: &StrategyGridRow,
+    detail: &StrategyDetail,
+) -> adw::Window {
+    let stack = adw::ViewStack::new();
+    stack.add_titled_with_icon(
+        &months_page(detail),
+        Some("months"),
+        "Periodos",
+        "x-office-calendar-symbolic",
+    );
+    stack.add_titled_with_icon(
+        &pairs_page(detail),
+        Some("pairs"),
+        "Pares",
+        "view-list-symbolic",
+    );
+    stack.add_titled_with_icon(
+        &trades_page(detail),
+        Some("trades"),
+        "Trades",
+        "view-list-bullet-symbolic",
+    );
+
+    let switcher = adw::ViewSwitcher::builder()
+        .stack(&stack)
+        .policy(adw::ViewSwitcherPolicy::Wide)
+        .build();
+    let header = adw::HeaderBar::new();
+    header.set_title_widget(Some(&switcher));
+
+    let config = Label::new(Some(&format!(
+        "ROI {} · SL {} · MOT {} · Trailing {} ({}/{}) · Precios {}/{}",
+        row.minimal_roi,
+        row.stoploss,
+        row.max_open_trades,
+        if row.trailing_stop { "✅" } else { "❌" },
+        row.trailing_stop_positive.unwrap_or(0.0),
+        row.trailing_stop_positive_offset.unwrap_or(0.0),
+        row.entry_price,
+        row.exit_price
+    )));
+    config.set_halign(Align::Start);
+    config.set_margin_start(12);
+    config.set_margin_top(6);
+    config.set_margin_bottom(6);
+    config.set_selectable(true);
+    config.add_css_class("dim-label");
+
+    let content = GtkBox::new(Orientation::Vertical, 0);
+    content.append(&config);
+    content.append(&stack);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&content));
+
+    let window = adw::Window::builder()
+        .title(format!(
+            "{} ({}) · {}",
+            row.strategy,
+            row.timeframe,
+            market_label(row)
+        ))
+        .default_width(900)
+        .default_height(600)
+        .content(&toolbar_view)
+        .build();
+    window.set_transient_for(parent);
+    window
+}
+
+fn months_page(detail: &StrategyDetail) -> GtkBox {
+    let page = GtkBox::new(Orientation::Vertical, 0);
+    let gaps = detail.gaps();
+    if !gaps.is_empty() {
+        let label = Label::new(Some(&format!("🕳 Sin datos: {}", gaps.join(", "))));
+        label.set_halign(Align::Start);
+        label.set_margin_start(12);
+        label.set_margin_bottom(6);
+        label.add_css_class("warning");
+        page.append(&label);
+    }
+
+    let rows = detail
+        .months
+        .iter()
+        .map(|month| {
+            let win_rate = if month.total_trades > 0 {
+                month.wins as f64 / month.total_trades as f64
+            } else {
+                0.0
+            };
+            vec![
+                month.timerange.to_value(),
+                month.profit_total.to_value(),
+                month.total_trades.to_value(),
+                month.wins.to_value(),
+                win_rate.to_value(),
+                month.max_drawdown.unwrap_or(f64::NAN).to_value(),
+                month.rejected_signals.unwrap_or(0).to_value(),
+            ]
+        })
+        .collect();
+    page.append(&table(
+        &[
+            ("📅 Periodo", Type::STRING),
+            ("💰 Profit (%)", Type::F64),
+            ("📊 Trades", Type::I32),
+            ("✅ Wins", Type::I32),
+            ("🎯 Win Rate (%)", Type::F64),
+            ("📉 Drawdown (%)", Type::F64),
+            ("❌ Rejected", Type::I32),
+        ],
+        rows,
+    ));
+    page
+}
+
+fn pairs_page(detail: &StrategyDetail) -> gtk4::Widget {
+    if detail.pairs.is_none() {
+        return missing_page("No hay resultados por par", "resultados por par");
+    }
+    let rows = detail
+        .pair_totals()
+        .into_iter()
+        .map(|pair| {
+            vec![
+                pair.pair.to_value(),
+                (pair.periods as i32).to_value(),
+                pair.trades.to_value(),
+                pair.wins.to_value(),
+                pair.draws.to_value(),
+                pair.losses.to_value(),
+                pair.profit_total.to_value(),
+                pair.profit_total_abs.to_value(),
+            ]
+        })
+        .collect();
+    table(
+        &[
+            ("💱 Par", Type::STRING),
+            ("📅 Periodos", Type::I32),
+            ("📊 Trades", Type::I32),
+            ("✅ Wins", Type::I32),
+            ("➖ Draws", Type::I32),
+            ("❌ Losses", Type::I32),
+            ("💰 Profit (%)", Type::F64),
+            ("💵 Profit", Type::F64),
+        ],
+        rows,
+    )
+    .upcast()
+}
+
+fn trades_page(detail: &StrategyDetail) -> gtk4::Widget {
+    let Some(trades) = &detail.trades else {
+        return missing_page("No hay trades", "trades");
+    };
+    let date = |date: Option<DateTime<Utc>>| {
+        date.map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    };
+    let rows = trades
+        .iter()
+        .map(|trade| {
+            vec![
+                date(trade.open_date).to_value(),
+                date(trade.close_date).to_value(),
+                trade.pair.to_value(),
+                (if trade.is_short { "short" } else { "long" }).to_value(),
+                trade.profit_ratio.to_value(),
+                trade.profit_abs.to_value(),
+                trade.trade_duration.unwrap_or(0).to_value(),
+                trade.exit_reason.clone().unwrap_or_default().to_value(),
+                trade.enter_tag.clone().unwrap_or_default().to_value(),
+            ]
+        })
+        .collect();
+    table(
+        &[
+            ("🕐 Apertura", Type::STRING),
+            ("🕔 Cierre", Type::STRING),
+            ("💱 Par", Type::STRING),
+            ("↕ Lado", Type::STRING),
+            ("💰 Profit (%)", Type::F64),
+            ("💵 Profit", Type::F64),
+            ("⏳ Minutos", Type::I32),
+            ("🚪 Salida", Type::STRING),
+            ("🏷 Tag", Type::STRING),
+        ],
+        rows,
+    )
+    .upcast()
+}
+
+/// Página vacía para las fuentes que no guardan `what`.
+fn missing_page(title: &str, what: &str) -> gtk4::Widget {
+    adw::StatusPage::builder()
+        .icon_name("dialog-information-symbolic")
+        .title(title)
+        .description(format!(
+            "La fuente no guarda {}: impórtelos con `cli import` en una base de datos Postgres.",
+            what
+        ))
+        .build()
+        .upcast()
+}
+
+/// Tabla ordenable de solo lectura. Las columnas F64 con "%" en el título
+/// son ratios y se muestran en %; las de profit se colorean por su signo.
+fn table(columns: &[(&str, Type)], rows: Vec<Vec<glib::Value>>) -> ScrolledWindow {
+    let types: Vec<Type> = columns.iter().map(|(_, column_type)| *column_type).collect();
+    let store = ListStore::new(&types);
+    for values in &rows {
+        let values: Vec<(u32, &dyn ToValue)> = values
+            .iter()
+            .enumerate()
+            .map(|(column, value)| (column as u32, value as &dyn ToValue))
+            .collect();
+        store.insert_with_values(None, &values);
+    }
+
+    let tree_view = TreeView::with_model(&TreeModelSort::with_model(&store));
+    tree_view.add_css_class("rich-list");
+    for (index, (title, column_type)) in columns.iter().enumerate() {
+        let column_id = index as i32;
+        let col = TreeViewColumn::new();
+        col.set_title(title);
+        col.set_resizable(true);
+        col.set_sort_column_id(column_id);
+
+        let cell = CellRendererText::new();
+        cell.set_padding(8, 4);
+        col.pack_start(&cell, true);
+        if *column_type == Type::F64 {
+            let percent = title.contains('%');
+            let colored = title.contains("Profit");
+            cell.set_property("xalign", 1.0_f32);
+            col.set_cell_data_func(&cell, move |_col, cell, model, iter| {
+                let Ok(value) = model.get_value(iter, column_id).get::<f64>() else {
+                    return;
+                };
+                let text = if value.is_nan() {
+                    "—".to_string()
+                } else if percent {
+                    format!("{:.2}%", value * 100.0)
+                } else {
+                    format!("{:.2}", value)
+                };
+                cell.set_property("text", &text);
+                if colored {
+                    let color = if value > 0.0 { "#2ec27e" } else { "#e01b24" };
+                    cell.set_property("foreground", color);
+                    cell.set_property("weight", 600);
+                }
+            });
+        } else {
+            if *column_type == Type::I32 {
+                cell.set_property("xalign", 1.0_f32);
+            }
+            col.add_attribute(&cell, "text", column_id);
+        }
+        tree_view.append_column(&col);
+    }
+
+    ScrolledWindow::builder()
+        .child(&tree_view)
+        .vexpand(true)
+        .hscrollbar_policy(PolicyType::Automatic)
+        .vscrollbar_policy(PolicyType::Automatic)
+        .build()
+}
@@ -8,6 +8,25 @@ use gtk4::{
 use libadwaita::prelude::*;
 use libadwaita::{PreferencesGroup, ComboRow, ActionRow, Clamp};
 
+use crate::backtest::model::{AccumulationMode, AggregationMode, Bucket, CoveragePolicy};
+use crate::config::{self, FilterThresholds, QueryDefaults, Settings};
+use crate::presets;
+
+/// Tamaños de periodo en el orden del ComboRow `bucket`.
+pub const BUCKETS: [(Bucket, &str); 4] = [
+    (Bucket::Week, "Semana"),
+    (Bucket::Month, "Mes"),
+    (Bucket::Quarter, "Trimestre"),
+    (Bucket::Custom, "Personalizado"),
+];
+
+/// Políticas de cobertura en el orden del ComboRow `coverage_policy`.
+pub const COVERAGE_POLICIES: [(CoveragePolicy, &str); 3] = [
+    (CoveragePolicy::FillZero, "Contar como 0"),
+    (CoveragePolicy::Penalize, "Penalizar"),
+    (CoveragePolicy::Exclude, "Excluir"),
+];
+
 /// Crea el panel izquierdo con controles de consulta y filtros usando componentes Adwaita
 pub fn create() -> Box {
     let panel = Box::new(Orientation::Vertical, 12);
@@ -24,8 +43,15 @@ pub fn create() -> Box {
     
     let content_box = Box::new(Orientation::Vertical, 12);
     
+    // Si la configuración no cargó se muestran los valores por defecto.
+    let settings = config::settings().cloned().unwrap_or_default();
+    
+    // Presets guardados
+    let preset_group = build_preset_section();
+    content_box.append(&preset_group);
+    
     // Sección de parámetros usando PreferencesGroup de Adwaita
-    let query_group = build_query_section();
+    let query_group = build_query_section(&settings);
     content_box.append(&query_group);
     
     // Botón ejecutar con estilo Adwaita
@@ -48,7 +74,7 @@ pub fn create() -> Box {
     content_box.append(&Separator::new(Orientation::Horizontal));
     
     // Sección de filtros usando PreferencesGroup
-    let filter_group = build_filter_section();
+    let filter_group = build_filter_section(&settings.filters);
     content_box.append(&filter_group);
     
     clamp.set_child(Some(&content_box));
@@ -57,42 +83,111 @@ pub fn create() -> Box {
     panel
 }
 
+/// Construye la sección de presets: elegir, cargar, borrar y guardar
+fn build_preset_section() -> PreferencesGroup {
+    let group = PreferencesGroup::new();
+    group.set_title("Presets");
+    group.set_description(Some("Consulta, filtros y orden guardados"));
+    
+    // Si el fichero no se puede leer la lista queda vacía; el error se ve al cargar
+    let names: Vec<String> = presets::load_all()
+        .map(|presets| presets.into_keys().collect())
+        .unwrap_or_default();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let preset_row = ComboRow::new();
+    preset_row.set_title("Preset");
+    preset_row.set_model(Some(&gtk4::StringList::new(&names)));
+    preset_row.set_widget_name("preset");
+    group.add(&preset_row);
+    
+    let actions_row = ActionRow::new();
+    actions_row.set_title("Preset seleccionado");
+    let load_button = Button::with_label("Cargar");
+    load_button.set_widget_name("preset_load");
+    load_button.set_valign(Align::Center);
+    let delete_button = Button::with_label("Borrar");
+    delete_button.set_widget_name("preset_delete");
+    delete_button.add_css_class("destructive-action");
+    delete_button.set_valign(Align::Center);
+    actions_row.add_suffix(&load_button);
+    actions_row.add_suffix(&delete_button);
+    group.add(&actions_row);
+    
+    let save_row = ActionRow::new();
+    save_row.set_title("Guardar como");
+    let name_entry = Entry::builder()
+        .placeholder_text("nombre")
+        .valign(Align::Center)
+        .build();
+    name_entry.set_widget_name("preset_name");
+    let save_button = Button::with_label("Guardar");
+    save_button.set_widget_name("preset_save");
+    save_button.set_valign(Align::Center);
+    save_row.add_suffix(&name_entry);
+    save_row.add_suffix(&save_button);
+    group.add(&save_row);
+    
+    group
+}
+
 /// Construye la sección de parámetros con componentes Adwaita
-fn build_query_section() -> PreferencesGroup {
+fn build_query_section(settings: &Settings) -> PreferencesGroup {
+    let defaults: &QueryDefaults = &settings.query;
     let group = PreferencesGroup::new();
     group.set_title("Parámetros de Consulta");
     group.set_description(Some("Configure los parámetros para la consulta de backtest"));
     
-    // Exchange ComboRow
-    let exchange_row = ComboRow::new();
-    exchange_row.set_title("Exchange");
-    exchange_row.set_subtitle("Seleccione el exchange");
-    let exchange_model = gtk4::StringList::new(&["BINANCE", "KRAKEN"]);
-    exchange_row.set_model(Some(&exchange_model));
-    exchange_row.set_selected(0);
-    exchange_row.set_widget_name("exchange");
+    // Perfil de base de datos
+    let profile_row = ComboRow::new();
+    profile_row.set_title("Perfil");
+    profile_row.set_subtitle("Base de datos de la configuración");
+    let profiles: Vec<&str> = settings.profiles.keys().map(String::as_str).collect();
+    let active = config::active_profile_name().unwrap_or_default();
+    profile_row.set_model(Some(&gtk4::StringList::new(&profiles)));
+    if let Some(index) = profiles.iter().position(|name| *name == active) {
+        profile_row.set_selected(index as u32);
+    }
+    profile_row.set_widget_name("profile");
+    group.add(&profile_row);
+    
+    // Exchanges ActionRow con Entry
+    let exchange_row = ActionRow::new();
+    exchange_row.set_title("Exchanges");
+    exchange_row.set_subtitle("Separados por comas");
+    let exchange_entry = Entry::builder()
+        .placeholder_text("BINANCE,KRAKEN")
+        .text(defaults.exchanges.join(","))
+        .valign(Align::Center)
+        .build();
+    exchange_entry.set_widget_name("exchanges");
+    exchange_row.add_suffix(&exchange_entry);
+    exchange_row.set_activatable_widget(Some(&exchange_entry));
     group.add(&exchange_row);
     
-    // Currency ComboRow
-    let currency_row = ComboRow::new();
-    currency_row.set_title("Moneda");
-    currency_row.set_subtitle("Moneda base para el trading");
-    let currency_model = gtk4::StringList::new(&["USDT", "BTC"]);
-    currency_row.set_model(Some(&currency_model));
-    currency_row.set_selected(0);
-    currency_row.set_widget_name("currency");
+    // Monedas ActionRow con Entry
+    let currency_row = ActionRow::new();
+    currency_row.set_title("Monedas");
+    currency_row.set_subtitle("Monedas base separadas por comas");
+    let currency_entry = Entry::builder()
+        .placeholder_text("USDT,BTC")
+        .text(defaults.currencies.join(","))
+        .valign(Align::Center)
+        .build();
+    currency_entry.set_widget_name("currencies");
+    currency_row.add_suffix(&currency_entry);
+    currency_row.set_activatable_widget(Some(&currency_entry));
     group.add(&currency_row);
     
-    // Pairlist ActionRow con Entry
+    // Pairlists ActionRow con Entry
     let pairlist_row = ActionRow::new();
     pairlist_row.set_title("Pares");
-    pairlist_row.set_subtitle("Lista de pares separados por comas");
+    pairlist_row.set_subtitle("Pairlists separadas por punto y coma");
     let pairlist_entry = Entry::builder()
-        .placeholder_text("BTC,ETH,BNB")
-        .text("BTC")
+        .placeholder_text("BTC;BTC,ETH,BNB")
+        .text(defaults.pairlists.join(";"))
         .valign(Align::Center)
         .build();
-    pairlist_entry.set_widget_name("pairlist");
+    pairlist_entry.set_widget_name("pairlists");
     pairlist_row.add_suffix(&pairlist_entry);
     pairlist_row.set_activatable_widget(Some(&pairlist_entry));
     group.add(&pairlist_row);
@@ -103,7 +198,7 @@ fn build_query_section() -> PreferencesGroup {
     date_row.set_subtitle("Formato YYYY-MM-DD");
     let start_entry = Entry::builder()
         .placeholder_text("YYYY-MM-DD")
-        .text("2024-01-01")
+        .text(defaults.start_date.as_str())
         .valign(Align::Center)
         .build();
     start_entry.set_widget_name("start_date");
@@ -111,23 +206,102 @@ fn build_query_section() -> PreferencesGroup {
     date_row.set_activatable_widget(Some(&start_entry));
     group.add(&date_row);
     
-    // Meses ActionRow con SpinButton
+    // Fecha de fin opcional
+    let end_row = ActionRow::new();
+    end_row.set_title("Fecha de Fin");
+    end_row.set_subtitle("Opcional; reemplaza al número de periodos");
+    let end_entry = Entry::builder()
+        .placeholder_text("YYYY-MM-DD")
+        .text(defaults.end_date.as_deref().unwrap_or_default())
+        .valign(Align::Center)
+        .build();
+    end_entry.set_widget_name("end_date");
+    end_row.add_suffix(&end_entry);
+    end_row.set_activatable_widget(Some(&end_entry));
+    group.add(&end_row);
+    
+    // Tamaño de periodo
+    let bucket_row = ComboRow::new();
+    bucket_row.set_title("Periodo");
+    bucket_row.set_subtitle("Tamaño de cada timerange");
+    let bucket_titles: Vec<&str> = BUCKETS.iter().map(|(_, title)| *title).collect();
+    bucket_row.set_model(Some(&gtk4::StringList::new(&bucket_titles)));
+    if let Some(index) = BUCKETS.iter().position(|(bucket, _)| *bucket == defaults.bucket) {
+        bucket_row.set_selected(index as u32);
+    }
+    bucket_row.set_widget_name("bucket");
+    group.add(&bucket_row);
+    
+    // Rangos del periodo personalizado
+    let ranges_row = ActionRow::new();
+    ranges_row.set_title("Rangos");
+    ranges_row.set_subtitle("Para el periodo personalizado, separados por comas");
+    let ranges_entry = Entry::builder()
+        .placeholder_text("20240101-20240115")
+        .text(defaults.custom_ranges.join(",").as_str())
+        .valign(Align::Center)
+        .build();
+    ranges_entry.set_widget_name("custom_ranges");
+    ranges_row.add_suffix(&ranges_entry);
+    ranges_row.set_activatable_widget(Some(&ranges_entry));
+    group.add(&ranges_row);
+    
+    // Periodos ActionRow con SpinButton
     let months_row = ActionRow::new();
-    months_row.set_title("Número de Meses");
-    months_row.set_subtitle("Período de análisis");
-    let months_spin = SpinButton::with_range(1.0, 24.0, 1.0);
-    months_spin.set_value(6.0);
+    months_row.set_title("Número de Periodos");
+    months_row.set_subtitle("Período de análisis sin fecha de fin");
+    let months_spin = SpinButton::with_range(1.0, 120.0, 1.0);
+    months_spin.set_value(defaults.months as f64);
     months_spin.set_widget_name("months");
     months_spin.set_valign(Align::Center);
     months_row.add_suffix(&months_spin);
     months_row.set_activatable_widget(Some(&months_spin));
     group.add(&months_row);
     
+    // Agregación en el servidor
+    let aggregation_row = ActionRow::new();
+    aggregation_row.set_title("Agrupar en el Servidor");
+    aggregation_row.set_subtitle("Postgres calcula los agregados por configuración");
+    let aggregation_switch = gtk4::Switch::new();
+    aggregation_switch.set_widget_name("server_aggregation");
+    aggregation_switch.set_active(defaults.aggregation == AggregationMode::Server);
+    aggregation_switch.set_valign(Align::Center);
+    aggregation_row.add_suffix(&aggregation_switch);
+    aggregation_row.set_activatable_widget(Some(&aggregation_switch));
+    group.add(&aggregation_row);
+    
+    // Curva de capital
+    let compounded_row = ActionRow::new();
+    compounded_row.set_title("Interés Compuesto");
+    compounded_row.set_subtitle("Reinvierte el profit de cada mes en la curva de capital");
+    let compounded_switch = gtk4::Switch::new();
+    compounded_switch.set_widget_name("compounded");
+    compounded_switch.set_active(defaults.accumulation == AccumulationMode::Compounded);
+    compounded_switch.set_valign(Align::Center);
+    compounded_row.add_suffix(&compounded_switch);
+    compounded_row.set_activatable_widget(Some(&compounded_switch));
+    group.add(&compounded_row);
+    
+    // Periodos sin resultado
+    let coverage_row = ComboRow::new();
+    coverage_row.set_title("Periodos Ausentes");
+    coverage_row.set_subtitle("Configuraciones sin resultado en algún periodo");
+    let policy_titles: Vec<&str> = COVERAGE_POLICIES.iter().map(|(_, title)| *title).collect();
+    coverage_row.set_model(Some(&gtk4::StringList::new(&policy_titles)));
+    if let Some(index) = COVERAGE_POLICIES
+        .iter()
+        .position(|(policy, _)| *policy == defaults.coverage_policy)
+    {
+        coverage_row.set_selected(index as u32);
+    }
+    coverage_row.set_widget_name("coverage_policy");
+    group.add(&coverage_row);
+    
     group
 }
 
 /// Construye la sección de filtros con componentes Adwaita
-fn build_filter_section() -> PreferencesGroup {
+fn build_filter_section(thresholds: &FilterThresholds) -> PreferencesGroup {
     let group = PreferencesGroup::new();
     group.set_title("Filtros");
     group.set_description(Some("Filtros para refinar los resultados"));
@@ -143,20 +317,80 @@ fn build_filter_section() -> PreferencesGroup {
     search_row.add_suffix(&search_entry);
     search_row.set_activatable_widget(Some(&search_entry));
     group.add(&search_row);
+
+    // Expresión de filtro (`backtest::filter`); se marca en rojo si no es válida
+    let expression_row = ActionRow::new();
+    expression_row.set_title("Expresión");
+    expression_row.set_subtitle("Métricas, textos y && || ! in ~");
+    let expression_entry = Entry::new();
+    expression_entry.set_placeholder_text(Some("trades >= 200 && drawdown < 15%"));
+    expression_entry.set_tooltip_text(Some(
+        "Los ratios (win_rate, drawdown, profit...) van como fracción o con %: \
+         drawdown < 0.15 o drawdown < 15%",
+    ));
+    expression_entry.set_widget_name("filter_expression");
+    expression_entry.set_valign(Align::Center);
+    expression_entry.set_hexpand(true);
+    expression_row.add_suffix(&expression_entry);
+    expression_row.set_activatable_widget(Some(&expression_entry));
+    group.add(&expression_row);
     
     // Switches usando ActionRow de Adwaita
+    // Los umbrales vienen de la sección `[filters]` de la configuración
     let filters = [
-        ("filter_profit", "Solo Profit Positivo", "Mostrar solo estrategias con profit > 0"),
-        ("filter_winrate", "Win Rate > 50%", "Filtrar por tasa de acierto superior al 50%"),
-        ("filter_trades", "Trades > 100", "Mostrar solo estrategias con más de 100 trades"),
-        ("filter_pf", "Profit Factor > 1", "Filtrar por profit factor positivo"),
-        ("filter_expectancy", "Expectancy > 0", "Mostrar solo estrategias con expectancy positiva"),
+        (
+            "filter_profit",
+            format!("Profit > {}", thresholds.min_profit),
+            format!("Mostrar solo estrategias con profit > {}", thresholds.min_profit),
+        ),
+        (
+            "filter_winrate",
+            format!("Win Rate > {:.0}%", thresholds.min_win_rate * 100.0),
+            format!(
+                "Filtrar por tasa de acierto superior al {:.0}%",
+                thresholds.min_win_rate * 100.0
+            ),
+        ),
+        (
+            "filter_trades",
+            format!("Trades > {}", thresholds.min_trades),
+            format!("Mostrar solo estrategias con más de {} trades", thresholds.min_trades),
+        ),
+        (
+            "filter_pf",
+            format!("Profit Factor > {}", thresholds.min_profit_factor),
+            format!("Filtrar por profit factor mayor que {}", thresholds.min_profit_factor),
+        ),
+        (
+            "filter_expectancy",
+            format!("Expectancy > {}", thresholds.min_expectancy),
+            format!(
+                "Mostrar solo estrategias con expectancy > {}",
+                thresholds.min_expectancy
+            ),
+        ),
+        (
+            "filter_roi",
+            format!("ROI 0m ≥ {:.1}%", thresholds.min_initial_roi * 100.0),
+            format!(
+                "Mostrar solo ROI inicial de al menos {:.1}%",
+                thresholds.min_initial_roi * 100.0
+            ),
+        ),
+        (
+            "filter_stoploss",
+            format!("Stoploss ≥ {:.1}%", thresholds.max_stoploss_depth * 100.0),
+            format!(
+                "Ocultar stoplosses más profundos que {:.1}%",
+                thresholds.max_stoploss_depth * 100.0
+            ),
+        ),
     ];
     
     for (name, title, subtitle) in filters {
         let row = ActionRow::new();
-        row.set_title(title);
-        row.set_subtitle(subtitle);
+        row.set_title(&title);
+        row.set_subtitle(&subtitle);
         
         let switch = gtk4::Switch::new();
         switch.set_widget_name(name);
@@ -168,4 +402,4 @@ fn build_filter_section() -> PreferencesGroup {
     }
     
     group
-}
\ No newline at end of file
+}
@@ -9,11 +9,8 @@ pub fn create() -> (Box, TreeView, TreeModelFilter) {
     content_box.set_hexpand(true);
     content_box.add_css_class("view");
 
-    let store = table_view::create_list_store();
-    let filter_model = TreeModelFilter::new(&store, None);
-    filter_model.set_visible_column(30);
-
-    let table_view = table_view::create(&filter_model);
+    let store = table_view::create_list_store(0);
+    let (table_view, filter_model) = table_view::create(&store);
 
     let scrolled = ScrolledWindow::builder()
         .child(&table_view)
@@ -3,14 +3,62 @@
 use glib::Type;
 use gtk4::pango::Style;
 use gtk4::prelude::*;
-use gtk4::{CellRendererText, ListStore, SelectionMode, TreeModelFilter, TreeView, TreeViewColumn};
+use gtk4::{
+    CellRendererText, ListStore, Ordering, SelectionMode, SortColumn, SortType,
+    TreeModelFilter, TreeModelSort, TreeView, TreeViewColumn,
+};
 use gtk4::CenterBox;
 use gtk4::Label;
 
+use crate::backtest::charts::month_label;
+use crate::backtest::model::GridQuery;
+use crate::backtest::timerange::generate_timeranges;
+
+
+/// Columna booleana que decide qué filas deja pasar el filtro
+pub const VISIBLE_COLUMN: i32 = 30;
+
+/// Primera columna del store con el profit de un periodo: hay una por cada
+/// periodo de la consulta (NaN si la fila no tiene datos en él).
+pub const MONTH_COLUMN: u32 = 47;
+
+/// Columnas del store que corresponden a un campo de `backtest::fields` (o a
+/// la puntuación): las que se pueden guardar como orden de un preset.
+pub const SORT_COLUMNS: [(&str, u32); 28] = [
+    ("strategy", 0),
+    ("timeframe", 1),
+    ("minimal_roi", 2),
+    ("stoploss_value", 3),
+    ("max_open_trades", 4),
+    ("total_profit", 12),
+    ("total_trades", 13),
+    ("wins", 14),
+    ("win_rate", 15),
+    ("win_time", 16),
+    ("drawdown_perc", 17),
+    ("rejected_signals", 18),
+    ("neg_months", 19),
+    ("avg_monthly_profit", 20),
+    ("expectancy", 27),
+    ("profit_factor", 28),
+    ("sharpe", 31),
+    ("sortino", 32),
+    ("calmar", 33),
+    ("ulcer_index", 34),
+    ("recovery_factor", 35),
+    ("max_losing_streak", 36),
+    ("equity_return", 37),
+    ("max_drawdown", 38),
+    ("max_drawdown_months", 39),
+    ("market", 43),
+    ("initial_roi", 45),
+    ("score", 46),
+];
 
 /// Crea la vista de tabla con el modelo filtrado y estilo Adwaita
-pub fn create(filter_model: &TreeModelFilter) -> TreeView {
-    let tree_view = TreeView::with_model(filter_model);
+pub fn create(store: &ListStore) -> (TreeView, TreeModelFilter) {
+    let tree_view = TreeView::new();
+    let filter_model = attach_store(&tree_view, store);
     tree_view.set_enable_search(true);
     tree_view.set_search_column(0);
     tree_view.add_css_class("rich-list");
@@ -21,12 +69,54 @@ pub fn create(filter_model: &TreeModelFilter) -> TreeView {
     // Habilitar selección múltiple
     tree_view.selection().set_mode(SelectionMode::Multiple);
 
-    tree_view
+    (tree_view, filter_model)
+}
+
+/// Muestra `store` en la vista: filtra por `VISIBLE_COLUMN` y ordena al
+/// pulsar las cabeceras. Devuelve el filtro para poder refiltrar.
+pub fn attach_store(tree_view: &TreeView, store: &ListStore) -> TreeModelFilter {
+    let filter_model = TreeModelFilter::new(store, None);
+    filter_model.set_visible_column(VISIBLE_COLUMN);
+    let sort_model = TreeModelSort::with_model(&filter_model);
+    sort_nan_last(&sort_model, store);
+    tree_view.set_model(Some(&sort_model));
+    filter_model
+}
+
+/// Las columnas `f64` usan NaN para "sin dato" (meses sin resultado, la
+/// puntuación...): se ordenan al final tanto en ascendente como en
+/// descendente.
+fn sort_nan_last(sort_model: &TreeModelSort, store: &ListStore) {
+    for column in 0..store.n_columns() {
+        if store.column_type(column) != Type::F64 {
+            continue;
+        }
+        let weak = sort_model.downgrade();
+        // `model` es el modelo hijo: solo se leen sus valores
+        sort_model.set_sort_func(SortColumn::Index(column as u32), move |model, a, b| {
+            let descending = weak
+                .upgrade()
+                .and_then(|sort_model| sort_model.sort_column_id())
+                .is_some_and(|(_, order)| order == SortType::Descending);
+            let a = model.get::<f64>(a, column);
+            let b = model.get::<f64>(b, column);
+            match (a.is_nan(), b.is_nan()) {
+                (false, false) => a.partial_cmp(&b).map_or(Ordering::Equal, Ordering::from),
+                (true, true) => Ordering::Equal,
+                // GTK invierte el resultado en descendente
+                (true, false) if descending => Ordering::Smaller,
+                (true, false) => Ordering::Larger,
+                (false, true) if descending => Ordering::Larger,
+                (false, true) => Ordering::Smaller,
+            }
+        });
+    }
 }
 
-/// Crea el ListStore con todas las columnas necesarias
-pub fn create_list_store() -> ListStore {
-    ListStore::new(&[
+/// Crea el ListStore con todas las columnas necesarias, más `months`
+/// columnas mensuales a partir de `MONTH_COLUMN`
+pub fn create_list_store(months: usize) -> ListStore {
+    let mut types = vec![
         Type::STRING, // 0: Estrategia
         Type::STRING, // 1: TF
         Type::STRING, // 2: ROI
@@ -58,7 +148,70 @@ pub fn create_list_store() -> ListStore {
         Type::F64,    // 28: Profit Factor
         Type::STRING, // 29: Estrategia lowercase para búsqueda
         Type::BOOL,   // 30: Visible (para filtrado)
-    ])
+        Type::F64,    // 31: Sharpe
+        Type::F64,    // 32: Sortino
+        Type::F64,    // 33: Calmar
+        Type::F64,    // 34: Ulcer Index
+        Type::F64,    // 35: Recovery Factor
+        Type::I32,    // 36: Racha de meses negativos
+        Type::F64,    // 37: Rentabilidad de la curva de capital
+        Type::F64,    // 38: Drawdown máximo de la curva
+        Type::I32,    // 39: Meses del drawdown máximo
+        Type::I32,    // 40: Periodos presentes
+        Type::I32,    // 41: Periodos esperados
+        Type::STRING, // 42: Huecos (AAAAMMDD separados por comas)
+        Type::STRING, // 43: Mercado (EXCHANGE/MONEDA/PAIRLIST)
+        Type::F64,    // 44: Robustez entre mercados
+        Type::F64,    // 45: ROI en el minuto 0 (NaN si no es interpretable)
+        Type::F64,    // 46: Puntuación (NaN si no cumple los requisitos)
+    ];
+    types.extend(std::iter::repeat_n(Type::F64, months));
+    ListStore::new(&types)
+}
+
+/// Periodos (`AAAAMMDD`, como las claves de `monthly`) que pide `query`,
+/// uno por columna mensual; `generate_timeranges` no repite fechas de inicio.
+pub fn month_keys(query: &GridQuery) -> Vec<String> {
+    generate_timeranges(query)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|timerange| timerange.get(..8).map(str::to_string))
+        .collect()
+}
+
+/// Sustituye las columnas mensuales de la vista por una por periodo de
+/// `months`, visibles según `visible`, y las devuelve en el mismo orden.
+pub fn set_month_columns(
+    tree_view: &TreeView,
+    months: &[String],
+    visible: bool,
+) -> Vec<TreeViewColumn> {
+    for col in tree_view.columns() {
+        if col.sort_column_id() >= MONTH_COLUMN as i32 {
+            tree_view.remove_column(&col);
+        }
+    }
+    months
+        .iter()
+        .enumerate()
+        .map(|(index, month)| {
+            let column_id = (MONTH_COLUMN as usize + index) as i32;
+            let col = TreeViewColumn::new();
+            col.set_title(&format!("📅 {}", month_label(month)));
+            col.set_resizable(true);
+            col.set_sort_column_id(column_id);
+            col.set_min_width(80);
+            col.set_visible(visible);
+
+            let cell = CellRendererText::new();
+            cell.set_padding(8, 4);
+            cell.set_property("xalign", 1.0_f32);
+            col.pack_start(&cell, true);
+            format_month_profit(&col, &cell, column_id);
+            tree_view.append_column(&col);
+            col
+        })
+        .collect()
 }
 
 /// Obtiene el ListStore base desde un TreeModelFilter
@@ -74,8 +227,11 @@ fn setup_columns(tree_view: &TreeView) {
     // Definición de columnas con iconos cuando sea apropiado
     let columns = [
         ("📊 Estrategia", 0, 140, false),
+        ("🏅 Score", 46, 90, true),
         ("⏱ TF", 1, 60, false),
+        ("🌍 Mercado", 43, 140, false),
         ("📈 Min ROI", 2, 90, false),
+        ("📈 ROI 0m (%)", 45, 90, true),
         ("🛑 SL", 3, 70, true),
         ("🔢 Max Trades", 4, 100, false),
         ("🎯 TS", 5, 50, false),
@@ -90,6 +246,18 @@ fn setup_columns(tree_view: &TreeView) {
         ("📊 Avg Monthly", 20, 110, true),
         ("🎲 Expectancy", 27, 100, true),
         ("⚖️ Profit Factor", 28, 110, true),
+        ("📐 Sharpe", 31, 90, true),
+        ("📐 Sortino", 32, 90, true),
+        ("📐 Calmar", 33, 90, true),
+        ("🌊 Ulcer", 34, 80, true),
+        ("🔁 Recovery", 35, 90, true),
+        ("🔻 Racha Neg", 36, 90, false),
+        ("📈 Equity (%)", 37, 100, true),
+        ("📉 Max DD (%)", 38, 100, true),
+        ("⌛ Meses DD", 39, 90, false),
+        ("📅 Cobertura", 40, 90, false),
+        ("🕳 Huecos", 42, 120, false),
+        ("🛡 Robustez", 44, 90, true),
     ];
 
     for (title, column_id, width, _is_numeric) in columns {
@@ -115,26 +283,35 @@ fn setup_cell_renderer(col: &TreeViewColumn, cell: &CellRendererText, column_id:
                                           //cell.set_property("xalign", 0.0_f32); // <-- Alinear a la izquierda
 
     match column_id {
-        // 3 => format_stop_loss(col, cell),
+        3 | 45 => format_ratio(col, cell, column_id),
         5 => format_trailing_stop(col, cell),
-        12 => format_total_profit(col, cell),
+        12 | 37 => format_total_profit(col, cell, column_id),
         15 => format_win_rate(col, cell),
         16 => format_win_time(col, cell),
-        17 => format_drawdown(col, cell),
-        20 | 27 => format_colored_numeric(col, cell, column_id),
+        17 | 38 => format_drawdown(col, cell, column_id),
+        20 | 27 | 31 | 32 | 33 | 35 => format_colored_numeric(col, cell, column_id),
         28 => format_profit_factor(col, cell),
-        4 | 13 | 14 | 18 | 19 => format_integer(col, cell, column_id),
-        0 | 1 | 2 | 9 | 10 => format_text(col, cell, column_id),
+        4 | 13 | 14 | 18 | 19 | 36 | 39 => format_integer(col, cell, column_id),
+        40 => format_coverage(col, cell),
+        46 => format_score(col, cell),
+        0 | 1 | 2 | 9 | 10 | 42 | 43 => format_text(col, cell, column_id),
         _ => format_float(col, cell, column_id),
     }
 }
 
 // Funciones de formateo específicas para cada tipo de columna
 
-fn format_stop_loss(col: &TreeViewColumn, cell: &CellRendererText) {
+/// Stoploss y ROI en %; NaN es un valor de la fuente que no se pudo interpretar.
+fn format_ratio(col: &TreeViewColumn, cell: &CellRendererText, column_id: i32) {
     col.set_cell_data_func(cell, move |_col, cell, model, iter| {
-        if let Ok(value) = model.get_value(&iter, 3).get::<f64>() {
-            cell.set_property("text", &format!("{:.1}%", value));
+        if let Ok(value) = model.get_value(&iter, column_id).get::<f64>() {
+            if value.is_nan() {
+                cell.set_property("text", "⚠ inválido");
+                cell.set_property("foreground", "#e01b24");
+            } else {
+                cell.set_property("text", &format!("{:.1}%", value * 100.0));
+                cell.set_property("foreground-set", false);
+            }
             cell.set_property("weight", 600);
         }
     });
@@ -148,9 +325,9 @@ fn format_trailing_stop(col: &TreeViewColumn, cell: &CellRendererText) {
     });
 }
 
-fn format_total_profit(col: &TreeViewColumn, cell: &CellRendererText) {
+fn format_total_profit(col: &TreeViewColumn, cell: &CellRendererText, column_id: i32) {
     col.set_cell_data_func(cell, move |_col, cell, model, iter| {
-        if let Ok(value) = model.get_value(&iter, 12).get::<f64>() {
+        if let Ok(value) = model.get_value(&iter, column_id).get::<f64>() {
             let text = format!("{:.1}%", value * 100.0);
             let (color, weight) = if value > 10.0 {
                 ("#2ec27e", 700) // Verde brillante para muy bueno
@@ -211,9 +388,9 @@ fn format_win_time(col: &TreeViewColumn, cell: &CellRendererText) {
     });
 }
 
-fn format_drawdown(col: &TreeViewColumn, cell: &CellRendererText) {
+fn format_drawdown(col: &TreeViewColumn, cell: &CellRendererText, column_id: i32) {
     col.set_cell_data_func(cell, move |_col, cell, model, iter| {
-        if let Ok(value) = model.get_value(&iter, 17).get::<f64>() {
+        if let Ok(value) = model.get_value(&iter, column_id).get::<f64>() {
             let percentage = value * 100.0;
             let text = format!("-{:.1}%", percentage.abs());
             let (color, weight) = if percentage < 5.0 {
@@ -289,6 +466,21 @@ fn format_integer(col: &TreeViewColumn, cell: &CellRendererText, column_id: i32)
     });
 }
 
+fn format_coverage(col: &TreeViewColumn, cell: &CellRendererText) {
+    col.set_cell_data_func(cell, move |_col, cell, model, iter| {
+        let present = model.get_value(&iter, 40).get::<i32>().unwrap_or(0);
+        let expected = model.get_value(&iter, 41).get::<i32>().unwrap_or(0);
+        cell.set_property("text", &format!("{}/{}", present, expected));
+        if present < expected {
+            cell.set_property("foreground", "#ff7800");
+            cell.set_property("weight", 600);
+        } else {
+            cell.set_property("foreground", "#57e389");
+            cell.set_property("weight", 400);
+        }
+    });
+}
+
 fn format_text(col: &TreeViewColumn, cell: &CellRendererText, column_id: i32) {
     col.add_attribute(cell, "text", column_id);
 
@@ -303,6 +495,50 @@ fn format_text(col: &TreeViewColumn, cell: &CellRendererText, column_id: i32) {
     }
 }
 
+/// Puntuación de `scoring.default`; NaN si la fila no cumple sus requisitos.
+fn format_score(col: &TreeViewColumn, cell: &CellRendererText) {
+    col.set_cell_data_func(cell, move |_col, cell, model, iter| {
+        if let Ok(value) = model.get_value(&iter, 46).get::<f64>() {
+            if value.is_nan() {
+                cell.set_property("text", "—");
+                cell.set_property("foreground", "#77767b");
+                cell.set_property("weight", 400);
+            } else {
+                cell.set_property("text", &format!("{:.4}", value));
+                cell.set_property("foreground", if value > 0.0 { "#2ec27e" } else { "#e01b24" });
+                cell.set_property("weight", 700);
+            }
+        }
+    });
+}
+
+/// Profit de un periodo en %, coloreado por su signo y tamaño; los periodos
+/// sin datos se muestran en gris.
+fn format_month_profit(col: &TreeViewColumn, cell: &CellRendererText, column_id: i32) {
+    col.set_cell_data_func(cell, move |_col, cell, model, iter| {
+        if let Ok(value) = model.get_value(iter, column_id).get::<f64>() {
+            if value.is_nan() {
+                cell.set_property("text", "—");
+                cell.set_property("foreground", "#77767b");
+                cell.set_property("weight", 400);
+                return;
+            }
+            let (color, weight) = if value > 0.05 {
+                ("#2ec27e", 700)
+            } else if value > 0.0 {
+                ("#57e389", 600)
+            } else if value > -0.05 {
+                ("#f6d32d", 500)
+            } else {
+                ("#e01b24", 600)
+            };
+            cell.set_property("text", format!("{:.1}%", value * 100.0));
+            cell.set_property("foreground", color);
+            cell.set_property("weight", weight);
+        }
+    });
+}
+
 fn format_float(col: &TreeViewColumn, cell: &CellRendererText, column_id: i32) {
     col.set_cell_data_func(cell, move |_col, cell, model, iter| {
         if let Ok(value) = model.get_value(&iter, column_id).get::<f64>() {
@@ -1,7 +1,10 @@
 // src/gui/ui/toolbar.rs
 
 use gtk4::prelude::*;
-use gtk4::{Box, Button, Label, Orientation};
+use gtk4::{
+    Box, Button, CheckButton, Label, MenuButton, Orientation, PolicyType, Popover, ScrolledWindow,
+    Separator,
+};
 use libadwaita::HeaderBar;
 
 pub fn create() -> HeaderBar {
@@ -37,6 +40,44 @@ pub fn create() -> HeaderBar {
         .build();
     header_bar.pack_end(&export_all);
 
+    let charts = Button::builder()
+        .name("charts")
+        .icon_name("utilities-system-monitor-symbolic")
+        .tooltip_text("Gráficos")
+        .sensitive(false)
+        .build();
+    header_bar.pack_end(&charts);
+
+    // Columnas mensuales: las casillas de cada periodo se crean con la consulta
+    let month_checks = Box::builder()
+        .name("month_checks")
+        .orientation(Orientation::Vertical)
+        .spacing(2)
+        .build();
+    let scrolled = ScrolledWindow::builder()
+        .child(&month_checks)
+        .hscrollbar_policy(PolicyType::Never)
+        .propagate_natural_height(true)
+        .max_content_height(400)
+        .build();
+    let all_months = CheckButton::builder()
+        .name("all_months")
+        .label("Todos los periodos")
+        .active(true)
+        .build();
+    let popover_box = Box::new(Orientation::Vertical, 6);
+    popover_box.append(&all_months);
+    popover_box.append(&Separator::new(Orientation::Horizontal));
+    popover_box.append(&scrolled);
+    let month_columns = MenuButton::builder()
+        .name("month_columns")
+        .icon_name("x-office-calendar-symbolic")
+        .tooltip_text("Columnas mensuales")
+        .popover(&Popover::builder().child(&popover_box).build())
+        .sensitive(false)
+        .build();
+    header_bar.pack_end(&month_columns);
+
     let clear_button = Button::builder()
         .name("clear")
         .icon_name("edit-clear-all-symbolic")
@@ -0,0 +1,141 @@
+// src/import.rs
+
+pub mod freqtrade;
+pub mod store;
+
+use deadpool_postgres::Client;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::import::freqtrade::{map_results, ConfigSnapshot, ImportedBacktest, ResultFile};
+use crate::schema;
+pub use crate::import::store::ImportReport;
+
+/// Importa resultados de `freqtrade backtesting` (`.json` o `.zip`) en la tabla
+/// `backtest`. Los directorios se recorren buscando ficheros de resultados.
+///
+/// `exchange` solo es necesario para resultados sin instantánea de configuración.
+pub async fn import_paths(
+    client: &mut Client,
+    paths: &[PathBuf],
+    exchange: Option<&str>,
+) -> Result<ImportReport> {
+    // Sin `id` no se pueden enlazar los pares y trades de cada fila
+    schema::verify_id(client).await?;
+    let mut report = ImportReport::default();
+
+    for file in collect_result_files(paths)? {
+        let backtests = read_result_file(&file, exchange)?;
+        let written = store::upsert(client, &backtests).await?;
+
+        println!(
+            "📥 {}: {} backtests, {} pares, {} trades",
+            file.display(),
+            written.backtests,
+            written.pairs,
+            written.trades
+        );
+        report.files += 1;
+        report.backtests += written.backtests;
+        report.pairs += written.pairs;
+        report.trades += written.trades;
+    }
+
+    Ok(report)
+}
+
+/// Lee un resultado y su configuración (dentro del ZIP o como `<nombre>_config.json`).
+/// Cualquier fallo es un `Error::Import` con la ruta del fichero.
+pub fn read_result_file(path: &Path, exchange: Option<&str>) -> Result<Vec<ImportedBacktest>> {
+    let fail = |reason: &dyn std::fmt::Display| Error::import(path.display().to_string(), reason);
+    let (results, snapshot) = if has_extension(path, "zip") {
+        read_zip(path).map_err(|reason| fail(&reason))?
+    } else {
+        let results: ResultFile = read_json(path).map_err(|reason| fail(&reason))?;
+        let config_path = path.with_file_name(format!(
+            "{}_config.json",
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+        ));
+        let snapshot = if config_path.exists() {
+            read_json(&config_path)
+                .map_err(|reason| fail(&format!("{}: {}", config_path.display(), reason)))?
+        } else {
+            ConfigSnapshot::default()
+        };
+        (results, snapshot)
+    };
+
+    map_results(results, &snapshot, exchange).map_err(|reason| fail(&reason))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> std::result::Result<T, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    serde_json::from_reader(file).map_err(|e| e.to_string())
+}
+
+fn read_zip(path: &Path) -> std::result::Result<(ResultFile, ConfigSnapshot), String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut results = None;
+    let mut snapshot = ConfigSnapshot::default();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let name = entry.name().to_string();
+        let is_config = name.ends_with("_config.json");
+        if !is_config && !is_result_name(&name) {
+            continue;
+        }
+        let mut text = String::new();
+        entry
+            .read_to_string(&mut text)
+            .map_err(|e| format!("{}: {}", name, e))?;
+        if is_config {
+            snapshot = serde_json::from_str(&text).map_err(|e| format!("{}: {}", name, e))?;
+        } else {
+            results = Some(serde_json::from_str(&text).map_err(|e| format!("{}: {}", name, e))?);
+        }
+    }
+
+    let results = results.ok_or("el ZIP no contiene un resultado de backtest")?;
+    Ok((results, snapshot))
+}
+
+fn collect_result_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = fs::read_dir(path)
+                .map_err(|e| Error::import(path.display().to_string(), e))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| {
+                    has_extension(p, "zip")
+                        || p.file_name()
+                            .and_then(|n| n.to_str())
+                            .is_some_and(is_result_name)
+                })
+                .collect();
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Descarta `.last_result.json`, `*.meta.json` y las instantáneas `*_config.json`.
+fn is_result_name(name: &str) -> bool {
+    name.ends_with(".json")
+        && !name.ends_with("_config.json")
+        && !name.ends_with(".meta.json")
+        && !name.ends_with(".last_result.json")
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(extension)
+}
@@ -0,0 +1,314 @@
+// src/import/freqtrade.rs
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::backtest::model::BacktestRecord;
+
+/// Fichero `backtest-results/*.json` que escribe `freqtrade backtesting`.
+#[derive(Debug, Deserialize)]
+pub struct ResultFile {
+    pub strategy: HashMap<String, StrategyStats>,
+    #[serde(default)]
+    pub strategy_comparison: Vec<ComparisonRow>,
+}
+
+/// Estadísticas de una estrategia dentro de `ResultFile::strategy`.
+#[derive(Debug, Deserialize)]
+pub struct StrategyStats {
+    #[serde(default)]
+    pub trades: Vec<Trade>,
+    #[serde(default)]
+    pub results_per_pair: Vec<PairResult>,
+    pub total_trades: i32,
+    pub profit_total: f64,
+    pub wins: i32,
+    pub winner_holding_avg_s: Option<f64>,
+    pub max_drawdown_account: Option<f64>,
+    /// Nombre antiguo de `max_drawdown_account`.
+    pub max_drawdown: Option<f64>,
+    pub rejected_signals: Option<i32>,
+    pub stake_currency: String,
+    #[serde(default)]
+    pub pairlist: Vec<String>,
+    pub max_open_trades: i32,
+    pub timeframe: String,
+    pub timerange: String,
+    pub stoploss: f64,
+    #[serde(default)]
+    pub trailing_stop: bool,
+    pub trailing_stop_positive: Option<f64>,
+    pub trailing_stop_positive_offset: Option<f64>,
+    #[serde(default)]
+    pub trailing_only_offset_is_reached: bool,
+    pub minimal_roi: HashMap<String, f64>,
+}
+
+/// Fila de `strategy_comparison`; se usa para validar el nombre de la estrategia.
+#[derive(Debug, Deserialize)]
+pub struct ComparisonRow {
+    pub key: String,
+}
+
+/// Fila de `results_per_pair`. La fila `TOTAL` se descarta al importar.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PairResult {
+    pub key: String,
+    pub trades: i32,
+    pub profit_mean: f64,
+    pub profit_total: f64,
+    pub profit_total_abs: f64,
+    pub wins: i32,
+    pub draws: i32,
+    pub losses: i32,
+    pub duration_avg: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Trade {
+    pub pair: String,
+    #[serde(default)]
+    pub is_short: bool,
+    pub open_timestamp: i64,
+    pub close_timestamp: Option<i64>,
+    pub open_rate: f64,
+    pub close_rate: Option<f64>,
+    pub amount: f64,
+    pub stake_amount: f64,
+    pub profit_ratio: f64,
+    pub profit_abs: f64,
+    pub trade_duration: Option<i32>,
+    pub exit_reason: Option<String>,
+    pub enter_tag: Option<String>,
+}
+
+impl Trade {
+    pub fn open_date(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.open_timestamp)
+    }
+
+    pub fn close_date(&self) -> Option<DateTime<Utc>> {
+        self.close_timestamp
+            .and_then(DateTime::from_timestamp_millis)
+    }
+}
+
+/// Instantánea `*_config.json` que freqtrade guarda junto al resultado.
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigSnapshot {
+    pub exchange: Option<ExchangeConfig>,
+    pub entry_pricing: Option<PricingConfig>,
+    pub exit_pricing: Option<PricingConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExchangeConfig {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PricingConfig {
+    pub price_side: Option<String>,
+    pub check_depth_of_market: Option<DepthOfMarket>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DepthOfMarket {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Un backtest listo para escribirse en las tablas de freqdash.
+#[derive(Debug, Clone)]
+pub struct ImportedBacktest {
+    pub record: BacktestRecord,
+    pub pairs: Vec<PairResult>,
+    pub trades: Vec<Trade>,
+}
+
+/// Convierte un resultado de freqtrade en una fila por estrategia.
+///
+/// `exchange` tiene prioridad sobre el de la instantánea de configuración;
+/// si ninguno lo indica, el fichero no se puede importar. El error es solo el
+/// motivo: `read_result_file` lo devuelve como `Error::Import` con la ruta.
+pub fn map_results(
+    results: ResultFile,
+    snapshot: &ConfigSnapshot,
+    exchange: Option<&str>,
+) -> Result<Vec<ImportedBacktest>, String> {
+    let exchange = exchange
+        .map(str::to_string)
+        .or_else(|| snapshot.exchange.as_ref().map(|e| e.name.clone()))
+        .ok_or("el resultado no incluye configuración: indique el exchange")?
+        .to_uppercase();
+
+    let entry_pricing = snapshot.entry_pricing.as_ref();
+    let exit_pricing = snapshot.exit_pricing.as_ref();
+    let price_side = |pricing: Option<&PricingConfig>| {
+        pricing
+            .and_then(|p| p.price_side.clone())
+            .unwrap_or_else(|| "same".to_string())
+    };
+    let check_depth_of_market_enable = entry_pricing
+        .and_then(|p| p.check_depth_of_market.as_ref())
+        .map(|d| d.enabled)
+        .unwrap_or(false);
+
+    let mut imported = Vec::new();
+    for (name, stats) in results.strategy {
+        if !results.strategy_comparison.is_empty()
+            && !results
+                .strategy_comparison
+                .iter()
+                .any(|row| row.key == name)
+        {
+            return Err(format!("'{}' no aparece en strategy_comparison", name));
+        }
+
+        let record = BacktestRecord {
+            strategy: name,
+            timeframe: stats.timeframe,
+            minimal_roi: roi_to_text(&stats.minimal_roi),
+            stoploss: stats.stoploss.to_string(),
+            // freqtrade escribe -1 cuando no hay límite.
+            max_open_trades: stats.max_open_trades,
+            trailing_stop: stats.trailing_stop,
+            trailing_stop_positive: stats.trailing_stop_positive.unwrap_or(0.0),
+            trailing_stop_positive_offset: stats.trailing_stop_positive_offset.unwrap_or(0.0),
+            trailing_only_offset_is_reached: stats.trailing_only_offset_is_reached,
+            entry_pricing: price_side(entry_pricing),
+            exit_pricing: price_side(exit_pricing),
+            check_depth_of_market_enable,
+            profit_total: stats.profit_total,
+            total_trades: stats.total_trades,
+            wins: stats.wins,
+            winner_holding_avg_s: stats.winner_holding_avg_s,
+            max_drawdown: stats.max_drawdown_account.or(stats.max_drawdown),
+            rejected_signals: stats.rejected_signals,
+            timerange: stats.timerange,
+            exchange: exchange.clone(),
+            stake_currency: stats.stake_currency.to_uppercase(),
+            pairlist: pairlist_label(&stats.pairlist),
+        };
+
+        imported.push(ImportedBacktest {
+            record,
+            pairs: stats
+                .results_per_pair
+                .into_iter()
+                .filter(|p| p.key != "TOTAL")
+                .collect(),
+            trades: stats.trades,
+        });
+    }
+
+    Ok(imported)
+}
+
+/// `{"0": 0.1, "30": 0.05}` ordenado por minutos.
+fn roi_to_text(roi: &HashMap<String, f64>) -> String {
+    let mut entries: Vec<(&String, &f64)> = roi.iter().collect();
+    entries.sort_by_key(|(minutes, _)| minutes.parse::<u64>().unwrap_or(u64::MAX));
+    let body = entries
+        .iter()
+        .map(|(minutes, ratio)| format!("\"{}\": {}", minutes, ratio))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{{{}}}", body)
+}
+
+/// `["BTC/USDT", "ETH/USDT:USDT"]` → `"BTC,ETH"`, el formato que se filtra
+/// desde la GUI y el CLI.
+fn pairlist_label(pairs: &[String]) -> String {
+    pairs
+        .iter()
+        .map(|pair| pair.split('/').next().unwrap_or(pair).to_uppercase())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESULT: &str = r#"{
+        "strategy": {
+            "SampleStrategy": {
+                "trades": [{
+                    "pair": "BTC/USDT", "is_short": false,
+                    "open_timestamp": 1704067200000, "close_timestamp": 1704070800000,
+                    "open_rate": 42000.0, "close_rate": 42420.0, "amount": 0.01,
+                    "stake_amount": 420.0, "profit_ratio": 0.008, "profit_abs": 3.36,
+                    "trade_duration": 60, "exit_reason": "roi", "enter_tag": null
+                }],
+                "results_per_pair": [
+                    {"key": "BTC/USDT", "trades": 1, "profit_mean": 0.008, "profit_total": 0.0034,
+                     "profit_total_abs": 3.36, "wins": 1, "draws": 0, "losses": 0, "duration_avg": "1:00:00"},
+                    {"key": "TOTAL", "trades": 1, "profit_mean": 0.008, "profit_total": 0.0034,
+                     "profit_total_abs": 3.36, "wins": 1, "draws": 0, "losses": 0, "duration_avg": "1:00:00"}
+                ],
+                "total_trades": 1, "profit_total": 0.0034, "wins": 1,
+                "winner_holding_avg_s": 3600.0, "max_drawdown_account": 0.0,
+                "rejected_signals": 2, "stake_currency": "USDT",
+                "pairlist": ["BTC/USDT", "ETH/USDT"], "max_open_trades": 3,
+                "timeframe": "5m", "timerange": "20240101-20240201", "stoploss": -0.1,
+                "trailing_stop": true, "trailing_stop_positive": null,
+                "trailing_stop_positive_offset": 0.0, "trailing_only_offset_is_reached": false,
+                "minimal_roi": {"30": 0.05, "0": 0.1}
+            }
+        },
+        "strategy_comparison": [{"key": "SampleStrategy"}]
+    }"#;
+
+    const CONFIG: &str = r#"{
+        "exchange": {"name": "binance", "pair_whitelist": ["BTC/USDT", "ETH/USDT"]},
+        "entry_pricing": {"price_side": "other", "check_depth_of_market": {"enabled": true}},
+        "exit_pricing": {"price_side": "same"}
+    }"#;
+
+    #[test]
+    fn maps_strategy_stats_onto_backtest_columns() {
+        let results: ResultFile = serde_json::from_str(RESULT).unwrap();
+        let snapshot: ConfigSnapshot = serde_json::from_str(CONFIG).unwrap();
+        let imported = map_results(results, &snapshot, None).unwrap();
+
+        assert_eq!(imported.len(), 1);
+        let backtest = &imported[0];
+        assert_eq!(backtest.record.exchange, "BINANCE");
+        assert_eq!(backtest.record.stake_currency, "USDT");
+        assert_eq!(backtest.record.pairlist, "BTC,ETH");
+        assert_eq!(backtest.record.minimal_roi, r#"{"0": 0.1, "30": 0.05}"#);
+        assert_eq!(backtest.record.stoploss, "-0.1");
+        assert_eq!(backtest.record.trailing_stop_positive, 0.0);
+        assert_eq!(backtest.record.entry_pricing, "other");
+        assert_eq!(backtest.record.exit_pricing, "same");
+        assert!(backtest.record.check_depth_of_market_enable);
+        assert_eq!(backtest.record.rejected_signals, Some(2));
+        assert_eq!(backtest.pairs.len(), 1);
+        assert_eq!(backtest.trades[0].trade_duration, Some(60));
+    }
+
+    #[test]
+    fn exchange_is_required_without_config_snapshot() {
+        let results: ResultFile = serde_json::from_str(RESULT).unwrap();
+        assert!(map_results(results, &ConfigSnapshot::default(), None).is_err());
+
+        // Leído de disco, el fallo lleva la ruta del fichero
+        let path = std::env::temp_dir().join(format!("freqdash-{}.json", std::process::id()));
+        std::fs::write(&path, RESULT).unwrap();
+        let result = crate::import::read_result_file(&path, None);
+        std::fs::remove_file(&path).unwrap();
+        match result {
+            Err(crate::error::Error::Import { path: at, reason }) => {
+                assert_eq!(at, path.display().to_string());
+                assert!(reason.contains("exchange"), "{}", reason);
+            }
+            other => panic!("se esperaba Import, llegó {:?}", other.map(|b| b.len())),
+        }
+        assert!(matches!(
+            crate::import::read_result_file(&path, Some("binance")),
+            Err(crate::error::Error::Import { .. })
+        ));
+    }
+}
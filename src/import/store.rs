@@ -0,0 +1,152 @@
+// src/import/store.rs
+
+use deadpool_postgres::Client;
+use tokio_postgres::types::ToSql;
+
+use crate::error::{Error, Result};
+use crate::import::freqtrade::ImportedBacktest;
+
+/// Clave natural de una fila de `backtest`: mercado, periodo y configuración.
+const NATURAL_KEY: &str = "exchange = $1 AND stake_currency = $2 AND pairlist = $3 \
+    AND timerange = $4 AND strategy = $5 AND timeframe = $6 AND minimal_roi = $7 \
+    AND stoploss = $8 AND max_open_trades = $9 AND trailing_stop = $10 \
+    AND trailing_stop_positive = $11 AND trailing_stop_positive_offset = $12 \
+    AND trailing_only_offset_is_reached = $13 AND entry_pricing = $14 \
+    AND exit_pricing = $15 AND check_depth_of_market_enable = $16";
+
+const INSERT_BACKTEST: &str = "INSERT INTO backtest (
+        exchange, stake_currency, pairlist, timerange, strategy, timeframe, minimal_roi,
+        stoploss, max_open_trades, trailing_stop, trailing_stop_positive,
+        trailing_stop_positive_offset, trailing_only_offset_is_reached, entry_pricing,
+        exit_pricing, check_depth_of_market_enable, profit_total, total_trades, wins,
+        winner_holding_avg_s, max_drawdown, rejected_signals
+    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+              $17, $18, $19, $20, $21, $22)
+    RETURNING id";
+
+const INSERT_PAIR: &str = "INSERT INTO backtest_pair (
+        backtest_id, pair, trades, profit_mean, profit_total, profit_total_abs,
+        wins, draws, losses, duration_avg
+    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)";
+
+const INSERT_TRADE: &str = "INSERT INTO backtest_trade (
+        backtest_id, pair, is_short, open_date, close_date, open_rate, close_rate,
+        amount, stake_amount, profit_ratio, profit_abs, trade_duration, exit_reason, enter_tag
+    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)";
+
+/// Totales de una importación.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ImportReport {
+    pub files: usize,
+    pub backtests: usize,
+    pub pairs: usize,
+    pub trades: usize,
+}
+
+/// Escribe los backtests en una transacción. Reimportar el mismo resultado
+/// reemplaza la fila existente con la misma clave natural en lugar de duplicarla.
+pub async fn upsert(
+    client: &mut Client,
+    backtests: &[ImportedBacktest],
+) -> Result<ImportReport> {
+    let tx = client.transaction().await.map_err(Error::query)?;
+    let mut report = ImportReport::default();
+
+    for backtest in backtests {
+        let r = &backtest.record;
+        let key: [&(dyn ToSql + Sync); 16] = [
+            &r.exchange,
+            &r.stake_currency,
+            &r.pairlist,
+            &r.timerange,
+            &r.strategy,
+            &r.timeframe,
+            &r.minimal_roi,
+            &r.stoploss,
+            &r.max_open_trades,
+            &r.trailing_stop,
+            &r.trailing_stop_positive,
+            &r.trailing_stop_positive_offset,
+            &r.trailing_only_offset_is_reached,
+            &r.entry_pricing,
+            &r.exit_pricing,
+            &r.check_depth_of_market_enable,
+        ];
+
+        for child in ["backtest_pair", "backtest_trade"] {
+            let sql = format!(
+                "DELETE FROM {} WHERE backtest_id IN (SELECT id FROM backtest WHERE {})",
+                child, NATURAL_KEY
+            );
+            tx.execute(&sql, &key).await.map_err(Error::query)?;
+        }
+        tx.execute(&format!("DELETE FROM backtest WHERE {}", NATURAL_KEY), &key)
+            .await
+            .map_err(Error::query)?;
+
+        let mut values = key.to_vec();
+        values.extend_from_slice(&[
+            &r.profit_total,
+            &r.total_trades,
+            &r.wins,
+            &r.winner_holding_avg_s,
+            &r.max_drawdown,
+            &r.rejected_signals,
+        ]);
+        let id: i64 = tx
+            .query_one(INSERT_BACKTEST, &values)
+            .await
+            .map_err(Error::query)?
+            .get(0);
+        report.backtests += 1;
+
+        for pair in &backtest.pairs {
+            tx.execute(
+                INSERT_PAIR,
+                &[
+                    &id,
+                    &pair.key,
+                    &pair.trades,
+                    &pair.profit_mean,
+                    &pair.profit_total,
+                    &pair.profit_total_abs,
+                    &pair.wins,
+                    &pair.draws,
+                    &pair.losses,
+                    &pair.duration_avg,
+                ],
+            )
+            .await
+            .map_err(Error::query)?;
+            report.pairs += 1;
+        }
+
+        for trade in &backtest.trades {
+            tx.execute(
+                INSERT_TRADE,
+                &[
+                    &id,
+                    &trade.pair,
+                    &trade.is_short,
+                    &trade.open_date(),
+                    &trade.close_date(),
+                    &trade.open_rate,
+                    &trade.close_rate,
+                    &trade.amount,
+                    &trade.stake_amount,
+                    &trade.profit_ratio,
+                    &trade.profit_abs,
+                    &trade.trade_duration,
+                    &trade.exit_reason,
+                    &trade.enter_tag,
+                ],
+            )
+            .await
+            .map_err(Error::query)?;
+            report.trades += 1;
+        }
+    }
+
+    tx.commit().await.map_err(Error::query)?;
+    Ok(report)
+}
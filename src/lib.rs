@@ -1,7 +1,14 @@
 pub mod backtest;
 pub mod config;
 pub mod db;
+pub mod error;
+pub mod import;
+pub mod presets;
+pub mod schema;
+pub mod source;
 pub mod utils;
 
+pub use error::{Error, Result};
+
 #[cfg(feature = "gui")]
 pub mod gui;
\ No newline at end of file
@@ -0,0 +1,271 @@
+// src/presets.rs
+//
+// Presets con nombre: una consulta (`GridQuery`), el estado de los filtros y
+// el orden de los resultados. Se guardan juntos en un JSON
+// (`$XDG_CONFIG_HOME/freqdash/presets.json` o `presets_file`) y se cargan
+// desde la barra lateral de la GUI o con `--preset` en la CLI.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backtest::fields::{Metric, TextField};
+use crate::backtest::filter::Filter;
+use crate::backtest::model::{GridQuery, StrategyGridRow};
+use crate::config::{self, FilterThresholds};
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub query: GridQuery,
+    #[serde(default)]
+    pub filters: FilterState,
+    #[serde(default)]
+    pub sort: SortOrder,
+}
+
+/// Búsqueda por nombre, expresión de filtro y filtros rápidos activos (por
+/// su nombre en `FilterThresholds::switches`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterState {
+    pub search: String,
+    pub expression: String,
+    pub switches: Vec<String>,
+}
+
+impl FilterState {
+    /// Filtros que aplica este estado: los rápidos activos con los umbrales
+    /// de `thresholds`, la expresión y la búsqueda (`strategy ~ ...`).
+    pub fn filters(&self, thresholds: &FilterThresholds) -> Result<Vec<Filter>> {
+        let switches = thresholds.switches();
+        let mut sources = Vec::new();
+        for name in &self.switches {
+            let (_, source) = switches
+                .iter()
+                .find(|(switch, _)| switch == name)
+                .ok_or_else(|| Error::Preset {
+                    name: name.clone(),
+                    reason: "filtro rápido desconocido".to_string(),
+                })?;
+            sources.push(source.clone());
+        }
+        if !self.expression.trim().is_empty() {
+            sources.push(self.expression.clone());
+        }
+        let search = self.search.trim();
+        if !search.is_empty() {
+            let quote = if search.contains('"') { '\'' } else { '"' };
+            sources.push(format!("strategy ~ {}{}{}", quote, search, quote));
+        }
+        sources.iter().map(|source| source.parse()).collect()
+    }
+}
+
+/// Columna por la que se ordenan los resultados: `score` (la puntuación), una
+/// métrica o un campo de texto de `fields`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SortOrder {
+    pub column: String,
+    pub descending: bool,
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        Self {
+            column: "score".to_string(),
+            descending: true,
+        }
+    }
+}
+
+impl SortOrder {
+    /// Ordena `rows` de forma estable; las filas sin valor van al final.
+    pub fn apply(&self, rows: &mut [StrategyGridRow]) -> Result<()> {
+        let by_number = |read: &dyn Fn(&StrategyGridRow) -> Option<f64>,
+                         rows: &mut [StrategyGridRow]| {
+            rows.sort_by(|a, b| match (read(a), read(b)) {
+                (Some(a), Some(b)) => {
+                    let order = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+                    if self.descending {
+                        order.reverse()
+                    } else {
+                        order
+                    }
+                }
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
+        };
+
+        if self.column.eq_ignore_ascii_case("score") {
+            by_number(&|row| row.score, rows);
+        } else if let Some(metric) = Metric::get(&self.column) {
+            by_number(&|row| metric.value(row), rows);
+        } else if let Some(field) = TextField::get(&self.column) {
+            rows.sort_by(|a, b| {
+                let order = field.value(a).cmp(&field.value(b));
+                if self.descending {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
+        } else {
+            return Err(Error::Preset {
+                name: self.column.clone(),
+                reason: "columna de orden desconocida".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Fichero de presets: `presets_file` o `freqdash/presets.json` en el
+/// directorio de configuración del usuario.
+pub fn path() -> Result<PathBuf> {
+    if let Some(path) = &config::settings()?.presets_file {
+        return Ok(path.clone());
+    }
+    config::config_dir()
+        .map(|dir| dir.join("freqdash").join("presets.json"))
+        .ok_or_else(|| Error::Config("no se encontró el directorio de configuración".into()))
+}
+
+/// Todos los presets, por nombre. Sin fichero no hay presets.
+pub fn load_all() -> Result<BTreeMap<String, Preset>> {
+    read(&path()?)
+}
+
+pub fn load(name: &str) -> Result<Preset> {
+    let mut presets = load_all()?;
+    presets.remove(name).ok_or_else(|| Error::Preset {
+        name: name.to_string(),
+        reason: if presets.is_empty() {
+            "no hay presets guardados".to_string()
+        } else {
+            format!(
+                "no existe (disponibles: {})",
+                presets.keys().cloned().collect::<Vec<_>>().join(", ")
+            )
+        },
+    })
+}
+
+/// Guarda o reemplaza `name`.
+pub fn save(name: &str, preset: &Preset) -> Result<()> {
+    let path = path()?;
+    let mut presets = read(&path)?;
+    presets.insert(name.to_string(), preset.clone());
+    write(&path, &presets)
+}
+
+/// Borra `name`; devuelve si existía.
+pub fn delete(name: &str) -> Result<bool> {
+    let path = path()?;
+    let mut presets = read(&path)?;
+    let existed = presets.remove(name).is_some();
+    if existed {
+        write(&path, &presets)?;
+    }
+    Ok(existed)
+}
+
+fn read(path: &Path) -> Result<BTreeMap<String, Preset>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let text = fs::read_to_string(path)
+        .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+    serde_json::from_str(&text).map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))
+}
+
+fn write(path: &Path, presets: &BTreeMap<String, Preset>) -> Result<()> {
+    let display = path.display().to_string();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| Error::export(&display, e))?;
+    }
+    let text = serde_json::to_string_pretty(presets).map_err(|e| Error::export(&display, e))?;
+    fs::write(path, text).map_err(|e| Error::export(&display, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(strategy: &str, total_profit: f64, score: Option<f64>) -> StrategyGridRow {
+        StrategyGridRow {
+            strategy: strategy.to_string(),
+            total_profit,
+            score,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn presets_round_trip_through_the_file() {
+        let path = std::env::temp_dir()
+            .join(format!("freqdash-presets-{}", std::process::id()))
+            .join("presets.json");
+        assert!(read(&path).unwrap().is_empty());
+
+        let preset = Preset {
+            query: config::QueryDefaults::default().grid_query(),
+            filters: FilterState {
+                search: "trend".to_string(),
+                expression: "trades >= 200".to_string(),
+                switches: vec!["profit".to_string()],
+            },
+            sort: SortOrder {
+                column: "sharpe".to_string(),
+                descending: true,
+            },
+        };
+        let presets = BTreeMap::from([("semanal".to_string(), preset.clone())]);
+        write(&path, &presets).unwrap();
+        assert_eq!(read(&path).unwrap(), presets);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        let filters = preset
+            .filters
+            .filters(&FilterThresholds::default())
+            .unwrap();
+        assert_eq!(filters.len(), 3);
+        assert_eq!(filters[2].to_string(), "strategy ~ \"trend\"");
+        let unknown = FilterState {
+            switches: vec!["volumen".to_string()],
+            ..Default::default()
+        };
+        assert!(unknown.filters(&FilterThresholds::default()).is_err());
+    }
+
+    #[test]
+    fn sort_order_puts_missing_values_last() {
+        let mut rows = vec![
+            row("A", 0.1, None),
+            row("B", 0.3, Some(1.0)),
+            row("C", 0.2, Some(2.0)),
+        ];
+        SortOrder::default().apply(&mut rows).unwrap();
+        let order: Vec<&str> = rows.iter().map(|r| r.strategy.as_str()).collect();
+        assert_eq!(order, vec!["C", "B", "A"]);
+
+        let ascending = SortOrder {
+            column: "profit".to_string(),
+            descending: false,
+        };
+        ascending.apply(&mut rows).unwrap();
+        let order: Vec<&str> = rows.iter().map(|r| r.strategy.as_str()).collect();
+        assert_eq!(order, vec!["A", "C", "B"]);
+
+        let unknown = SortOrder {
+            column: "beneficio".to_string(),
+            descending: true,
+        };
+        assert!(unknown.apply(&mut rows).is_err());
+    }
+}
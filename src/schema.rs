@@ -0,0 +1,367 @@
+// src/schema.rs
+
+use deadpool_postgres::Client;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Migración embebida en el binario.
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// Migraciones en orden de aplicación. Nunca se editan una vez publicadas:
+/// los cambios van en una versión nueva.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "backtest",
+        sql: include_str!("../migrations/V1__backtest.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "backtest_details",
+        sql: include_str!("../migrations/V2__backtest_details.sql"),
+    },
+];
+
+/// Columnas de `backtest` que lee freqdash y el `udt_name` que esperan los
+/// `row.get` de `source::postgres`.
+const EXPECTED_COLUMNS: &[(&str, &str)] = &[
+    ("exchange", "text"),
+    ("stake_currency", "text"),
+    ("pairlist", "text"),
+    ("timerange", "text"),
+    ("strategy", "text"),
+    ("timeframe", "text"),
+    ("minimal_roi", "text"),
+    ("stoploss", "text"),
+    ("max_open_trades", "int4"),
+    ("trailing_stop", "bool"),
+    ("trailing_stop_positive", "float8"),
+    ("trailing_stop_positive_offset", "float8"),
+    ("trailing_only_offset_is_reached", "bool"),
+    ("entry_pricing", "text"),
+    ("exit_pricing", "text"),
+    ("check_depth_of_market_enable", "bool"),
+    ("profit_total", "float8"),
+    ("total_trades", "int4"),
+    ("wins", "int4"),
+    ("winner_holding_avg_s", "float8"),
+    ("max_drawdown", "float8"),
+    ("rejected_signals", "int4"),
+];
+
+/// Problema encontrado en una columna de `backtest`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnProblem {
+    Missing {
+        column: &'static str,
+        expected: &'static str,
+    },
+    Mistyped {
+        column: &'static str,
+        expected: &'static str,
+        found: String,
+    },
+}
+
+impl fmt::Display for ColumnProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnProblem::Missing { column, expected } => {
+                write!(f, "falta la columna '{}' ({})", column, expected)
+            }
+            ColumnProblem::Mistyped {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "la columna '{}' es {} y se esperaba {}",
+                column, found, expected
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SchemaError {
+    /// La tabla `backtest` no existe; ejecute `cli migrate`.
+    MissingTable(&'static str),
+    /// La tabla existe pero no coincide con la definición de `migrations/`.
+    Columns(Vec<ColumnProblem>),
+    /// `backtest` no tiene el `id` entero y único al que apuntan las tablas
+    /// de detalle; el texto dice qué le falta.
+    MissingId(String),
+    Database(tokio_postgres::Error),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::MissingTable(table) => {
+                write!(f, "no existe la tabla '{}' (ejecute `cli migrate`)", table)
+            }
+            SchemaError::Columns(problems) => {
+                let details = problems
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                write!(
+                    f,
+                    "la tabla 'backtest' no coincide con el esquema: {}",
+                    details
+                )
+            }
+            SchemaError::MissingId(problem) => write!(
+                f,
+                "la tabla 'backtest' {}; el detalle por par y trade lo necesita \
+                 (ejecute `cli migrate`, que lo añade, o créelo con \
+                 `ALTER TABLE backtest ADD COLUMN id BIGSERIAL PRIMARY KEY`)",
+                problem
+            ),
+            SchemaError::Database(e) => write!(f, "error consultando el esquema: {}", e),
+        }
+    }
+}
+
+impl Error for SchemaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SchemaError::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<tokio_postgres::Error> for SchemaError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        SchemaError::Database(e)
+    }
+}
+
+/// Aplica las migraciones pendientes, cada una en su transacción, y devuelve
+/// las versiones aplicadas.
+pub async fn migrate(client: &mut Client) -> Result<Vec<i32>, SchemaError> {
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS freqdash_migrations (
+                version    INTEGER PRIMARY KEY,
+                name       TEXT NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            )",
+        )
+        .await?;
+
+    let applied: Vec<i32> = client
+        .query("SELECT version FROM freqdash_migrations", &[])
+        .await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+
+    let mut newly_applied = Vec::new();
+    for migration in MIGRATIONS {
+        if applied.contains(&migration.version) {
+            continue;
+        }
+        let tx = client.transaction().await?;
+        tx.batch_execute(migration.sql).await?;
+        tx.execute(
+            "INSERT INTO freqdash_migrations (version, name) VALUES ($1, $2)",
+            &[&migration.version, &migration.name],
+        )
+        .await?;
+        tx.commit().await?;
+        newly_applied.push(migration.version);
+    }
+
+    Ok(newly_applied)
+}
+
+/// Comprueba que `backtest` tenga las columnas y tipos que lee freqdash.
+/// Se llama antes de la primera consulta para no fallar dentro de `Row::get`.
+pub async fn verify(client: &Client) -> Result<(), SchemaError> {
+    let rows = client
+        .query(
+            "SELECT column_name::text, udt_name::text
+             FROM information_schema.columns
+             WHERE table_schema = current_schema() AND table_name = 'backtest'",
+            &[],
+        )
+        .await?;
+
+    if rows.is_empty() {
+        return Err(SchemaError::MissingTable("backtest"));
+    }
+
+    let found: HashMap<String, String> = rows.iter().map(|r| (r.get(0), r.get(1))).collect();
+    let problems = compare_columns(&found);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaError::Columns(problems))
+    }
+}
+
+/// Comprueba que `backtest` tenga un `id` entero y único, al que apuntan
+/// `backtest_pair` y `backtest_trade`. Sin él se puede consultar (el detalle
+/// cae al mensual) pero no migrar a V2 ni importar.
+pub async fn verify_id(client: &Client) -> Result<(), SchemaError> {
+    let rows = client
+        .query(
+            "SELECT c.udt_name::text, EXISTS (
+                 SELECT 1
+                 FROM pg_index i
+                 JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = i.indkey[0]
+                 WHERE i.indrelid = 'backtest'::regclass
+                   AND i.indisunique AND i.indnatts = 1 AND i.indpred IS NULL
+                   AND a.attname = 'id'
+             )
+             FROM information_schema.columns c
+             WHERE c.table_schema = current_schema() AND c.table_name = 'backtest'
+               AND c.column_name = 'id'",
+            &[],
+        )
+        .await?;
+    let found = rows.first().map(|row| (row.get::<_, String>(0), row.get(1)));
+    match id_problem(found.as_ref().map(|(udt, unique)| (udt.as_str(), *unique))) {
+        Some(problem) => Err(SchemaError::MissingId(problem)),
+        None => Ok(()),
+    }
+}
+
+/// `found` es el `udt_name` de `id` y si tiene un índice único propio.
+fn id_problem(found: Option<(&str, bool)>) -> Option<String> {
+    match found {
+        None => Some("no tiene columna 'id'".to_string()),
+        Some((udt, _)) if !matches!(udt, "int2" | "int4" | "int8") => {
+            Some(format!("tiene un 'id' {} y se esperaba un entero", udt))
+        }
+        Some((_, false)) => Some("tiene un 'id' que no es único ni clave primaria".to_string()),
+        Some(_) => None,
+    }
+}
+
+fn compare_columns(found: &HashMap<String, String>) -> Vec<ColumnProblem> {
+    EXPECTED_COLUMNS
+        .iter()
+        .filter_map(|&(column, expected)| match found.get(column) {
+            None => Some(ColumnProblem::Missing { column, expected }),
+            Some(udt) if !is_compatible(expected, udt) => Some(ColumnProblem::Mistyped {
+                column,
+                expected,
+                found: udt.clone(),
+            }),
+            Some(_) => None,
+        })
+        .collect()
+}
+
+/// `String` de tokio-postgres también acepta `varchar` y `bpchar`.
+fn is_compatible(expected: &str, found: &str) -> bool {
+    expected == found || (expected == "text" && matches!(found, "varchar" | "bpchar"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_and_mistyped_columns() {
+        let mut found: HashMap<String, String> = EXPECTED_COLUMNS
+            .iter()
+            .map(|(c, t)| (c.to_string(), t.to_string()))
+            .collect();
+        found.insert("pairlist".to_string(), "varchar".to_string());
+        found.insert("total_trades".to_string(), "int8".to_string());
+        found.remove("rejected_signals");
+
+        assert_eq!(
+            compare_columns(&found),
+            vec![
+                ColumnProblem::Mistyped {
+                    column: "total_trades",
+                    expected: "int4",
+                    found: "int8".to_string(),
+                },
+                ColumnProblem::Missing {
+                    column: "rejected_signals",
+                    expected: "int4",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn id_must_be_a_unique_integer() {
+        assert_eq!(id_problem(Some(("int8", true))), None);
+        assert_eq!(id_problem(Some(("int4", true))), None);
+        assert!(id_problem(None).unwrap().contains("no tiene columna 'id'"));
+        assert!(id_problem(Some(("text", true))).unwrap().contains("entero"));
+        assert!(id_problem(Some(("int8", false))).unwrap().contains("no es único"));
+    }
+
+    /// Una tabla `backtest` creada fuera de freqdash, sin `id`, queda lista
+    /// para el detalle tras `migrate`. Usa un esquema temporal que se borra
+    /// al final. Ejecutar con `DATABASE_URL=... cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "requiere una base de datos Postgres"]
+    async fn migrate_adds_id_to_an_external_table() {
+        use deadpool_postgres::{Config, Runtime};
+        use tokio_postgres::NoTls;
+
+        dotenvy::dotenv().ok();
+        let mut cfg = Config::new();
+        cfg.url = Some(std::env::var("DATABASE_URL").expect("DATABASE_URL"));
+        let pool = cfg.create_pool(Some(Runtime::Tokio1), NoTls).unwrap();
+        let mut client = pool.get().await.unwrap();
+
+        let columns = EXPECTED_COLUMNS
+            .iter()
+            .map(|(column, udt)| format!("{} {}", column, udt))
+            .collect::<Vec<_>>()
+            .join(", ");
+        client
+            .batch_execute(&format!(
+                "DROP SCHEMA IF EXISTS freqdash_test_external CASCADE;
+                 CREATE SCHEMA freqdash_test_external;
+                 SET search_path TO freqdash_test_external;
+                 CREATE TABLE backtest ({}, PRIMARY KEY (exchange, timerange, strategy));
+                 INSERT INTO backtest (exchange, timerange, strategy)
+                     VALUES ('BINANCE', '20240101-20240201', 'A'),
+                            ('BINANCE', '20240201-20240301', 'A');",
+                columns
+            ))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            verify_id(&client).await,
+            Err(SchemaError::MissingId(_))
+        ));
+        let result = migrate(&mut client).await;
+        let verified = verify_id(&client).await;
+        let ids: Vec<i64> = client
+            .query("SELECT id FROM backtest ORDER BY id", &[])
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get(0))
+            .collect();
+        client
+            .batch_execute(
+                "RESET search_path; DROP SCHEMA freqdash_test_external CASCADE;",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.unwrap(), vec![1, 2]);
+        verified.unwrap();
+        assert_eq!(ids, vec![1, 2]);
+    }
+}
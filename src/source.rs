@@ -0,0 +1,284 @@
+// src/source.rs
+
+pub mod file;
+pub mod postgres;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::backtest::aggregate::{strategy_key, summarize_records, StrategyKey};
+use crate::backtest::drilldown::StrategyDetail;
+use crate::backtest::model::{BacktestRecord, GridQuery, StrategyGridRow};
+use crate::config::mask_password;
+use crate::error::{Error, Result};
+
+pub use file::FileSource;
+pub use postgres::PostgresSource;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteSource;
+
+/// Origen de las filas mensuales que consume la lógica de agrupación.
+pub trait BacktestSource: Sync {
+    /// Devuelve las filas de los mercados de `params` (cualquier combinación
+    /// de `exchanges`, `currencies` y `pairlists`) cuyo `timerange` esté en
+    /// `timeranges`.
+    fn fetch(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+    ) -> impl Future<Output = Result<Vec<BacktestRecord>>> + Send;
+
+    /// Como `fetch`, pero entrega las filas a `sink` a medida que se leen.
+    /// Por defecto las lee todas con `fetch` y después las entrega.
+    fn fetch_each(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+        sink: &mut dyn RecordSink,
+    ) -> impl Future<Output = Result<()>> + Send {
+        async move {
+            let records = self.fetch(params, timeranges).await?;
+            sink.expect(records.len());
+            for record in records {
+                sink.push(record);
+            }
+            Ok(())
+        }
+    }
+
+    /// Número de filas que devolvería `fetch`, si la fuente sabe contarlas
+    /// sin leerlas. Permite informar del avance de las lecturas por tramos.
+    fn count(
+        &self,
+        _params: &GridQuery,
+        _timeranges: &[String],
+    ) -> impl Future<Output = Result<Option<usize>>> + Send {
+        async { Ok(None) }
+    }
+
+    /// Consultas que la fuente puede atender a la vez; con más de una,
+    /// `get_grid_summary` lee las ventanas largas por tramos en paralelo.
+    fn concurrency(&self) -> usize {
+        1
+    }
+
+    /// Devuelve los `timerange` distintos que tienen filas en alguno de los
+    /// mercados de `params`, en cualquier orden.
+    fn available_timeranges(
+        &self,
+        params: &GridQuery,
+    ) -> impl Future<Output = Result<Vec<String>>> + Send;
+
+    /// Devuelve una fila por configuración y mercado. Las fuentes que no saben agregar
+    /// por su cuenta agrupan en Rust.
+    fn fetch_grouped(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+    ) -> impl Future<Output = Result<Vec<StrategyGridRow>>> + Send {
+        async move {
+            let records = self.fetch(params, timeranges).await?;
+            Ok(summarize_records(records, params, timeranges))
+        }
+    }
+
+    /// Filas mensuales de la configuración `key` en los mercados de `params`
+    /// y periodos `timeranges`, con sus resultados por par y sus trades si
+    /// la fuente los guarda. Por defecto solo las mensuales, leídas con
+    /// `fetch`.
+    fn fetch_detail(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+        key: &StrategyKey,
+    ) -> impl Future<Output = Result<StrategyDetail>> + Send {
+        async move {
+            let months = self
+                .fetch(params, timeranges)
+                .await?
+                .into_iter()
+                .filter(|record| strategy_key(record) == *key)
+                .collect();
+            Ok(StrategyDetail::new(key.clone(), timeranges.to_vec(), months))
+        }
+    }
+
+    /// Marca de agua de los datos: cambia cuando se insertan, borran o
+    /// modifican filas. `None` si la fuente no sabe calcularla; sus
+    /// resúmenes no se guardan entonces en caché.
+    fn watermark(&self) -> impl Future<Output = Result<Option<String>>> + Send {
+        async { Ok(None) }
+    }
+}
+
+/// Destino de las filas de `BacktestSource::fetch_each`.
+pub trait RecordSink: Send {
+    /// Número de filas que se van a entregar, si la fuente lo sabe.
+    fn expect(&mut self, _rows: usize) {}
+
+    fn push(&mut self, record: BacktestRecord);
+}
+
+/// Fuente elegida a partir de la URL configurada en `DATABASE_URL`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceConfig {
+    /// `postgres://...` o `postgresql://...`
+    Postgres(String),
+    /// `sqlite://ruta/al/fichero.db`
+    Sqlite(PathBuf),
+    /// `file://ruta/al/volcado.parquet` o `.csv`
+    File(PathBuf),
+}
+
+impl SourceConfig {
+    pub fn from_url(url: &str) -> Result<Self> {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            Ok(SourceConfig::Postgres(url.to_string()))
+        } else if let Some(path) = url.strip_prefix("sqlite://") {
+            Ok(SourceConfig::Sqlite(PathBuf::from(path)))
+        } else if let Some(path) = url.strip_prefix("file://") {
+            Ok(SourceConfig::File(PathBuf::from(path)))
+        } else {
+            Err(Error::Config(format!(
+                "URL de origen no soportada: {}",
+                mask_password(url)
+            )))
+        }
+    }
+}
+
+/// Fuente concreta seleccionada por configuración.
+pub enum Source {
+    Postgres(PostgresSource),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteSource),
+    File(FileSource),
+}
+
+impl BacktestSource for Source {
+    async fn fetch(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+    ) -> Result<Vec<BacktestRecord>> {
+        match self {
+            Source::Postgres(source) => source.fetch(params, timeranges).await,
+            #[cfg(feature = "sqlite")]
+            Source::Sqlite(source) => source.fetch(params, timeranges).await,
+            Source::File(source) => source.fetch(params, timeranges).await,
+        }
+    }
+
+    async fn fetch_each(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+        sink: &mut dyn RecordSink,
+    ) -> Result<()> {
+        match self {
+            Source::Postgres(source) => source.fetch_each(params, timeranges, sink).await,
+            #[cfg(feature = "sqlite")]
+            Source::Sqlite(source) => source.fetch_each(params, timeranges, sink).await,
+            Source::File(source) => source.fetch_each(params, timeranges, sink).await,
+        }
+    }
+
+    async fn count(&self, params: &GridQuery, timeranges: &[String]) -> Result<Option<usize>> {
+        match self {
+            Source::Postgres(source) => source.count(params, timeranges).await,
+            #[cfg(feature = "sqlite")]
+            Source::Sqlite(source) => source.count(params, timeranges).await,
+            Source::File(source) => source.count(params, timeranges).await,
+        }
+    }
+
+    fn concurrency(&self) -> usize {
+        match self {
+            Source::Postgres(source) => source.concurrency(),
+            #[cfg(feature = "sqlite")]
+            Source::Sqlite(source) => source.concurrency(),
+            Source::File(source) => source.concurrency(),
+        }
+    }
+
+    async fn available_timeranges(&self, params: &GridQuery) -> Result<Vec<String>> {
+        match self {
+            Source::Postgres(source) => source.available_timeranges(params).await,
+            #[cfg(feature = "sqlite")]
+            Source::Sqlite(source) => source.available_timeranges(params).await,
+            Source::File(source) => source.available_timeranges(params).await,
+        }
+    }
+
+    async fn fetch_grouped(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+    ) -> Result<Vec<StrategyGridRow>> {
+        match self {
+            Source::Postgres(source) => source.fetch_grouped(params, timeranges).await,
+            #[cfg(feature = "sqlite")]
+            Source::Sqlite(source) => source.fetch_grouped(params, timeranges).await,
+            Source::File(source) => source.fetch_grouped(params, timeranges).await,
+        }
+    }
+
+    async fn fetch_detail(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+        key: &StrategyKey,
+    ) -> Result<StrategyDetail> {
+        match self {
+            Source::Postgres(source) => source.fetch_detail(params, timeranges, key).await,
+            #[cfg(feature = "sqlite")]
+            Source::Sqlite(source) => source.fetch_detail(params, timeranges, key).await,
+            Source::File(source) => source.fetch_detail(params, timeranges, key).await,
+        }
+    }
+
+    async fn watermark(&self) -> Result<Option<String>> {
+        match self {
+            Source::Postgres(source) => source.watermark().await,
+            #[cfg(feature = "sqlite")]
+            Source::Sqlite(source) => source.watermark().await,
+            Source::File(source) => source.watermark().await,
+        }
+    }
+}
+
+/// Marca de agua de las fuentes de fichero: tamaño y fecha de modificación.
+pub(crate) fn file_watermark(path: &Path) -> Result<String> {
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| Error::Query(format!("{}: {}", path.display(), e).into()))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    Ok(format!("{}:{}", metadata.len(), modified))
+}
+
+/// Indica si una fila pertenece al mercado y periodo pedidos; sin
+/// `timeranges` vale cualquier periodo. Lo usan las fuentes que filtran en
+/// Rust en lugar de en SQL.
+pub(crate) fn matches_query(
+    params: &GridQuery,
+    timeranges: Option<&[String]>,
+    exchange: &str,
+    currency: &str,
+    pairlist: &str,
+    timerange: &str,
+) -> bool {
+    let contains = |values: &[String], value: &str| {
+        values.iter().any(|v| v.eq_ignore_ascii_case(value))
+    };
+    contains(&params.exchanges, exchange)
+        && contains(&params.currencies, currency)
+        && contains(&params.pairlists, pairlist)
+        && timeranges.is_none_or(|timeranges| timeranges.iter().any(|t| t == timerange))
+}
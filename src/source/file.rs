@@ -0,0 +1,277 @@
+// src/source/file.rs
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::backtest::model::{BacktestRecord, GridQuery};
+use crate::error::{BoxError, Error, Result};
+use crate::source::{file_watermark, matches_query, BacktestSource};
+
+/// Lee volcados de la tabla `backtest` en CSV o Parquet (por ejemplo, los que
+/// genera `COPY backtest TO 'backtest.parquet'` en DuckDB).
+///
+/// Los ficheros deben tener las columnas de la tabla con los mismos nombres,
+/// incluidas `exchange`, `stake_currency` y `pairlist`.
+#[derive(Debug, Clone)]
+pub struct FileSource {
+    path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl BacktestSource for FileSource {
+    async fn fetch(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+    ) -> Result<Vec<BacktestRecord>> {
+        let path = self.path.clone();
+        let params = params.clone();
+        let timeranges = timeranges.to_vec();
+
+        tokio::task::spawn_blocking(move || read_records(&path, &params, Some(&timeranges)))
+            .await
+            .map_err(Error::query)?
+            .map_err(Error::Query)
+    }
+
+    /// El volcado no tiene índices: se lee entero y se quedan los periodos.
+    async fn available_timeranges(&self, params: &GridQuery) -> Result<Vec<String>> {
+        let path = self.path.clone();
+        let params = params.clone();
+
+        let records = tokio::task::spawn_blocking(move || read_records(&path, &params, None))
+            .await
+            .map_err(Error::query)?
+            .map_err(Error::Query)?;
+        let timeranges: BTreeSet<String> =
+            records.into_iter().map(|record| record.timerange).collect();
+        Ok(timeranges.into_iter().collect())
+    }
+
+    async fn watermark(&self) -> Result<Option<String>> {
+        file_watermark(&self.path).map(Some)
+    }
+}
+
+fn read_records(
+    path: &Path,
+    params: &GridQuery,
+    timeranges: Option<&[String]>,
+) -> std::result::Result<Vec<BacktestRecord>, BoxError> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => read_csv(path, params, timeranges),
+        #[cfg(feature = "parquet")]
+        Some("parquet") => columnar::read_parquet(path, params, timeranges),
+        _ => Err(format!("formato de fichero no soportado: {}", path.display()).into()),
+    }
+}
+
+fn read_csv(
+    path: &Path,
+    params: &GridQuery,
+    timeranges: Option<&[String]>,
+) -> std::result::Result<Vec<BacktestRecord>, BoxError> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h == name)
+            .ok_or_else(|| format!("falta la columna '{}' en {}", name, path.display()))
+    };
+    let exchange = column("exchange")?;
+    let currency = column("stake_currency")?;
+    let pairlist = column("pairlist")?;
+    let timerange = column("timerange")?;
+
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let row = row?;
+        if matches_query(
+            params,
+            timeranges,
+            &row[exchange],
+            &row[currency],
+            &row[pairlist],
+            &row[timerange],
+        ) {
+            records.push(row.deserialize(Some(&headers))?);
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(feature = "parquet")]
+mod columnar {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+    use std::collections::HashMap;
+    use std::error::Error;
+    use std::fs::File;
+    use std::path::Path;
+
+    use crate::backtest::model::{BacktestRecord, GridQuery};
+    use crate::source::matches_query;
+
+    type Columns<'a> = HashMap<&'a str, &'a Field>;
+
+    pub(super) fn read_parquet(
+        path: &Path,
+        params: &GridQuery,
+        timeranges: Option<&[String]>,
+    ) -> Result<Vec<BacktestRecord>, Box<dyn Error + Send + Sync>> {
+        let reader = SerializedFileReader::new(File::open(path)?)?;
+
+        let mut records = Vec::new();
+        for row in reader.get_row_iter(None)? {
+            let row = row?;
+            let columns: Columns = row
+                .get_column_iter()
+                .map(|(name, field)| (name.as_str(), field))
+                .collect();
+
+            if !matches_query(
+                params,
+                timeranges,
+                &text(&columns, "exchange")?,
+                &text(&columns, "stake_currency")?,
+                &text(&columns, "pairlist")?,
+                &text(&columns, "timerange")?,
+            ) {
+                continue;
+            }
+
+            records.push(BacktestRecord {
+                strategy: text(&columns, "strategy")?,
+                timeframe: text(&columns, "timeframe")?,
+                minimal_roi: text(&columns, "minimal_roi")?,
+                stoploss: text(&columns, "stoploss")?,
+                max_open_trades: integer(&columns, "max_open_trades")?.unwrap_or(0),
+                trailing_stop: boolean(&columns, "trailing_stop")?,
+                trailing_stop_positive: float(&columns, "trailing_stop_positive")?.unwrap_or(0.0),
+                trailing_stop_positive_offset: float(&columns, "trailing_stop_positive_offset")?
+                    .unwrap_or(0.0),
+                trailing_only_offset_is_reached: boolean(
+                    &columns,
+                    "trailing_only_offset_is_reached",
+                )?,
+                entry_pricing: text(&columns, "entry_pricing")?,
+                exit_pricing: text(&columns, "exit_pricing")?,
+                check_depth_of_market_enable: boolean(&columns, "check_depth_of_market_enable")?,
+                profit_total: float(&columns, "profit_total")?.unwrap_or(0.0),
+                total_trades: integer(&columns, "total_trades")?.unwrap_or(0),
+                wins: integer(&columns, "wins")?.unwrap_or(0),
+                winner_holding_avg_s: float(&columns, "winner_holding_avg_s")?,
+                max_drawdown: float(&columns, "max_drawdown")?,
+                rejected_signals: integer(&columns, "rejected_signals")?,
+                timerange: text(&columns, "timerange")?,
+                exchange: text(&columns, "exchange")?,
+                stake_currency: text(&columns, "stake_currency")?,
+                pairlist: text(&columns, "pairlist")?,
+            });
+        }
+        Ok(records)
+    }
+
+    fn field<'a>(columns: &Columns<'a>, name: &str) -> Result<&'a Field, String> {
+        columns
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("falta la columna '{}'", name))
+    }
+
+    fn mismatch(name: &str, field: &Field) -> String {
+        format!("tipo inesperado en la columna '{}': {:?}", name, field)
+    }
+
+    fn text(columns: &Columns, name: &str) -> Result<String, String> {
+        match field(columns, name)? {
+            Field::Str(value) => Ok(value.clone()),
+            Field::Null => Ok(String::new()),
+            // `stoploss` puede venir como número en volcados de DuckDB.
+            Field::Double(value) => Ok(value.to_string()),
+            other => Err(mismatch(name, other)),
+        }
+    }
+
+    fn float(columns: &Columns, name: &str) -> Result<Option<f64>, String> {
+        match field(columns, name)? {
+            Field::Null => Ok(None),
+            Field::Double(value) => Ok(Some(*value)),
+            Field::Float(value) => Ok(Some(*value as f64)),
+            Field::Int(value) => Ok(Some(*value as f64)),
+            Field::Long(value) => Ok(Some(*value as f64)),
+            other => Err(mismatch(name, other)),
+        }
+    }
+
+    fn integer(columns: &Columns, name: &str) -> Result<Option<i32>, String> {
+        match field(columns, name)? {
+            Field::Null => Ok(None),
+            Field::Int(value) => Ok(Some(*value)),
+            Field::Long(value) => Ok(Some(*value as i32)),
+            Field::Short(value) => Ok(Some(*value as i32)),
+            other => Err(mismatch(name, other)),
+        }
+    }
+
+    fn boolean(columns: &Columns, name: &str) -> Result<bool, String> {
+        match field(columns, name)? {
+            Field::Null => Ok(false),
+            Field::Bool(value) => Ok(*value),
+            other => Err(mismatch(name, other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::model::AggregationMode;
+
+    #[tokio::test]
+    async fn csv_rows_are_filtered_by_market_and_timerange() {
+        let path = std::env::temp_dir().join(format!("freqdash-{}.csv", std::process::id()));
+        std::fs::write(
+            &path,
+            "strategy,timeframe,minimal_roi,stoploss,max_open_trades,trailing_stop,\
+             trailing_stop_positive,trailing_stop_positive_offset,\
+             trailing_only_offset_is_reached,entry_pricing,exit_pricing,\
+             check_depth_of_market_enable,profit_total,total_trades,wins,\
+             winner_holding_avg_s,max_drawdown,rejected_signals,timerange,\
+             exchange,stake_currency,pairlist\n\
+             A,5m,{},-0.1,3,false,0.01,0.02,false,same,same,false,0.05,10,6,,0.02,1,20240101-20240201,BINANCE,USDT,BTC\n\
+             A,5m,{},-0.1,3,false,0.01,0.02,false,same,same,false,0.07,12,7,60,0.03,,20240101-20240201,BINANCE,USDT,ETH\n",
+        )
+        .unwrap();
+
+        let query = GridQuery {
+            exchanges: vec!["BINANCE".to_string()],
+            currencies: vec!["USDT".to_string()],
+            pairlists: vec!["BTC".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 1,
+            aggregation: AggregationMode::Client,
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        };
+        let records = FileSource::new(&path)
+            .fetch(&query, &["20240101-20240201".to_string()])
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].profit_total, 0.05);
+        assert_eq!(records[0].winner_holding_avg_s, None);
+        assert_eq!(records[0].rejected_signals, Some(1));
+    }
+}
@@ -0,0 +1,380 @@
+// src/source/postgres.rs
+
+use deadpool_postgres::{Client, Pool};
+use futures_util::TryStreamExt;
+use std::pin::pin;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tokio_postgres::Row;
+
+use crate::backtest::aggregate::{strategy_key, summarize_grouped, GroupedRecord, StrategyKey};
+use crate::backtest::drilldown::{PairDetail, StrategyDetail, TradeDetail};
+use crate::backtest::model::{BacktestRecord, GridQuery, StrategyGridRow};
+use crate::backtest::sql::{
+    build_count_sql, build_detail_sql, build_flat_sql, build_grouped_sql, build_timeranges_sql,
+};
+use crate::error::{Error, Result};
+use crate::schema;
+use crate::source::{BacktestSource, RecordSink};
+
+/// Resultados por par de las filas `backtest_id = ANY($1)`, con el mercado
+/// y periodo de su fila mensual.
+const SELECT_PAIRS: &str = "SELECT b.exchange, b.stake_currency, b.pairlist, b.timerange,
+        p.pair, p.trades, p.profit_mean, p.profit_total, p.profit_total_abs,
+        p.wins, p.draws, p.losses, p.duration_avg
+    FROM backtest_pair p JOIN backtest b ON b.id = p.backtest_id
+    WHERE p.backtest_id = ANY($1)
+    ORDER BY b.exchange, b.stake_currency, b.pairlist, b.timerange, p.pair";
+
+const SELECT_TRADES: &str = "SELECT b.exchange, b.stake_currency, b.pairlist, b.timerange,
+        t.pair, t.is_short, t.open_date, t.close_date, t.open_rate, t.close_rate,
+        t.amount, t.stake_amount, t.profit_ratio, t.profit_abs, t.trade_duration,
+        t.exit_reason, t.enter_tag
+    FROM backtest_trade t JOIN backtest b ON b.id = t.backtest_id
+    WHERE t.backtest_id = ANY($1)
+    ORDER BY t.open_date, t.pair";
+
+/// Lee la tabla `backtest` de Postgres a través del pool de deadpool.
+#[derive(Clone)]
+pub struct PostgresSource {
+    pool: Pool,
+    /// El esquema se verifica una sola vez, antes de la primera consulta.
+    schema_checked: Arc<OnceCell<()>>,
+    /// Consulta de la marca de agua según las columnas de la tabla; `None`
+    /// si no tiene `id` ni `updated_at`.
+    watermark_sql: Arc<OnceCell<Option<String>>>,
+    /// Si `backtest` tiene un `id` entero con el que buscar pares y trades.
+    has_id: Arc<OnceCell<bool>>,
+}
+
+impl PostgresSource {
+    pub fn new(pool: Pool) -> Self {
+        Self {
+            pool,
+            schema_checked: Arc::new(OnceCell::new()),
+            watermark_sql: Arc::new(OnceCell::new()),
+            has_id: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Obtiene un cliente del pool comprobando antes que `backtest` tenga el
+    /// esquema esperado, para fallar con un `SchemaError` legible en lugar de
+    /// un pánico dentro de `Row::get`.
+    async fn client(&self) -> Result<Client> {
+        let client = self.pool.get().await.map_err(Error::connection)?;
+        self.schema_checked
+            .get_or_try_init(|| schema::verify(&client))
+            .await?;
+        Ok(client)
+    }
+}
+
+impl BacktestSource for PostgresSource {
+    async fn fetch(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+    ) -> Result<Vec<BacktestRecord>> {
+        let client = self.client().await?;
+        let query = build_flat_sql(params, timeranges);
+        let rows = client
+            .query(&query.sql, &query.params())
+            .await
+            .map_err(Error::query)?;
+        Ok(rows.iter().map(record_from_row).collect())
+    }
+
+    /// Cuenta antes las filas para poder informar del avance y después las
+    /// lee con `query_raw`, entregando cada una según llega.
+    async fn fetch_each(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+        sink: &mut dyn RecordSink,
+    ) -> Result<()> {
+        let client = self.client().await?;
+        sink.expect(count_rows(&client, params, timeranges).await?);
+
+        let query = build_flat_sql(params, timeranges);
+        let rows = client
+            .query_raw(&query.sql, query.params())
+            .await
+            .map_err(Error::query)?;
+        let mut rows = pin!(rows);
+        while let Some(row) = rows.try_next().await.map_err(Error::query)? {
+            sink.push(record_from_row(&row));
+        }
+        Ok(())
+    }
+
+    async fn count(&self, params: &GridQuery, timeranges: &[String]) -> Result<Option<usize>> {
+        let client = self.client().await?;
+        count_rows(&client, params, timeranges).await.map(Some)
+    }
+
+    /// Cada consulta usa su propia conexión del pool.
+    fn concurrency(&self) -> usize {
+        self.pool.status().max_size
+    }
+
+    async fn available_timeranges(&self, params: &GridQuery) -> Result<Vec<String>> {
+        let client = self.client().await?;
+        let query = build_timeranges_sql(params);
+        let rows = client
+            .query(&query.sql, &query.params())
+            .await
+            .map_err(Error::query)?;
+        Ok(rows.iter().map(|row| row.get("timerange")).collect())
+    }
+
+    /// Postgres hace el `GROUP BY`; solo viaja una fila por configuración.
+    async fn fetch_grouped(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+    ) -> Result<Vec<StrategyGridRow>> {
+        let client = self.client().await?;
+        let query = build_grouped_sql(params, timeranges);
+        let rows = client
+            .query(&query.sql, &query.params())
+            .await
+            .map_err(Error::query)?;
+        let records = rows.iter().map(grouped_from_row).collect();
+        Ok(summarize_grouped(records, params, timeranges))
+    }
+
+    /// Las filas mensuales se leen con su `id` y, si la base de datos tiene
+    /// las tablas de la migración V2, con sus pares y trades. Las tablas sin
+    /// `id` entero solo dan las filas mensuales.
+    async fn fetch_detail(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+        key: &StrategyKey,
+    ) -> Result<StrategyDetail> {
+        let client = self.client().await?;
+        if !*self.has_id.get_or_try_init(|| has_integer_id(&client)).await? {
+            let months = self
+                .fetch(params, timeranges)
+                .await?
+                .into_iter()
+                .filter(|record| strategy_key(record) == *key)
+                .collect();
+            return Ok(StrategyDetail::new(key.clone(), timeranges.to_vec(), months));
+        }
+
+        let query = build_detail_sql(params, timeranges, &key.0);
+        let rows = client
+            .query(&query.sql, &query.params())
+            .await
+            .map_err(Error::query)?;
+        let mut ids = Vec::new();
+        let mut months = Vec::new();
+        for row in &rows {
+            let record = record_from_row(row);
+            if strategy_key(&record) == *key {
+                ids.push(row.try_get::<_, i64>("id").map_err(Error::query)?);
+                months.push(record);
+            }
+        }
+        let mut detail = StrategyDetail::new(key.clone(), timeranges.to_vec(), months);
+
+        let tables = client
+            .query_one(
+                "SELECT to_regclass('backtest_pair') IS NOT NULL, \
+                 to_regclass('backtest_trade') IS NOT NULL",
+                &[],
+            )
+            .await
+            .map_err(Error::query)?;
+        if tables.get(0) {
+            let rows = client
+                .query(SELECT_PAIRS, &[&ids])
+                .await
+                .map_err(Error::query)?;
+            detail.pairs = Some(rows.iter().map(pair_from_row).collect());
+        }
+        if tables.get(1) {
+            let rows = client
+                .query(SELECT_TRADES, &[&ids])
+                .await
+                .map_err(Error::query)?;
+            detail.trades = Some(rows.iter().map(trade_from_row).collect());
+        }
+        Ok(detail)
+    }
+
+    /// `COUNT(*)` detecta borrados, `MAX(id)` inserciones y `MAX(updated_at)`
+    /// las modificaciones que renuevan esa columna. Las tablas creadas sin
+    /// las migraciones pueden no tener alguna de las dos.
+    async fn watermark(&self) -> Result<Option<String>> {
+        let client = self.client().await?;
+        let sql = self
+            .watermark_sql
+            .get_or_try_init(|| watermark_sql(&client))
+            .await?;
+        let Some(sql) = sql else {
+            return Ok(None);
+        };
+        let row = client.query_one(sql, &[]).await.map_err(Error::query)?;
+        Ok(Some(row.get(0)))
+    }
+}
+
+/// Filas mensuales que leería `build_flat_sql` con los mismos parámetros.
+async fn count_rows(client: &Client, params: &GridQuery, timeranges: &[String]) -> Result<usize> {
+    let count = build_count_sql(params, timeranges);
+    let total: i64 = client
+        .query_one(&count.sql, &count.params())
+        .await
+        .map_err(Error::query)?
+        .get(0);
+    Ok(total as usize)
+}
+
+/// `id` de `backtest` con un tipo entero que `build_detail_sql` pueda leer
+/// como `int8`; las tablas creadas sin las migraciones pueden no tenerlo.
+async fn has_integer_id(client: &Client) -> Result<bool> {
+    let row = client
+        .query_one(
+            "SELECT EXISTS (SELECT 1 FROM information_schema.columns \
+             WHERE table_schema = current_schema() AND table_name = 'backtest' \
+             AND column_name = 'id' AND udt_name IN ('int2', 'int4', 'int8'))",
+            &[],
+        )
+        .await
+        .map_err(Error::query)?;
+    Ok(row.get(0))
+}
+
+async fn watermark_sql(client: &Client) -> Result<Option<String>> {
+    let rows = client
+        .query(
+            "SELECT column_name::text FROM information_schema.columns \
+             WHERE table_schema = current_schema() AND table_name = 'backtest' \
+             AND column_name IN ('id', 'updated_at')",
+            &[],
+        )
+        .await
+        .map_err(Error::query)?;
+    let columns: Vec<String> = rows.iter().map(|row| row.get(0)).collect();
+    let mut parts = vec!["COUNT(*)::text".to_string()];
+    for column in ["id", "updated_at"] {
+        if columns.iter().any(|c| c == column) {
+            parts.push(format!("COALESCE(MAX({})::text, '')", column));
+        }
+    }
+    if parts.len() == 1 {
+        return Ok(None);
+    }
+    Ok(Some(format!("SELECT {} FROM backtest", parts.join(" || ':' || "))))
+}
+
+fn grouped_from_row(row: &Row) -> GroupedRecord {
+    GroupedRecord {
+        exchange: row.get("exchange"),
+        stake_currency: row.get("stake_currency"),
+        pairlist: row.get("pairlist"),
+        strategy: row.get("strategy"),
+        timeframe: row.get("timeframe"),
+        minimal_roi: row.get("minimal_roi"),
+        stoploss: row.get("stoploss"),
+        max_open_trades: row.get("max_open_trades"),
+        trailing_stop: row.get("trailing_stop"),
+        trailing_stop_positive: row.get("trailing_stop_positive"),
+        trailing_stop_positive_offset: row.get("trailing_stop_positive_offset"),
+        trailing_only_offset_is_reached: row.get("trailing_only_offset_is_reached"),
+        entry_pricing: row.get("entry_pricing"),
+        exit_pricing: row.get("exit_pricing"),
+        check_depth_of_market_enable: row.get("check_depth_of_market_enable"),
+        total_trades: row.get("total_trades"),
+        wins: row.get("wins"),
+        win_time: row.get("win_time"),
+        drawdown_perc: row.get("drawdown_perc"),
+        rejected_signals: row.get("rejected_signals"),
+        profit_total: row.get("profit_total"),
+        avg_monthly_profit: row.get("avg_monthly_profit"),
+        std_monthly_profit: row.get("std_monthly_profit"),
+        max_profit_month: row.get("max_profit_month"),
+        min_profit_month: row.get("min_profit_month"),
+        neg_months: row.get::<_, i64>("neg_months") as usize,
+        total_gain: row.get("total_gain"),
+        total_loss: row.get("total_loss"),
+        timeranges: row.get("timeranges"),
+        profits: row.get("profits"),
+    }
+}
+
+fn record_from_row(row: &Row) -> BacktestRecord {
+    BacktestRecord {
+        strategy: row.get("strategy"),
+        timeframe: row.get("timeframe"),
+        minimal_roi: row.get("minimal_roi"),
+        stoploss: row.get("stoploss"),
+        max_open_trades: row.get("max_open_trades"),
+        trailing_stop: row.get("trailing_stop"),
+        trailing_stop_positive: row.get("trailing_stop_positive"),
+        trailing_stop_positive_offset: row.get("trailing_stop_positive_offset"),
+        trailing_only_offset_is_reached: row.get("trailing_only_offset_is_reached"),
+        entry_pricing: row.get("entry_pricing"),
+        exit_pricing: row.get("exit_pricing"),
+        check_depth_of_market_enable: row.get("check_depth_of_market_enable"),
+        profit_total: row.get("profit_total"),
+        total_trades: row.get("total_trades"),
+        wins: row.get("wins"),
+        winner_holding_avg_s: row.get("winner_holding_avg_s"),
+        max_drawdown: row.get("max_drawdown"),
+        rejected_signals: row.get("rejected_signals"),
+        timerange: row.get("timerange"),
+        exchange: row.get("exchange"),
+        stake_currency: row.get("stake_currency"),
+        pairlist: row.get("pairlist"),
+    }
+}
+
+/// `EXCHANGE/MONEDA/PAIRLIST` de la fila mensual unida en `SELECT_PAIRS` y
+/// `SELECT_TRADES`.
+fn market_from_row(row: &Row) -> String {
+    format!(
+        "{}/{}/{}",
+        row.get::<_, String>("exchange"),
+        row.get::<_, String>("stake_currency"),
+        row.get::<_, String>("pairlist")
+    )
+}
+
+fn pair_from_row(row: &Row) -> PairDetail {
+    PairDetail {
+        market: market_from_row(row),
+        timerange: row.get("timerange"),
+        pair: row.get("pair"),
+        trades: row.get("trades"),
+        profit_mean: row.get("profit_mean"),
+        profit_total: row.get("profit_total"),
+        profit_total_abs: row.get("profit_total_abs"),
+        wins: row.get("wins"),
+        draws: row.get("draws"),
+        losses: row.get("losses"),
+        duration_avg: row.get("duration_avg"),
+    }
+}
+
+fn trade_from_row(row: &Row) -> TradeDetail {
+    TradeDetail {
+        market: market_from_row(row),
+        timerange: row.get("timerange"),
+        pair: row.get("pair"),
+        is_short: row.get("is_short"),
+        open_date: row.get("open_date"),
+        close_date: row.get("close_date"),
+        open_rate: row.get("open_rate"),
+        close_rate: row.get("close_rate"),
+        amount: row.get("amount"),
+        stake_amount: row.get("stake_amount"),
+        profit_ratio: row.get("profit_ratio"),
+        profit_abs: row.get("profit_abs"),
+        trade_duration: row.get("trade_duration"),
+        exit_reason: row.get("exit_reason"),
+        enter_tag: row.get("enter_tag"),
+    }
+}
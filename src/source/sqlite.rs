@@ -0,0 +1,224 @@
+// src/source/sqlite.rs
+
+use rusqlite::{params_from_iter, Connection, OpenFlags, Row};
+use std::path::{Path, PathBuf};
+
+use crate::backtest::model::{BacktestRecord, GridQuery};
+use crate::backtest::sql::FLAT_COLUMNS;
+use crate::error::{Error, Result};
+use crate::source::{file_watermark, BacktestSource};
+
+/// Lee una copia de la tabla `backtest` guardada en un fichero SQLite.
+#[derive(Debug, Clone)]
+pub struct SqliteSource {
+    path: PathBuf,
+}
+
+impl SqliteSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl BacktestSource for SqliteSource {
+    async fn fetch(
+        &self,
+        params: &GridQuery,
+        timeranges: &[String],
+    ) -> Result<Vec<BacktestRecord>> {
+        let path = self.path.clone();
+        let params = params.clone();
+        let timeranges = timeranges.to_vec();
+
+        // rusqlite es bloqueante: se ejecuta fuera de los hilos del runtime.
+        tokio::task::spawn_blocking(move || read_records(&path, &params, &timeranges))
+            .await
+            .map_err(Error::query)?
+    }
+
+    async fn available_timeranges(&self, params: &GridQuery) -> Result<Vec<String>> {
+        let path = self.path.clone();
+        let params = params.clone();
+
+        tokio::task::spawn_blocking(move || read_timeranges(&path, &params))
+            .await
+            .map_err(Error::query)?
+    }
+
+    /// En modo WAL las escrituras recientes están en el fichero `-wal`.
+    async fn watermark(&self) -> Result<Option<String>> {
+        let mut watermark = file_watermark(&self.path)?;
+        let mut wal = self.path.clone().into_os_string();
+        wal.push("-wal");
+        if let Ok(wal_watermark) = file_watermark(Path::new(&wal)) {
+            watermark.push('+');
+            watermark.push_str(&wal_watermark);
+        }
+        Ok(Some(watermark))
+    }
+}
+
+/// Condiciones `columna IN (?n, ...)` con sus valores enlazados en orden.
+#[derive(Default)]
+struct Conditions {
+    sql: Vec<String>,
+    values: Vec<String>,
+}
+
+impl Conditions {
+    fn market(params: &GridQuery) -> Self {
+        let upper = |values: &[String]| -> Vec<String> {
+            values.iter().map(|value| value.to_uppercase()).collect()
+        };
+        Self::default()
+            .any("exchange", upper(&params.exchanges))
+            .any("stake_currency", upper(&params.currencies))
+            .any("pairlist", upper(&params.pairlists))
+    }
+
+    fn any(mut self, column: &str, values: Vec<String>) -> Self {
+        let placeholders = (0..values.len())
+            .map(|i| format!("?{}", self.values.len() + i + 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.sql.push(format!("{} IN ({})", column, placeholders));
+        self.values.extend(values);
+        self
+    }
+}
+
+/// No abrir el fichero es un error de conexión; el resto, de consulta.
+fn open(path: &Path) -> Result<Connection> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(Error::connection)
+}
+
+fn read_timeranges(path: &Path, params: &GridQuery) -> Result<Vec<String>> {
+    let conn = open(path)?;
+    let conditions = Conditions::market(params);
+    let sql = format!(
+        "SELECT DISTINCT timerange FROM backtest WHERE {}",
+        conditions.sql.join(" AND ")
+    );
+
+    let mut stmt = conn.prepare(&sql).map_err(Error::query)?;
+    stmt.query_map(params_from_iter(conditions.values), |row| row.get(0))
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<String>>>())
+        .map_err(Error::query)
+}
+
+fn read_records(
+    path: &Path,
+    params: &GridQuery,
+    timeranges: &[String],
+) -> Result<Vec<BacktestRecord>> {
+    let conn = open(path)?;
+    let conditions = Conditions::market(params).any("timerange", timeranges.to_vec());
+    let sql = format!(
+        "SELECT {} FROM backtest WHERE {}",
+        FLAT_COLUMNS.join(", "),
+        conditions.sql.join(" AND ")
+    );
+    let values = conditions.values;
+
+    let mut stmt = conn.prepare(&sql).map_err(Error::query)?;
+    stmt.query_map(params_from_iter(values), record_from_row)
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(Error::query)
+}
+
+fn record_from_row(row: &Row) -> rusqlite::Result<BacktestRecord> {
+    Ok(BacktestRecord {
+        strategy: row.get("strategy")?,
+        timeframe: row.get("timeframe")?,
+        minimal_roi: row.get("minimal_roi")?,
+        stoploss: row.get("stoploss")?,
+        max_open_trades: row.get("max_open_trades")?,
+        trailing_stop: row.get("trailing_stop")?,
+        trailing_stop_positive: row.get("trailing_stop_positive")?,
+        trailing_stop_positive_offset: row.get("trailing_stop_positive_offset")?,
+        trailing_only_offset_is_reached: row.get("trailing_only_offset_is_reached")?,
+        entry_pricing: row.get("entry_pricing")?,
+        exit_pricing: row.get("exit_pricing")?,
+        check_depth_of_market_enable: row.get("check_depth_of_market_enable")?,
+        profit_total: row.get("profit_total")?,
+        total_trades: row.get("total_trades")?,
+        wins: row.get("wins")?,
+        winner_holding_avg_s: row.get("winner_holding_avg_s")?,
+        max_drawdown: row.get("max_drawdown")?,
+        rejected_signals: row.get("rejected_signals")?,
+        timerange: row.get("timerange")?,
+        exchange: row.get("exchange")?,
+        stake_currency: row.get("stake_currency")?,
+        pairlist: row.get("pairlist")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::model::AggregationMode;
+
+    #[tokio::test]
+    async fn reads_only_matching_rows() {
+        let path = std::env::temp_dir().join(format!("freqdash-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE backtest (
+                strategy TEXT, timeframe TEXT, minimal_roi TEXT, stoploss TEXT,
+                max_open_trades INTEGER, trailing_stop INTEGER,
+                trailing_stop_positive REAL, trailing_stop_positive_offset REAL,
+                trailing_only_offset_is_reached INTEGER, entry_pricing TEXT,
+                exit_pricing TEXT, check_depth_of_market_enable INTEGER,
+                profit_total REAL, total_trades INTEGER, wins INTEGER,
+                winner_holding_avg_s REAL, max_drawdown REAL, rejected_signals INTEGER,
+                timerange TEXT, exchange TEXT, stake_currency TEXT, pairlist TEXT
+            );
+            INSERT INTO backtest VALUES
+                ('A', '5m', '{}', '-0.1', 3, 0, 0.01, 0.02, 0, 'same', 'same', 0,
+                 0.05, 10, 6, NULL, 0.02, 1, '20240101-20240201', 'BINANCE', 'USDT', 'BTC'),
+                ('A', '5m', '{}', '-0.1', 3, 0, 0.01, 0.02, 0, 'same', 'same', 0,
+                 0.05, 10, 6, NULL, 0.02, 1, '20240101-20240201', 'KRAKEN', 'USDT', 'BTC'),
+                ('A', '5m', '{}', '-0.1', 3, 0, 0.01, 0.02, 0, 'same', 'same', 0,
+                 0.05, 10, 6, NULL, 0.02, 1, '20240301-20240401', 'BINANCE', 'USDT', 'BTC');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let query = GridQuery {
+            exchanges: vec!["binance".to_string()],
+            currencies: vec!["usdt".to_string()],
+            pairlists: vec!["btc".to_string()],
+            start_date: "2024-01-01".to_string(),
+            months: 2,
+            aggregation: AggregationMode::Client,
+            accumulation: Default::default(),
+            end_date: None,
+            bucket: Default::default(),
+            custom_ranges: Vec::new(),
+            coverage_policy: Default::default(),
+        };
+        let timeranges = vec![
+            "20240101-20240201".to_string(),
+            "20240201-20240301".to_string(),
+        ];
+        let source = SqliteSource::new(&path);
+        let records = source.fetch(&query, &timeranges).await.unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].timerange, "20240101-20240201");
+        assert_eq!(records[0].winner_holding_avg_s, None);
+        assert!(!records[0].trailing_stop);
+
+        let mut available = source.available_timeranges(&query).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        available.sort();
+        assert_eq!(available, vec!["20240101-20240201", "20240301-20240401"]);
+
+        // Sin fichero no hay conexión (solo lectura: no se crea uno vacío)
+        assert!(matches!(
+            source.fetch(&query, &timeranges).await,
+            Err(Error::Connection(_))
+        ));
+    }
+}